@@ -0,0 +1,76 @@
+use std::time::Instant;
+
+use futures::{stream, StreamExt, TryStreamExt};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{
+    trace::{elapsed_ms, record},
+    LanceDbVectorIndex, QueryOptions,
+};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, for many queries at once.
+    /// The queries are embedded with the batch embedding call of the model, `M::MAX_DOCUMENTS` queries per request.
+    /// At most `parallelism` LanceDB queries run concurrently.
+    /// The results are returned in the order of `queries`, one list of results per query.
+    /// # Example
+    /// ```
+    /// let results = vector_store_index
+    ///     .top_n_batch::<WordDefinition>(&["What is a zindle?", "What is a glarb?"], 5, 8)
+    ///     .await?;
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.top_n_batch",
+            skip_all,
+            fields(
+                table = self.table.name(),
+                query_count = queries.len(),
+                n = n,
+                parallelism = parallelism,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn top_n_batch<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        queries: &[&str],
+        n: usize,
+        parallelism: usize,
+    ) -> Result<Vec<Vec<(f64, String, T)>>, VectorStoreError> {
+        let start = Instant::now();
+        let mut embeddings = Vec::with_capacity(queries.len());
+
+        for batch in queries.chunks(M::MAX_DOCUMENTS.max(1)) {
+            embeddings.extend(
+                self.embed_texts(batch.iter().map(|query| query.to_string()))
+                    .await?,
+            );
+        }
+
+        if embeddings.len() != queries.len() {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Expected {} embeddings from the model, got {}",
+                    queries.len(),
+                    embeddings.len()
+                )
+                .into(),
+            ));
+        }
+
+        let results = stream::iter(queries.iter().zip(embeddings))
+            .map(|(query, embedding)| async move {
+                self.seizzyh_embedding(&embedding.vec, Some(query), n, QueryOptions::default())
+                    .await
+            })
+            .buffered(parallelism.max(1))
+            .try_collect()
+            .await?;
+        record!(latency_ms = elapsed_ms(start));
+
+        Ok(results)
+    }
+}
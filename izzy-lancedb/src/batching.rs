@@ -0,0 +1,128 @@
+use izzy::embeddings::embedding::EmbeddingModel;
+
+use crate::LanceDbVectorIndex;
+
+/// Limits of the embedding requests made during ingestion, e.g. to stay below the caps of the embedding provider.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::embedding_batching`.
+/// When unset, the limits are taken from the embedding model (see `EmbeddingBatching::for_model`):
+/// set the token cap of the provider explicitly, e.g. 300,000 tokens for OpenAI.
+/// Tokens are counted with the tokenizer of the index (see `LanceDbVectorIndex::tokenizer`).
+/// # Example
+/// ```
+/// use izzy_lancedb::EmbeddingBatching;
+///
+/// let vector_store_index = vector_store_index.embedding_batching(
+///     EmbeddingBatching::default().max_texts(512).max_tokens(100_000),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EmbeddingBatching {
+    max_texts: Option<usize>,
+    max_tokens: Option<usize>,
+}
+
+impl EmbeddingBatching {
+    /// Limits declared by the embedding model `M`: at most `M::MAX_DOCUMENTS` texts per request, without a token cap.
+    pub fn for_model<M: EmbeddingModel>() -> Self {
+        Self {
+            max_texts: Some(M::MAX_DOCUMENTS),
+            max_tokens: None,
+        }
+    }
+
+    /// Sets the maximum number of texts per embedding request. The default is `M::MAX_DOCUMENTS`.
+    pub fn max_texts(mut self, max_texts: usize) -> Self {
+        self.max_texts = Some(max_texts);
+        self
+    }
+
+    /// Sets the maximum number of tokens per embedding request, summed over its texts. There is no limit by default.
+    /// A text over the limit on its own is embedded in a request of its own.
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the limits of the embedding requests made during ingestion.
+    /// The default is `EmbeddingBatching::for_model::<M>()`.
+    pub fn embedding_batching(mut self, embedding_batching: EmbeddingBatching) -> Self {
+        self.embedding_batching = Some(embedding_batching);
+        self
+    }
+
+    /// Split `texts` into the batches of texts embedded together, within the embedding batching limits of the index.
+    pub(crate) fn embedding_batches(&self, texts: Vec<String>) -> Vec<Vec<String>> {
+        let batching = self
+            .embedding_batching
+            .unwrap_or_else(EmbeddingBatching::for_model::<M>);
+
+        batches(
+            texts,
+            batching.max_texts.unwrap_or(M::MAX_DOCUMENTS),
+            batching.max_tokens,
+            |text| self.count_tokens(text),
+        )
+    }
+}
+
+/// Split `texts` into consecutive batches of at most `max_texts` texts and `max_tokens` tokens,
+/// counted with `count_tokens`.
+fn batches(
+    texts: Vec<String>,
+    max_texts: usize,
+    max_tokens: Option<usize>,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Vec<Vec<String>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_tokens = 0;
+
+    for text in texts {
+        let tokens = max_tokens.map_or(0, |_| count_tokens(&text));
+
+        let full = batch.len() >= max_texts.max(1)
+            || max_tokens.is_some_and(|max_tokens| batch_tokens + tokens > max_tokens);
+        if full && !batch.is_empty() {
+            batches.push(std::mem::take(&mut batch));
+            batch_tokens = 0;
+        }
+
+        batch.push(text);
+        batch_tokens += tokens;
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::batches;
+
+    #[test]
+    fn test_batches() {
+        let texts = ["aaaa", "bb", "cccccc", "d", "ee"]
+            .map(String::from)
+            .to_vec();
+        let count_tokens = |text: &str| text.len();
+
+        assert_eq!(
+            batches(texts.clone(), 2, None, count_tokens),
+            vec![vec!["aaaa", "bb"], vec!["cccccc", "d"], vec!["ee"]]
+        );
+        assert_eq!(
+            batches(texts.clone(), 10, Some(6), count_tokens),
+            vec![vec!["aaaa", "bb"], vec!["cccccc"], vec!["d", "ee"]]
+        );
+        assert_eq!(
+            batches(texts, 10, Some(3), count_tokens),
+            vec![vec!["aaaa"], vec!["bb"], vec!["cccccc"], vec!["d", "ee"]]
+        );
+        assert!(batches(vec![], 10, None, count_tokens).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    sync::{izzy, Mutex},
+    time::{Duration, Instant},
+};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::LanceDbVectorIndex;
+
+/// LRU cache of query embeddings, keyed by query text.
+pub(crate) struct EmbeddingCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Embedding, insertion time and last use of each query.
+    entries: HashMap<String, (Vec<f64>, Instant, u64)>,
+    /// Incremented on each use, to find the least recently used entry.
+    tick: u64,
+}
+
+impl EmbeddingCache {
+    pub(crate) fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            capacity,
+            ttl,
+            state: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, query: &str, now: Instant) -> Option<Vec<f64>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+
+        let (embedding, inserted_at, last_used) = state.entries.get_mut(query)?;
+
+        if self
+            .ttl
+            .is_some_and(|ttl| now.duration_since(*inserted_at) >= ttl)
+        {
+            state.entries.remove(query);
+            return None;
+        }
+
+        *last_used = tick;
+        Some(embedding.clone())
+    }
+
+    pub(crate) fn insert(&self, query: &str, embedding: Vec<f64>, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(query) {
+            // Linear scan: the cache is meant to hold a modest number of queries.
+            if let Some(least_recently_used) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, _, last_used))| *last_used)
+                .map(|(query, _)| query.clone())
+            {
+                state.entries.remove(&least_recently_used);
+            }
+        }
+
+        state
+            .entries
+            .insert(query.to_string(), (embedding, now, tick));
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets an LRU cache of query embeddings in front of the embedding model, holding at most `capacity` queries.
+    /// Seizzyhes for a query already in the cache skip the embedding model call.
+    /// Cached embeddings expire after `ttl`, if set.
+    pub fn embedding_cache(mut self, capacity: usize, ttl: Option<Duration>) -> Self {
+        self.embedding_cache = Some(izzy::new(EmbeddingCache::new(capacity, ttl)));
+        self
+    }
+
+    /// Embed the seizzyh `query`, using the embedding cache if set.
+    pub(crate) async fn embed_seizzyh_query(
+        &self,
+        query: &str,
+    ) -> Result<Vec<f64>, VectorStoreError> {
+        let Some(cache) = &self.embedding_cache else {
+            return Ok(self.embed_text(query).await?.vec);
+        };
+
+        if let Some(embedding) = cache.get(query, Instant::now()) {
+            return Ok(embedding);
+        }
+
+        let embedding = self.embed_text(query).await?.vec;
+        cache.insert(query, embedding.clone(), Instant::now());
+
+        Ok(embedding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::EmbeddingCache;
+
+    #[test]
+    fn test_embedding_cache_lru() {
+        let cache = EmbeddingCache::new(2, None);
+        let now = Instant::now();
+
+        cache.insert("a", vec![1.0], now);
+        cache.insert("b", vec![2.0], now);
+        assert_eq!(cache.get("a", now), Some(vec![1.0]));
+
+        // "b" is the least recently used query.
+        cache.insert("c", vec![3.0], now);
+        assert_eq!(cache.get("b", now), None);
+        assert_eq!(cache.get("a", now), Some(vec![1.0]));
+        assert_eq!(cache.get("c", now), Some(vec![3.0]));
+    }
+
+    #[test]
+    fn test_embedding_cache_ttl() {
+        let cache = EmbeddingCache::new(2, Some(Duration::from_secs(60)));
+        let now = Instant::now();
+
+        cache.insert("a", vec![1.0], now);
+        assert_eq!(
+            cache.get("a", now + Duration::from_secs(30)),
+            Some(vec![1.0])
+        );
+        assert_eq!(cache.get("a", now + Duration::from_secs(60)), None);
+    }
+}
@@ -0,0 +1,298 @@
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    utils::{id_to_string, QueryToJson},
+    Filter, LanceDbVectorIndex, QueryOptions,
+};
+
+/// Column containing the id of the document a chunk belongs to.
+pub const PARENT_ID_COLUMN: &str = "parent_id";
+/// Column containing the position of a chunk within its parent document, starting at 0.
+pub const CHUNK_INDEX_COLUMN: &str = "chunk_index";
+/// Column containing the number of chunks of the parent document.
+pub const TOTAL_CHUNKS_COLUMN: &str = "total_chunks";
+
+/// Position of a chunk within its parent document.
+/// Write these values to the `parent_id`, `chunk_index` and `total_chunks` columns when ingesting chunked documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkPosition {
+    pub parent_id: String,
+    pub chunk_index: i32,
+    pub total_chunks: i32,
+}
+
+/// Positions of the `total_chunks` chunks of the document `parent_id`, in document order.
+pub fn chunk_positions(parent_id: &str, total_chunks: usize) -> Vec<ChunkPosition> {
+    (0..total_chunks)
+        .map(|chunk_index| ChunkPosition {
+            parent_id: parent_id.to_string(),
+            chunk_index: chunk_index as i32,
+            total_chunks: total_chunks as i32,
+        })
+        .collect()
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Get the chunks surrounding the chunk with id `id`, in document order.
+    /// Returns the chunks of the same parent document whose chunk index is at most `window` away
+    /// from the chunk index of `id`, including the chunk `id` itself.
+    /// The filter of the seizzyh params applies, and soft deleted and expired chunks are skipped.
+    /// The result is a list of tuples of the form (id, document).
+    /// Requires the `parent_id` and `chunk_index` columns (see `SchemaOptions::chunk_fields`).
+    /// # Example
+    /// ```
+    /// let passage = vector_store_index
+    ///     .neighbors::<serde_json::Value>("doc1#3", 2)
+    ///     .await?;
+    /// ```
+    pub async fn neighbors<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        id: &str,
+        window: usize,
+    ) -> Result<Vec<(String, T)>, VectorStoreError> {
+        let chunk = self
+            .table
+            .query()
+            .only_if(self.combined_filter_with(self.id_filter(id)?))
+            .select(Select::Columns(vec![
+                PARENT_ID_COLUMN.to_string(),
+                CHUNK_INDEX_COLUMN.to_string(),
+            ]))
+            .limit(1)
+            .execute_query()
+            .await?
+            .pop()
+            .ok_or(VectorStoreError::MissingIdError(id.to_string()))?;
+
+        let (Some(Value::String(parent_id)), Some(chunk_index)) = (
+            chunk.get(PARENT_ID_COLUMN),
+            chunk.get(CHUNK_INDEX_COLUMN).and_then(Value::as_i64),
+        ) else {
+            return Err(VectorStoreError::DatastoreError(
+                format!("Record {id} has no chunk position").into(),
+            ));
+        };
+
+        self.chunk_range(
+            parent_id,
+            chunk_index - window as i64,
+            chunk_index + window as i64,
+        )
+        .await
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, but each match is expanded with the `window`
+    /// chunks before and after it in its parent document.
+    /// Overlapping and adjacent windows of the same document are merged, so each chunk is returned at most once.
+    /// The passages are ordered by their best match, and the score of a passage is the score of its best match.
+    /// Requires the `parent_id` and `chunk_index` columns (see `SchemaOptions::chunk_fields`).
+    /// # Example
+    /// ```
+    /// let passages = vector_store_index
+    ///     .top_n_expanded::<serde_json::Value>("My boss says I zindle too much, what does that mean?", 3, 1)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_expanded<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        window: usize,
+    ) -> Result<Vec<Passage<T>>, VectorStoreError> {
+        let windows = self
+            .seizzyh::<Value>(query, n, QueryOptions::default())
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (score, id, value))| {
+                match (
+                    value.get(PARENT_ID_COLUMN),
+                    value.get(CHUNK_INDEX_COLUMN).and_then(Value::as_i64),
+                ) {
+                    (Some(Value::String(parent_id)), Some(chunk_index)) => Ok(ChunkWindow {
+                        rank,
+                        score,
+                        parent_id: parent_id.to_string(),
+                        start: chunk_index - window as i64,
+                        end: chunk_index + window as i64,
+                    }),
+                    _ => Err(VectorStoreError::DatastoreError(
+                        format!("Record {id} has no chunk position").into(),
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut passages = Vec::new();
+
+        for window in merge_windows(windows) {
+            passages.push(Passage {
+                score: window.score,
+                chunks: self
+                    .chunk_range(&window.parent_id, window.start, window.end)
+                    .await?,
+                parent_id: window.parent_id,
+            });
+        }
+
+        Ok(passages)
+    }
+
+    /// Get the chunks of the document `parent_id` with a chunk index between `start` and `end` (inclusive), in document order.
+    /// The `parent_id` and `chunk_index` columns are read even if they are not selected (see `SeizzyhParams::select_columns`),
+    /// and removed from the documents in that case.
+    async fn chunk_range<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        parent_id: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<(String, T)>, VectorStoreError> {
+        let mut columns = self.payload_columns().await?;
+        let added = [PARENT_ID_COLUMN, CHUNK_INDEX_COLUMN]
+            .into_iter()
+            .filter(|column| !columns.iter().any(|selected| selected == column))
+            .collect::<Vec<_>>();
+        columns.extend(added.iter().map(|column| column.to_string()));
+
+        let mut chunks = self
+            .table
+            .query()
+            .only_if(
+                self.combined_filter_with(
+                    Filter::eq(PARENT_ID_COLUMN, parent_id)
+                        .and(Filter::gte(CHUNK_INDEX_COLUMN, start))
+                        .and(Filter::lte(CHUNK_INDEX_COLUMN, end)),
+                ),
+            )
+            .select(Select::Columns(columns))
+            .limit((end - start + 1).max(0) as usize)
+            .execute_query()
+            .await?;
+
+        chunks.sort_by_key(|chunk| chunk.get(CHUNK_INDEX_COLUMN).and_then(Value::as_i64));
+
+        chunks
+            .into_iter()
+            .map(|mut chunk| {
+                self.stamp_id(&mut chunk);
+
+                if let Some(chunk) = chunk.as_object_mut() {
+                    for column in &added {
+                        chunk.remove(*column);
+                    }
+                }
+
+                Ok((
+                    chunk
+                        .get(&self.id_field)
+                        .and_then(id_to_string)
+                        .unwrap_or_default(),
+                    self.document(chunk)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Contiguous chunks of a parent document, returned by `top_n_expanded`.
+#[derive(Debug, Clone)]
+pub struct Passage<T> {
+    /// Score of the best match contained in the passage.
+    pub score: f64,
+    /// Id of the parent document of the chunks.
+    pub parent_id: String,
+    /// Chunks of the passage in document order, as tuples of the form (id, document).
+    pub chunks: Vec<(String, T)>,
+}
+
+/// Range of chunk indices of a parent document around a match.
+#[derive(Debug, Clone, PartialEq)]
+struct ChunkWindow {
+    /// Rank of the best match in the window.
+    rank: usize,
+    /// Score of the best match in the window.
+    score: f64,
+    parent_id: String,
+    start: i64,
+    end: i64,
+}
+
+/// Merge the overlapping and adjacent windows of each parent document.
+/// The merged windows are ordered by the rank of their best match.
+fn merge_windows(mut windows: Vec<ChunkWindow>) -> Vec<ChunkWindow> {
+    windows.sort_by(|a, b| (&a.parent_id, a.start).cmp(&(&b.parent_id, b.start)));
+
+    let mut merged: Vec<ChunkWindow> = Vec::new();
+
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if last.parent_id == window.parent_id && window.start <= last.end + 1 => {
+                last.end = last.end.max(window.end);
+                if window.rank < last.rank {
+                    last.rank = window.rank;
+                    last.score = window.score;
+                }
+            }
+            _ => merged.push(window),
+        }
+    }
+
+    merged.sort_by_key(|window| window.rank);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_positions, merge_windows, ChunkPosition, ChunkWindow};
+
+    fn window(rank: usize, parent_id: &str, start: i64, end: i64) -> ChunkWindow {
+        ChunkWindow {
+            rank,
+            score: rank as f64,
+            parent_id: parent_id.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_chunk_positions() {
+        assert_eq!(
+            chunk_positions("doc0", 2),
+            vec![
+                ChunkPosition {
+                    parent_id: "doc0".to_string(),
+                    chunk_index: 0,
+                    total_chunks: 2,
+                },
+                ChunkPosition {
+                    parent_id: "doc0".to_string(),
+                    chunk_index: 1,
+                    total_chunks: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_windows() {
+        let merged = merge_windows(vec![
+            window(0, "doc1", 4, 6),
+            window(1, "doc0", 0, 2),
+            window(2, "doc1", 1, 3),
+            window(3, "doc1", 9, 11),
+            window(4, "doc0", 1, 3),
+        ]);
+
+        assert_eq!(
+            merged,
+            vec![
+                window(0, "doc1", 1, 6),
+                window(1, "doc0", 0, 3),
+                window(3, "doc1", 9, 11),
+            ]
+        );
+    }
+}
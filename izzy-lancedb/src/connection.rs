@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use izzy::vector_store::VectorStoreError;
+
+use crate::lancedb_to_izzy_error;
+
+/// Builder of the LanceDB connection of a `LanceDbVectorIndex`.
+/// # Example
+/// ```
+/// use izzy_lancedb::ConnectionBuilder;
+///
+/// // Safe concurrent writes on plain S3, with a DynamoDB table as the commit store.
+/// let db = ConnectionBuilder::new("s3://my-bucket/lancedb")
+///     .dynamodb_commit_store("lancedb-commits")
+///     .execute()
+///     .await?;
+///
+/// // S3-compatible object store with a custom endpoint.
+/// let table = ConnectionBuilder::new("s3://my-bucket/lancedb")
+///     .storage_options([
+///         ("aws_endpoint", "http://localhost:9000"),
+///         ("aws_access_key_id", "minio"),
+///         ("aws_secret_access_key", "minio123"),
+///         ("allow_http", "true"),
+///     ])
+///     .open_table("definitions")
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionBuilder {
+    uri: String,
+    dynamodb_commit_store: Option<String>,
+    read_consistency_interval: Option<Duration>,
+    storage_options: Vec<(String, String)>,
+    #[cfg(feature = "remote")]
+    api_key: Option<String>,
+    #[cfg(feature = "remote")]
+    region: Option<String>,
+    #[cfg(feature = "remote")]
+    host_override: Option<String>,
+}
+
+impl ConnectionBuilder {
+    /// Connect to the database at `uri`, e.g. a local path, an `s3://` uri or a `db://` LanceDB Cloud uri.
+    pub fn new(uri: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            dynamodb_commit_store: None,
+            read_consistency_interval: None,
+            storage_options: Vec::new(),
+            #[cfg(feature = "remote")]
+            api_key: None,
+            #[cfg(feature = "remote")]
+            region: None,
+            #[cfg(feature = "remote")]
+            host_override: None,
+        }
+    }
+
+    /// Use the DynamoDB table `table_name` as the external commit store of the database, as supported by Lance.
+    /// Plain S3 does not support the atomic commits required by concurrent writers: without a commit store,
+    /// concurrent writes to an S3 table can lose data. Only supported for `s3://` uris.
+    /// See [LanceDB on S3](https://lancedb.github.io/lancedb/guides/storage/#aws-s3) for the required DynamoDB table schema.
+    pub fn dynamodb_commit_store(mut self, table_name: &str) -> Self {
+        self.dynamodb_commit_store = Some(table_name.to_string());
+        self
+    }
+
+    /// Sets the interval after which the tables of the connection check for the writes of other processes.
+    /// `Duration::ZERO` checks before every read, for a strong consistency.
+    /// By default, tables never check: call `LanceDbVectorIndex::checkout_latest` to see the writes of other processes,
+    /// or set a strong read consistency on the index (see `ReadConsistency`).
+    pub fn read_consistency_interval(mut self, read_consistency_interval: Duration) -> Self {
+        self.read_consistency_interval = Some(read_consistency_interval);
+        self
+    }
+
+    /// Sets the API key of the LanceDB Cloud database. Only used with `db://` uris.
+    /// Requires the `remote` feature of this crate.
+    #[cfg(feature = "remote")]
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Sets the region of the LanceDB Cloud database, e.g. `us-east-1`. Only used with `db://` uris.
+    /// Requires the `remote` feature of this crate.
+    #[cfg(feature = "remote")]
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    /// Sets the host of the LanceDB Cloud API, instead of the host derived from the region,
+    /// e.g. for LanceDB Enterprise. Requires the `remote` feature of this crate.
+    #[cfg(feature = "remote")]
+    pub fn host_override(mut self, host_override: &str) -> Self {
+        self.host_override = Some(host_override.to_string());
+        self
+    }
+
+    /// Sets the object store option `key` to `value`, e.g. the credentials, region or endpoint of the S3, GCS or Azure store.
+    /// Options override the corresponding environment variables.
+    /// See [LanceDB storage](https://lancedb.github.io/lancedb/guides/storage/) for the supported options.
+    pub fn storage_option(mut self, key: &str, value: &str) -> Self {
+        self.storage_options
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the object store options of `storage_options`, e.g. parsed from a configuration file.
+    /// See `storage_option`.
+    pub fn storage_options<K: Into<String>, V: Into<String>>(
+        mut self,
+        storage_options: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        self.storage_options.extend(
+            storage_options
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+        self
+    }
+
+    /// Connect to the database.
+    pub async fn execute(self) -> Result<lancedb::Connection, VectorStoreError> {
+        let mut builder =
+            lancedb::connect(&self.resolved_uri()?).storage_options(self.storage_options.clone());
+
+        if let Some(read_consistency_interval) = self.read_consistency_interval {
+            builder = builder.read_consistency_interval(read_consistency_interval);
+        }
+
+        #[cfg(feature = "remote")]
+        {
+            if let Some(api_key) = &self.api_key {
+                builder = builder.api_key(api_key);
+            }
+            if let Some(region) = &self.region {
+                builder = builder.region(region);
+            }
+            if let Some(host_override) = &self.host_override {
+                builder = builder.host_override(host_override);
+            }
+        }
+
+        builder.execute().await.map_err(lancedb_to_izzy_error)
+    }
+
+    /// Connect to the database and open its table named `name`, ready for `LanceDbVectorIndex::new`.
+    /// The table reads and writes with the object store options of the connection.
+    pub async fn open_table(self, name: &str) -> Result<lancedb::Table, VectorStoreError> {
+        self.execute()
+            .await?
+            .open_table(name)
+            .execute()
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Uri passed to LanceDB, with the commit store configuration.
+    fn resolved_uri(&self) -> Result<String, VectorStoreError> {
+        let Some(table_name) = &self.dynamodb_commit_store else {
+            return Ok(self.uri.clone());
+        };
+
+        let path = self.uri.strip_prefix("s3://").ok_or_else(|| {
+            VectorStoreError::DatastoreError(
+                format!(
+                    "A DynamoDB commit store requires an s3:// uri, got {}",
+                    self.uri
+                )
+                .into(),
+            )
+        })?;
+
+        let separator = if path.contains('?') { '&' } else { '?' };
+
+        Ok(format!(
+            "s3+ddb://{path}{separator}ddbTableName={table_name}"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConnectionBuilder;
+
+    #[test]
+    fn test_resolved_uri() {
+        assert_eq!(
+            ConnectionBuilder::new("data/lancedb-store")
+                .resolved_uri()
+                .unwrap(),
+            "data/lancedb-store"
+        );
+        assert_eq!(
+            ConnectionBuilder::new("s3://bucket/path")
+                .dynamodb_commit_store("commits")
+                .resolved_uri()
+                .unwrap(),
+            "s3+ddb://bucket/path?ddbTableName=commits"
+        );
+        assert!(ConnectionBuilder::new("gs://bucket/path")
+            .dynamodb_commit_store("commits")
+            .resolved_uri()
+            .is_err());
+    }
+}
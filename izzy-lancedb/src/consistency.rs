@@ -0,0 +1,52 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex};
+
+/// Read consistency of a `LanceDbVectorIndex`, when other processes write to its table.
+/// See [LanceDB consistency](https://lancedb.github.io/lancedb/guides/tables/#consistency) for more information.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReadConsistency {
+    /// Queries read the version of the table loaded by LanceDB, refreshed according to the read consistency
+    /// interval of the connection (see `ConnectionBuilder::read_consistency_interval`).
+    /// Call `LanceDbVectorIndex::checkout_latest` to refresh the table explicitly. This is the default.
+    #[default]
+    Eventual,
+    /// Each query first checks out the latest version of the table, at the cost of a read of the table manifest.
+    Strong,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the read consistency of the index. The default is `ReadConsistency::Eventual`.
+    pub fn read_consistency(mut self, read_consistency: ReadConsistency) -> Self {
+        self.read_consistency = read_consistency;
+        self
+    }
+
+    /// Check out the latest version of the table, to see the writes of other processes.
+    /// # Example
+    /// ```
+    /// vector_store_index.checkout_latest().await?;
+    /// ```
+    pub async fn checkout_latest(&self) -> Result<(), VectorStoreError> {
+        self.table
+            .checkout_latest()
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Table to check out to its latest version before each query, with a strong read consistency.
+    pub(crate) fn latest_table(&self) -> Option<&lancedb::Table> {
+        match self.read_consistency {
+            ReadConsistency::Strong => Some(&self.table),
+            ReadConsistency::Eventual => None,
+        }
+    }
+
+    /// Check out the latest version of the table if the read consistency is strong.
+    pub(crate) async fn ensure_consistency(&self) -> Result<(), VectorStoreError> {
+        match self.latest_table() {
+            Some(_) => self.checkout_latest().await,
+            None => Ok(()),
+        }
+    }
+}
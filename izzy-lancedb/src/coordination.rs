@@ -0,0 +1,193 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        izzy,
+    },
+    time::Duration,
+};
+
+use izzy::embeddings::embedding::EmbeddingModel;
+use tokio::{
+    sync::{Notify, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    task::JoinHandle,
+};
+
+use crate::{trace::warning, LanceDbVectorIndex};
+
+/// Coordinates `optimize` with the bulk ingests and the pause signals of a `LanceDbVectorIndex`,
+/// so that compactions do not conflict with the writes of a heavy load job.
+#[derive(Default)]
+pub(crate) struct OptimizeGate {
+    /// Held shared by the bulk ingests, and exclusively by `optimize`.
+    lock: RwLock<()>,
+    /// Number of `pause_optimize` calls not yet matched by a `resume_optimize`.
+    pauses: AtomicUsize,
+    resumed: Notify,
+}
+
+impl OptimizeGate {
+    /// Wait until optimizations are resumed and no bulk ingest is in flight, and hold off bulk ingests until the guard drops.
+    /// The pauses are checked again once the lock is held, as `pause_optimize` may be called while waiting for it.
+    pub(crate) async fn optimize(&self) -> RwLockWriteGuard<'_, ()> {
+        loop {
+            loop {
+                let resumed = self.resumed.notified();
+                if self.pauses.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                resumed.await;
+            }
+
+            let guard = self.lock.write().await;
+            if self.pauses.load(Ordering::SeqCst) == 0 {
+                return guard;
+            }
+        }
+    }
+
+    fn pause(&self) {
+        self.pauses.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn resume(&self) {
+        let resumed = self
+            .pauses
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pauses| {
+                pauses.checked_sub(1)
+            });
+
+        if resumed == Ok(1) {
+            self.resumed.notify_waiters();
+        }
+    }
+}
+
+/// Guard of a bulk ingest, returned by `bulk_ingest`: `optimize` waits until every guard is dropped.
+pub struct BulkIngest<'a> {
+    _guard: RwLockReadGuard<'a, ()>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Mark the start of a bulk ingest, ended when the returned guard is dropped.
+    /// `optimize` waits until no bulk ingest is in flight, so that the compaction does not repeatedly conflict
+    /// with the writes of the ingest and retry. Bulk ingests started while an optimization is running wait until it completes.
+    /// `IngestionPipeline::run` and `insert_records` hold a guard for their whole duration.
+    /// This coordinates the writers of this index only: use `acquire_writer` to coordinate service instances.
+    /// # Example
+    /// ```
+    /// let ingest = vector_store_index.bulk_ingest().await;
+    ///
+    /// for record_batch in record_batches {
+    ///     vector_store_index.insert(vec![record_batch]).await?;
+    /// }
+    ///
+    /// drop(ingest);
+    /// ```
+    pub async fn bulk_ingest(&self) -> BulkIngest<'_> {
+        BulkIngest {
+            _guard: self.optimize_gate.lock.read().await,
+        }
+    }
+
+    /// Hold off `optimize` until `resume_optimize` is called, e.g. before a heavy load job in this process.
+    /// The pause only applies to this index in this process: use `acquire_writer` to coordinate several processes.
+    /// Optimizations already running are not interrupted. Calls are counted: optimizations resume once every
+    /// `pause_optimize` is matched by a `resume_optimize`.
+    pub fn pause_optimize(&self) {
+        self.optimize_gate.pause();
+    }
+
+    /// Resume the optimizations held off by `pause_optimize`.
+    pub fn resume_optimize(&self) {
+        self.optimize_gate.resume();
+    }
+
+    /// Returns true if optimizations are held off by `pause_optimize`.
+    pub fn is_optimize_paused(&self) -> bool {
+        self.optimize_gate.pauses.load(Ordering::SeqCst) > 0
+    }
+
+    /// Spawn a task optimizing the table every `interval`, on the Tokio runtime.
+    /// Each optimization waits for the in-flight bulk ingests and for `resume_optimize` (see `bulk_ingest`).
+    /// Failed optimizations are logged and retried at the next interval. Abort the returned handle to stop the task.
+    /// # Example
+    /// ```
+    /// use std::{sync::izzy, time::Duration};
+    ///
+    /// let vector_store_index = izzy::new(vector_store_index);
+    /// let auto_optimize = vector_store_index.clone().spawn_auto_optimize(Duration::from_secs(3600));
+    /// ```
+    pub fn spawn_auto_optimize(self: izzy<Self>, interval: Duration) -> JoinHandle<()>
+    where
+        M: 'static,
+    {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                if self.is_closed() {
+                    return;
+                }
+
+                if let Err(e) = self.optimize().await {
+                    warning!(
+                        "Failed to optimize table {}, retrying at the next interval: {e}",
+                        self.table.name()
+                    );
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::OptimizeGate;
+
+    #[tokio::test]
+    async fn test_optimize_gate() {
+        let gate = OptimizeGate::default();
+
+        let ingest = gate.lock.read().await;
+        assert!(timeout(Duration::from_millis(10), gate.optimize())
+            .await
+            .is_err());
+        drop(ingest);
+        assert!(timeout(Duration::from_millis(10), gate.optimize())
+            .await
+            .is_ok());
+
+        gate.pause();
+        gate.pause();
+        gate.resume();
+        assert!(timeout(Duration::from_millis(10), gate.optimize())
+            .await
+            .is_err());
+        gate.resume();
+        gate.resume();
+        assert!(timeout(Duration::from_millis(10), gate.optimize())
+            .await
+            .is_ok());
+
+        // A pause while waiting for the bulk ingests holds off the optimization.
+        let ingest = gate.lock.read().await;
+        let optimize = gate.optimize();
+        tokio::pin!(optimize);
+        assert!(timeout(Duration::from_millis(10), &mut optimize)
+            .await
+            .is_err());
+        gate.pause();
+        drop(ingest);
+        assert!(timeout(Duration::from_millis(10), &mut optimize)
+            .await
+            .is_err());
+        gate.resume();
+        assert!(timeout(Duration::from_millis(10), &mut optimize)
+            .await
+            .is_ok());
+    }
+}
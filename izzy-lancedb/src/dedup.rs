@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    chunks::PARENT_ID_COLUMN,
+    utils::{scan_all, QueryToJson},
+    Filter, IdGeneration, LanceDbVectorIndex,
+};
+
+/// Name of the column containing the hash of the text of a chunk, written by the ingestion pipeline.
+pub const CONTENT_HASH_COLUMN: &str = "content_hash";
+
+/// What the ingestion pipeline does with the chunks whose content hash already exists among the chunks
+/// of the same parent document in the table, e.g. the unchanged sections of a re-crawled page
+/// (see `IngestionPipeline::dedup`). Identical chunks of other documents are not duplicates.
+/// Duplicate chunks within an ingested document are always skipped, before the chunks are numbered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dedup {
+    /// Skip the chunk, keeping the existing row. The chunk is not embedded.
+    Skip,
+    /// Replace the existing row with the chunk, with a merge insert on the parent id and the content hash,
+    /// e.g. to update the position of a chunk that moved within its document. Once every chunk of a document
+    /// is written, its chunks that are no longer part of it are deleted.
+    Upsert,
+}
+
+/// Hash of the text of a chunk, written to the `content_hash` column.
+pub(crate) fn content_hash(text: &str) -> String {
+    IdGeneration::ContentHash.generate(text)
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Pairs of parent id and content hash among `chunks` of the rows of the table,
+    /// including the rows skipped by the queries of the index.
+    pub(crate) async fn existing_chunks(
+        &self,
+        chunks: &[(&str, &str)],
+    ) -> Result<HashSet<(String, String)>, VectorStoreError> {
+        if chunks.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let filter = Filter::is_in(
+            PARENT_ID_COLUMN,
+            chunks.iter().map(|(parent_id, _)| *parent_id),
+        )
+        .and(Filter::is_in(
+            CONTENT_HASH_COLUMN,
+            chunks.iter().map(|(_, content_hash)| *content_hash),
+        ));
+
+        // The filter also matches other pairs of these parents and hashes: every matching row is read.
+        Ok(scan_all(&self.table)
+            .only_if(filter.to_string())
+            .select(Select::Columns(vec![
+                PARENT_ID_COLUMN.to_string(),
+                CONTENT_HASH_COLUMN.to_string(),
+            ]))
+            .execute_query_with(self.query_settings())
+            .await?
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row.get(PARENT_ID_COLUMN)?.as_str()?.to_string(),
+                    row.get(CONTENT_HASH_COLUMN)?.as_str()?.to_string(),
+                ))
+            })
+            .filter(|(parent_id, content_hash)| {
+                chunks.contains(&(parent_id.as_str(), content_hash.as_str()))
+            })
+            .collect())
+    }
+
+    /// Delete the chunks of the documents `parent_ids` whose content hash is not among the `content_hashes`
+    /// just written for their document.
+    pub(crate) async fn delete_stale_chunks(
+        &self,
+        content_hashes: &[(String, Vec<String>)],
+    ) -> Result<(), VectorStoreError> {
+        let Some(stale) = content_hashes
+            .iter()
+            .map(|(parent_id, content_hashes)| {
+                let stale = Filter::eq(PARENT_ID_COLUMN, parent_id.as_str());
+
+                match content_hashes.is_empty() {
+                    true => stale,
+                    false => stale.and(
+                        Filter::is_in(
+                            CONTENT_HASH_COLUMN,
+                            content_hashes.iter().map(String::as_str),
+                        )
+                        .not(),
+                    ),
+                }
+            })
+            .reduce(Filter::or)
+        else {
+            return Ok(());
+        };
+
+        self.delete(stale).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_hash;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash("zindle"), content_hash("zindle"));
+        assert_ne!(content_hash("zindle"), content_hash("zindle "));
+    }
+}
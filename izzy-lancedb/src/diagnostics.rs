@@ -0,0 +1,328 @@
+use std::{collections::BTreeMap, sync::izzy};
+
+use datafusion_physical_plan::{collect, display::DisplayableExecutionPlan};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Serialize;
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex};
+
+/// Diagnostics of the vector seizzyh performed for a query, returned by `query_diagnostics`.
+/// Read from the analyzed plan of the query: Lance does not report which IVF partitions were probed,
+/// nor how many candidates each of them contributed.
+#[derive(Debug, Clone)]
+pub struct QueryDiagnostics {
+    /// Name of the vector index used by the seizzyh, `None` if the seizzyh is a flat seizzyh.
+    pub vector_index: Option<String>,
+    /// Number of IVF partitions probed, as reported by the `ANNIvfPartition` operator of the plan.
+    /// `None` if the seizzyh does not use an IVF index.
+    pub nprobes: Option<usize>,
+    /// Number of candidates returned by the vector seizzyh, i.e. the output rows of the root operator of the plan.
+    pub candidates: usize,
+    /// Verbose physical plan of the query with the runtime statistics of each operator, as printed by LanceDB.
+    pub plan: String,
+}
+
+/// Node of a `QueryPlan`, i.e. an operator of the physical plan of the query.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlanNode {
+    /// Name of the operator, e.g. `ANNSubIndex`, `KNNVectorDistance` or `LanceScan`.
+    pub operator: String,
+    /// Parameters of the operator, as reported by LanceDB.
+    pub details: String,
+    /// Runtime statistics of the operator, e.g. `output_rows` or `elapsed_compute`, when the query is analyzed.
+    pub metrics: BTreeMap<String, String>,
+    pub children: Vec<PlanNode>,
+}
+
+/// Prefix of the names of the operators reading an ANN index, e.g. `ANNSubIndex` and `ANNIvfPartition`.
+const ANN_OPERATOR_PREFIX: &str = "ANN";
+/// Name of the operator computing the distances of the rows not covered by an ANN index.
+const FLAT_SCAN_OPERATOR: &str = "KNNVectorDistance";
+/// Name of the operator seizzyhing the sub-index of an ANN index, whose details name the index.
+const SUB_INDEX_OPERATOR: &str = "ANNSubIndex";
+/// Name of the operator selecting the IVF partitions to probe, whose details report `nprobes`.
+const IVF_PARTITION_OPERATOR: &str = "ANNIvfPartition";
+
+/// Physical plan of the vector seizzyh of `top_n`, returned by `explain`.
+/// `uses_vector_index` and `flat_scan` are derived from the operator names printed by Lance 0.22,
+/// which are not a stable interface and may change with the Lance version: `text` is always the plan as printed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryPlan {
+    /// Root operators of the plan.
+    pub nodes: Vec<PlanNode>,
+    /// Whether the seizzyh reads an ANN index, i.e. the plan has an `ANN*` operator.
+    pub uses_vector_index: bool,
+    /// Whether the seizzyh computes the distance of rows not covered by an ANN index, i.e. a flat seizzyh
+    /// of the whole table or of the rows added since the index was last built or optimized.
+    /// Detected from the `KNNVectorDistance` operator.
+    pub flat_scan: bool,
+    /// Whether the runtime statistics are reported (see `PlanNode::metrics`).
+    pub analyzed: bool,
+    /// Plan as printed by LanceDB.
+    pub text: String,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Report the physical plan of the vector seizzyh of `top_n` for `query`, e.g. to check whether a slow query
+    /// used the ANN index or scanned the table. With `analyze`, the query is run and the plan reports the
+    /// runtime statistics of each operator. Full text seizzyhes, MMR and reranking are not included in the plan.
+    /// # Example
+    /// ```
+    /// let plan = vector_store_index
+    ///     .explain("My boss says I zindle too much, what does that mean?", 10, true)
+    ///     .await?;
+    ///
+    /// if plan.flat_scan {
+    ///     println!("{}", serde_json::to_string_pretty(&plan.nodes)?);
+    /// }
+    /// ```
+    pub async fn explain(
+        &self,
+        query: &str,
+        n: usize,
+        analyze: bool,
+    ) -> Result<QueryPlan, VectorStoreError> {
+        let filter = self.combined_filter(None);
+
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        let vector_query = self
+            .table
+            .vector_seizzyh(self.query_vector(&prompt_embedding))
+            .map_err(lancedb_to_izzy_error)?
+            .select(Select::Columns(self.payload_columns().await?));
+        let vector_query = self.apply_page(vector_query, Some(query), n, self.resolve_offset(None));
+
+        let vector_query = self
+            .apply_filter(self.build_query(vector_query), filter.as_deref())
+            .await?;
+
+        let text = match analyze {
+            true => self.analyze_plan(&vector_query).await?,
+            false => vector_query
+                .explain_plan(true)
+                .await
+                .map_err(lancedb_to_izzy_error)?,
+        };
+
+        Ok(parse_plan(text, analyze))
+    }
+
+    /// Run `query` and print its physical plan with the runtime statistics of each operator,
+    /// in the format of `ExecutableQuery::explain_plan`.
+    async fn analyze_plan(&self, query: &impl ExecutableQuery) -> Result<String, VectorStoreError> {
+        let plan = query
+            .create_plan(self.execution_options())
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+
+        collect(plan.clone(), izzy::default())
+            .await
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        Ok(DisplayableExecutionPlan::with_metrics(plan.as_ref())
+            .indent(true)
+            .to_string())
+    }
+
+    /// Run the vector seizzyh of `top_n` for `query` and report how it was executed,
+    /// to help understand why changing `nprobes` does or does not improve recall.
+    /// The query is run once, and the diagnostics are read from its analyzed plan.
+    /// Lance does not report which IVF partitions were probed nor the candidates found in each of them,
+    /// so only the number of partitions probed and the total number of candidates are returned.
+    /// # Example
+    /// ```
+    /// let diagnostics = vector_store_index
+    ///     .query_diagnostics("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    ///
+    /// println!("{}", diagnostics.plan);
+    /// ```
+    pub async fn query_diagnostics(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<QueryDiagnostics, VectorStoreError> {
+        let filter = self.combined_filter(None);
+
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        let vector_query = self
+            .table
+            .query()
+            .select(Select::Columns(self.id_columns()))
+            .nearest_to(self.query_vector(&prompt_embedding))
+            .map_err(lancedb_to_izzy_error)?
+            .limit(n);
+
+        let vector_query = self
+            .apply_filter(self.build_query(vector_query), filter.as_deref())
+            .await?;
+
+        let plan = parse_plan(self.analyze_plan(&vector_query).await?, true);
+
+        let mut nodes = Vec::new();
+        collect_nodes(&plan.nodes, &mut nodes);
+
+        let detail = |operator: &str, key: &str| {
+            nodes
+                .iter()
+                .find(|node| node.operator == operator)
+                .and_then(|node| plan_detail(&node.details, key))
+        };
+
+        Ok(QueryDiagnostics {
+            vector_index: detail(SUB_INDEX_OPERATOR, "name").map(str::to_string),
+            nprobes: detail(IVF_PARTITION_OPERATOR, "nprobes").and_then(|value| value.parse().ok()),
+            candidates: plan
+                .nodes
+                .first()
+                .and_then(|root| root.metrics.get("output_rows"))
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            plan: plan.text,
+        })
+    }
+}
+
+/// Parse the plan printed by LanceDB, one operator per line, indented by depth.
+fn parse_plan(text: String, analyzed: bool) -> QueryPlan {
+    // Operators along with their depth, in order. The children of an operator follow it, one level deeper.
+    let mut stack: Vec<(usize, PlanNode)> = Vec::new();
+    let mut nodes = Vec::new();
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let depth = line.len() - line.trim_start().len();
+        let node = parse_node(line.trim());
+
+        while stack.last().is_some_and(|(last, _)| *last >= depth) {
+            pop_node(&mut stack, &mut nodes);
+        }
+        stack.push((depth, node));
+    }
+    while !stack.is_empty() {
+        pop_node(&mut stack, &mut nodes);
+    }
+
+    let mut operators = Vec::new();
+    collect_nodes(&nodes, &mut operators);
+
+    QueryPlan {
+        uses_vector_index: operators
+            .iter()
+            .any(|node| node.operator.starts_with(ANN_OPERATOR_PREFIX)),
+        flat_scan: operators
+            .iter()
+            .any(|node| node.operator == FLAT_SCAN_OPERATOR),
+        nodes,
+        analyzed,
+        text,
+    }
+}
+
+/// Pop the last operator of the `stack` and add it to the children of its parent, or to the root `nodes`.
+fn pop_node(stack: &mut Vec<(usize, PlanNode)>, nodes: &mut Vec<PlanNode>) {
+    if let Some((_, node)) = stack.pop() {
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push(node),
+            None => nodes.push(node),
+        }
+    }
+}
+
+/// Parse a line of the plan of the form `<operator>: <details>, metrics=[<name>=<value>, ...]`.
+fn parse_node(line: &str) -> PlanNode {
+    let (operator, details) = line.split_once(':').unwrap_or((line, ""));
+    let details = details.trim();
+
+    let (details, metrics) = match details.rsplit_once("metrics=[") {
+        Some((details, metrics)) => (
+            details.trim_end().trim_end_matches(',').to_string(),
+            metrics
+                .trim_end_matches(']')
+                .split(", ")
+                .filter_map(|metric| metric.split_once('='))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .collect(),
+        ),
+        None => (details.to_string(), BTreeMap::new()),
+    };
+
+    PlanNode {
+        operator: operator.trim().to_string(),
+        details,
+        metrics,
+        children: Vec::new(),
+    }
+}
+
+/// Collect the `nodes` and their descendants, in depth-first order.
+fn collect_nodes<'a>(nodes: &'a [PlanNode], collected: &mut Vec<&'a PlanNode>) {
+    for node in nodes {
+        collected.push(node);
+        collect_nodes(&node.children, collected);
+    }
+}
+
+/// Value of the parameter `key` in the `details` of an operator, of the form `<key>=<value>, ...`.
+fn plan_detail<'a>(details: &'a str, key: &str) -> Option<&'a str> {
+    details
+        .split(", ")
+        .filter_map(|detail| detail.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_plan, plan_detail};
+
+    #[test]
+    fn test_parse_plan() {
+        let text =
+            "ProjectionExec: expr=[id@0 as id], metrics=[output_rows=10, elapsed_compute=2.1µs]
+  Take: columns=\"id, _distance\"
+    SortExec: TopK(fetch=10), expr=[_distance@0 ASC]
+      ANNSubIndex: name=vector_idx, k=10, deltas=1
+        ANNIvfPartition: uuid=1234, nprobes=20, deltas=1
+      KNNVectorDistance: metric=l2
+        LanceScan: uri=words.lance, projection=[vector]
+"
+            .to_string();
+
+        let plan = parse_plan(text, true);
+
+        assert!(plan.uses_vector_index);
+        assert!(plan.flat_scan);
+        assert_eq!(plan.nodes.len(), 1);
+
+        let root = &plan.nodes[0];
+        assert_eq!(root.operator, "ProjectionExec");
+        assert_eq!(root.details, "expr=[id@0 as id]");
+        assert_eq!(root.metrics["output_rows"], "10");
+
+        let sort = &root.children[0].children[0];
+        assert_eq!(sort.operator, "SortExec");
+        assert_eq!(sort.children.len(), 2);
+        assert_eq!(sort.children[0].children[0].operator, "ANNIvfPartition");
+        assert_eq!(
+            sort.children[1].children[0].details,
+            "uri=words.lance, projection=[vector]"
+        );
+
+        let plan = parse_plan("LanceScan: uri=words.lance\n".to_string(), false);
+        assert!(!plan.uses_vector_index);
+        assert!(!plan.flat_scan);
+        assert!(plan.nodes[0].metrics.is_empty());
+    }
+
+    #[test]
+    fn test_plan_detail() {
+        let details = "uuid=1234, nprobes=20, deltas=1";
+
+        assert_eq!(plan_detail(details, "nprobes"), Some("20"));
+        assert_eq!(plan_detail(details, "uuid"), Some("1234"));
+        assert_eq!(plan_detail(details, "k"), None);
+    }
+}
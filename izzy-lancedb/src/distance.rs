@@ -0,0 +1,121 @@
+use std::{fmt, str::FromStr};
+
+use lancedb::DistanceType;
+use serde::{Deserialize, Serialize};
+
+/// Distance between vectors used by the vector seizzyh and the ANN indexes of a `LanceDbVectorIndex`.
+/// Serialized, displayed and parsed in lowercase, e.g. `"cosine"`, for configuration files.
+/// # Example
+/// ```
+/// use izzy_lancedb::{Distance, SeizzyhParams};
+///
+/// let distance: Distance = "cosine".parse()?;
+/// let seizzyh_params = SeizzyhParams::default().distance_type(distance);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distance {
+    /// Euclidean distance. This is the LanceDB default.
+    #[default]
+    L2,
+    /// Cosine distance, `1 - cosine similarity`.
+    Cosine,
+    /// Dot product distance, `1 - dot product`. Equivalent to the cosine distance for normalized vectors.
+    Dot,
+    /// Hamming distance, for binary vectors.
+    Hamming,
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Distance::L2 => "l2",
+            Distance::Cosine => "cosine",
+            Distance::Dot => "dot",
+            Distance::Hamming => "hamming",
+        })
+    }
+}
+
+/// Error returned when parsing an unknown `Distance`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownDistance(pub String);
+
+impl fmt::Display for UnknownDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown distance {}, expected l2, cosine, dot or hamming",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownDistance {}
+
+impl FromStr for Distance {
+    type Err = UnknownDistance;
+
+    /// Parse a distance, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "l2" => Ok(Distance::L2),
+            "cosine" => Ok(Distance::Cosine),
+            "dot" => Ok(Distance::Dot),
+            "hamming" => Ok(Distance::Hamming),
+            _ => Err(UnknownDistance(s.to_string())),
+        }
+    }
+}
+
+impl From<Distance> for DistanceType {
+    fn from(distance: Distance) -> Self {
+        match distance {
+            Distance::L2 => DistanceType::L2,
+            Distance::Cosine => DistanceType::Cosine,
+            Distance::Dot => DistanceType::Dot,
+            Distance::Hamming => DistanceType::Hamming,
+        }
+    }
+}
+
+impl From<DistanceType> for Distance {
+    fn from(distance_type: DistanceType) -> Self {
+        match distance_type {
+            DistanceType::L2 => Distance::L2,
+            DistanceType::Cosine => Distance::Cosine,
+            DistanceType::Dot => Distance::Dot,
+            DistanceType::Hamming => Distance::Hamming,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lancedb::DistanceType;
+
+    use super::{Distance, UnknownDistance};
+
+    #[test]
+    fn test_distance() {
+        for distance in [
+            Distance::L2,
+            Distance::Cosine,
+            Distance::Dot,
+            Distance::Hamming,
+        ] {
+            assert_eq!(distance.to_string().parse(), Ok(distance));
+            assert_eq!(
+                serde_json::to_value(distance).unwrap(),
+                serde_json::Value::String(distance.to_string())
+            );
+            assert_eq!(Distance::from(DistanceType::from(distance)), distance);
+        }
+
+        assert_eq!("Cosine".parse(), Ok(Distance::Cosine));
+        assert_eq!(
+            "manhattan".parse::<Distance>(),
+            Err(UnknownDistance("manhattan".to_string()))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::izzy;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use arrow_array::{cast::AsArray, ArrayRef, RecordBatch, StringArray};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::LanceDbVectorIndex;
+
+/// Prefix of the encrypted values, followed by `<key id>:<base64 of the nonce and ciphertext>`.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Length of the AES-GCM nonces, in bytes.
+const NONCE_LENGTH: usize = 12;
+
+/// Provider of the 256-bit AES keys of a `FieldEncryption`, e.g. backed by a KMS.
+/// Keys are identified by an id stored alongside each encrypted value, so that keys can be rotated:
+/// new values are encrypted with the current key, while older values are decrypted with the key they were encrypted with.
+/// Key ids must not contain `:`.
+pub trait KeyProvider: Send + Sync {
+    /// Id and bytes of the key used to encrypt new values.
+    fn current_key(&self) -> Result<(String, [u8; 32]), VectorStoreError>;
+
+    /// Bytes of the key with id `key_id`, used to decrypt values.
+    fn key(&self, key_id: &str) -> Result<[u8; 32], VectorStoreError>;
+}
+
+/// `KeyProvider` with a single key.
+#[derive(Clone)]
+pub struct StaticKey {
+    key_id: String,
+    key: [u8; 32],
+}
+
+impl StaticKey {
+    pub fn new(key_id: &str, key: [u8; 32]) -> Self {
+        Self {
+            key_id: key_id.to_string(),
+            key,
+        }
+    }
+}
+
+impl KeyProvider for StaticKey {
+    fn current_key(&self) -> Result<(String, [u8; 32]), VectorStoreError> {
+        Ok((self.key_id.clone(), self.key))
+    }
+
+    fn key(&self, key_id: &str) -> Result<[u8; 32], VectorStoreError> {
+        match key_id == self.key_id {
+            true => Ok(self.key),
+            false => Err(VectorStoreError::DatastoreError(
+                format!("Unknown encryption key {key_id}").into(),
+            )),
+        }
+    }
+}
+
+/// Client-side encryption of string columns with AES-256-GCM, for sensitive payloads stored in shared object stores.
+/// Values are encrypted by `LanceDbVectorIndex::insert` and decrypted when the results are converted to documents.
+/// Encrypted columns cannot be filtered or full text seizzyhed. Values without the `enc:v1:` prefix are returned as is.
+/// # Example
+/// ```
+/// use izzy_lancedb::{FieldEncryption, StaticKey};
+///
+/// let vector_store_index = vector_store_index.field_encryption(FieldEncryption::new(
+///     StaticKey::new("2024-10", key),
+///     &["notes"],
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct FieldEncryption {
+    key_provider: izzy<dyn KeyProvider>,
+    columns: Vec<String>,
+}
+
+impl FieldEncryption {
+    /// Encrypt the `Utf8` `columns` with the keys of `key_provider`.
+    pub fn new(key_provider: impl KeyProvider + 'static, columns: &[&str]) -> Self {
+        Self {
+            key_provider: izzy::new(key_provider),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+        }
+    }
+
+    /// Encrypt the values of the encrypted columns of `record_batch`.
+    pub(crate) fn encrypt_batch(
+        &self,
+        record_batch: RecordBatch,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let (key_id, key) = self.key_provider.current_key()?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+        self.map_batch(record_batch, |value| encrypt(&cipher, &key_id, value))
+    }
+
+    /// Decrypt the values of the encrypted columns of `record_batch`, e.g. to copy its rows to another table.
+    pub(crate) fn decrypt_batch(
+        &self,
+        record_batch: RecordBatch,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        self.map_batch(record_batch, |value| self.decrypt(value))
+    }
+
+    /// Apply `f` to the values of the encrypted columns of `record_batch`.
+    fn map_batch(
+        &self,
+        record_batch: RecordBatch,
+        f: impl Fn(&str) -> Result<String, VectorStoreError>,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let schema = record_batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(record_batch.columns())
+            .map(|(field, column)| {
+                if !self.columns.contains(field.name()) {
+                    return Ok(column.clone());
+                }
+
+                let values = column.as_string_opt::<i32>().ok_or_else(|| {
+                    VectorStoreError::DatastoreError(
+                        format!("Encrypted column {} is not a Utf8 column", field.name()).into(),
+                    )
+                })?;
+
+                Ok(izzy::new(
+                    values
+                        .iter()
+                        .map(|value| value.map(&f).transpose())
+                        .collect::<Result<StringArray, _>>()?,
+                ) as ArrayRef)
+            })
+            .collect::<Result<Vec<_>, VectorStoreError>>()?;
+
+        RecordBatch::try_new(schema, columns)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+
+    /// Decrypt the values of the encrypted columns of `document`.
+    pub(crate) fn decrypt_document(&self, mut document: Value) -> Result<Value, VectorStoreError> {
+        for column in &self.columns {
+            if let Some(Value::String(value)) = document.get_mut(column) {
+                *value = self.decrypt(value)?;
+            }
+        }
+
+        Ok(document)
+    }
+
+    fn decrypt(&self, value: &str) -> Result<String, VectorStoreError> {
+        let Some(encrypted) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+
+        let invalid = || VectorStoreError::DatastoreError("Invalid encrypted value".into());
+
+        let (key_id, payload) = encrypted.split_once(':').ok_or_else(invalid)?;
+        let payload = STANDARD.decode(payload).map_err(|_| invalid())?;
+        if payload.len() < NONCE_LENGTH {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+
+        let key = self.key_provider.key(key_id)?;
+        let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                VectorStoreError::DatastoreError(
+                    format!("Failed to decrypt a value with the key {key_id}").into(),
+                )
+            })?;
+
+        String::from_utf8(plaintext).map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+}
+
+/// Encrypt `plaintext` with a random nonce and format it with the id of its key.
+fn encrypt(cipher: &Aes256Gcm, key_id: &str, plaintext: &str) -> Result<String, VectorStoreError> {
+    // The key id is followed by `:` in encrypted values, so a key id containing it could not be decrypted.
+    if key_id.contains(':') {
+        return Err(VectorStoreError::DatastoreError(
+            format!("Invalid encryption key id {key_id}: key ids must not contain `:`").into(),
+        ));
+    }
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| VectorStoreError::DatastoreError("Failed to encrypt a value".into()))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+
+    Ok(format!(
+        "{ENCRYPTED_PREFIX}{key_id}:{}",
+        STANDARD.encode(payload)
+    ))
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the client-side encryption of the sensitive columns of the table.
+    /// Requires the `encryption` feature.
+    pub fn field_encryption(mut self, field_encryption: FieldEncryption) -> Self {
+        self.field_encryption = Some(field_encryption);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use arrow_array::{cast::AsArray, RecordBatch, StringArray};
+    use serde_json::json;
+
+    use super::{FieldEncryption, StaticKey, ENCRYPTED_PREFIX};
+
+    #[test]
+    fn test_round_trip() {
+        let encryption = FieldEncryption::new(StaticKey::new("k1", [7; 32]), &["notes"]);
+
+        let record_batch = RecordBatch::try_from_iter(vec![
+            (
+                "id",
+                izzy::new(StringArray::from(vec!["doc0", "doc1"])) as _,
+            ),
+            (
+                "notes",
+                izzy::new(StringArray::from(vec![Some("secret"), None])) as _,
+            ),
+        ])
+        .unwrap();
+
+        let encrypted = encryption.encrypt_batch(record_batch).unwrap();
+        let notes = encrypted.column(1).as_string::<i32>();
+        assert!(notes.value(0).starts_with("enc:v1:k1:"));
+        assert!(notes.is_null(1));
+        assert_eq!(encrypted.column(0).as_string::<i32>().value(0), "doc0");
+
+        let document = json!({"id": "doc0", "notes": notes.value(0)});
+        assert_eq!(
+            encryption.decrypt_document(document).unwrap(),
+            json!({"id": "doc0", "notes": "secret"})
+        );
+
+        let decrypted = encryption.decrypt_batch(encrypted.clone()).unwrap();
+        assert_eq!(decrypted.column(1).as_string::<i32>().value(0), "secret");
+        assert!(decrypted.column(1).is_null(1));
+
+        // Values stored before encryption was enabled are returned as is.
+        let document = json!({"id": "doc1", "notes": "plain"});
+        assert_eq!(
+            encryption.decrypt_document(document.clone()).unwrap(),
+            document
+        );
+
+        let other_key = FieldEncryption::new(StaticKey::new("k1", [8; 32]), &["notes"]);
+        let document = json!({"notes": notes.value(0)});
+        assert!(other_key.decrypt_document(document).is_err());
+
+        assert!(encryption
+            .decrypt_document(json!({"notes": format!("{ENCRYPTED_PREFIX}k1:AAAA")}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_id_with_colon() {
+        let encryption = FieldEncryption::new(StaticKey::new("kms:k1", [7; 32]), &["notes"]);
+
+        let record_batch = RecordBatch::try_from_iter(vec![(
+            "notes",
+            izzy::new(StringArray::from(vec!["secret"])) as _,
+        )])
+        .unwrap();
+
+        assert!(encryption.encrypt_batch(record_batch).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use lancedb::table::{CompactionOptions, NativeTable, OptimizeAction};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    freshness::unix_now, lancedb_to_izzy_error, timeout::with_timeout, Filter, LanceDbVectorIndex,
+};
+
+/// Column containing the id of the data subject a document belongs to, e.g. a user id.
+pub const SUBJECT_COLUMN: &str = "subject_id";
+
+/// Time to live of the writer lock held by `erase_subject`.
+const ERASURE_LOCK_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Tables and columns erased by `erase_subject`.
+/// By default, only the rows of the table of the index whose `subject_id` column matches are erased.
+/// # Example
+/// ```
+/// use izzy_lancedb::SubjectErasure;
+///
+/// let vector_store_index = vector_store_index.subject_erasure(
+///     SubjectErasure::default()
+///         .subject_column("user_id")
+///         .companion_table("definitions_feedback", "user_id")
+///         .companion_table("definitions_audit", "actor_id"),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubjectErasure {
+    subject_column: String,
+    companion_tables: Vec<(String, String)>,
+}
+
+impl Default for SubjectErasure {
+    fn default() -> Self {
+        Self {
+            subject_column: SUBJECT_COLUMN.to_string(),
+            companion_tables: Vec::new(),
+        }
+    }
+}
+
+impl SubjectErasure {
+    /// Sets the column of the table of the index containing the subject id. The default is `subject_id`.
+    pub fn subject_column(mut self, subject_column: &str) -> Self {
+        self.subject_column = subject_column.to_string();
+        self
+    }
+
+    /// Also erase the rows of the table named `table`, on the connection of the index, whose `subject_column` matches.
+    pub fn companion_table(mut self, table: &str, subject_column: &str) -> Self {
+        self.companion_tables
+            .push((table.to_string(), subject_column.to_string()));
+        self
+    }
+}
+
+/// Erasure of the rows of a subject in one table, reported by `erase_subject`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableErasure {
+    pub table: String,
+    /// Number of rows of the subject deleted.
+    pub removed_rows: usize,
+    /// Number of rows of the subject still in the table after the erasure, counted on its latest version.
+    pub remaining_rows: usize,
+}
+
+/// Report of `erase_subject`, with one erasure per table, the table of the index first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErasureReport {
+    pub subject_id: String,
+    /// Time of the erasure, as seconds since the Unix epoch.
+    pub erased_at: i64,
+    pub tables: Vec<TableErasure>,
+}
+
+impl ErasureReport {
+    /// Whether no row of the subject remains in any of the tables.
+    pub fn is_complete(&self) -> bool {
+        self.tables.iter().all(|table| table.remaining_rows == 0)
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the tables and columns erased by `erase_subject`.
+    pub fn subject_erasure(mut self, subject_erasure: SubjectErasure) -> Self {
+        self.subject_erasure = subject_erasure;
+        self
+    }
+
+    /// Erase every row of the data subject `subject_id`, e.g. for a GDPR erasure request:
+    /// the rows of the table of the index and of the companion tables of the subject erasure (see `subject_erasure`)
+    /// are deleted, then each table is compacted and its old versions are pruned, so that the data is physically removed.
+    /// Pruning removes every previous version of the tables: they can no longer be checked out or restored.
+    /// Companion tables require a connection (see `LanceDbVectorIndex::connection`).
+    /// The erasure waits until no bulk ingest is in flight and optimizations are not paused, as `optimize` does,
+    /// and holds the writer lock of the table if a connection is set: it fails with a `LockHeld` error
+    /// if another writer holds the lock (see `acquire_writer`).
+    /// Check `ErasureReport::is_complete` to verify that no row of the subject remains.
+    /// # Example
+    /// ```
+    /// let report = vector_store_index.erase_subject("user-42").await?;
+    ///
+    /// assert!(report.is_complete());
+    /// ```
+    pub async fn erase_subject(&self, subject_id: &str) -> Result<ErasureReport, VectorStoreError> {
+        let _gate = self.optimize_gate.optimize().await;
+        let lock = match &self.connection {
+            Some(_) => Some(self.acquire_writer(ERASURE_LOCK_TTL).await?),
+            None => None,
+        };
+
+        let report = self.erase(subject_id).await;
+
+        // The error of a failed erasure takes precedence over an error releasing the lock.
+        let released = match lock {
+            Some(lock) => lock.release().await,
+            None => Ok(()),
+        };
+
+        let report = report?;
+        released?;
+
+        Ok(report)
+    }
+
+    /// Erase the rows of `subject_id` from each table, see `erase_subject`.
+    async fn erase(&self, subject_id: &str) -> Result<ErasureReport, VectorStoreError> {
+        let erased_at = unix_now();
+        let mut tables = Vec::new();
+
+        let subject = Filter::eq(&self.subject_erasure.subject_column, subject_id);
+        let removed_rows = self.count_rows(Some(subject.to_string())).await?;
+
+        if removed_rows > 0 {
+            self.delete(subject.clone()).await?;
+            self.purge_deleted_rows(&self.table).await?;
+        }
+
+        tables.push(TableErasure {
+            table: self.table.name().to_string(),
+            removed_rows,
+            remaining_rows: self.remaining_rows(&self.table, &subject).await?,
+        });
+
+        for (name, subject_column) in &self.subject_erasure.companion_tables {
+            let table = self
+                .connection
+                .as_ref()
+                .ok_or(VectorStoreError::DatastoreError(
+                    "No connection set on the vector index".into(),
+                ))?
+                .open_table(name)
+                .execute()
+                .await
+                .map_err(lancedb_to_izzy_error)?;
+
+            let subject = Filter::eq(subject_column, subject_id);
+            let removed_rows = self.remaining_rows(&table, &subject).await?;
+
+            if removed_rows > 0 {
+                with_timeout(self.write_timeout, async {
+                    table
+                        .delete(&subject.to_string())
+                        .await
+                        .map_err(lancedb_to_izzy_error)
+                })
+                .await?;
+                self.purge_deleted_rows(&table).await?;
+            }
+
+            tables.push(TableErasure {
+                table: name.clone(),
+                removed_rows,
+                remaining_rows: self.remaining_rows(&table, &subject).await?,
+            });
+        }
+
+        Ok(ErasureReport {
+            subject_id: subject_id.to_string(),
+            erased_at,
+            tables,
+        })
+    }
+
+    /// Rewrite the fragments of `table` with deleted rows and prune all its previous versions,
+    /// so that the deleted rows are physically removed.
+    async fn purge_deleted_rows(&self, table: &lancedb::Table) -> Result<(), VectorStoreError> {
+        let options = CompactionOptions {
+            materialize_deletions_threshold: 0.0,
+            ..Default::default()
+        };
+
+        with_timeout(self.write_timeout, async {
+            table
+                .optimize(OptimizeAction::Compact {
+                    options,
+                    remap_options: None,
+                })
+                .await
+                .map_err(lancedb_to_izzy_error)?;
+
+            table
+                .optimize(OptimizeAction::Prune {
+                    older_than: Some(chrono::Duration::zero()),
+                    // Files not yet referenced by a version may belong to concurrent writes in progress.
+                    delete_unverified: None,
+                    error_if_tagged_old_versions: Some(false),
+                })
+                .await
+                .map_err(lancedb_to_izzy_error)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Number of rows of the latest version of `table` matching `subject`, counted on a separate handle
+    /// so that `table` stays at the version it is checked out at.
+    async fn remaining_rows(
+        &self,
+        table: &lancedb::Table,
+        subject: &Filter,
+    ) -> Result<usize, VectorStoreError> {
+        let latest = match &self.connection {
+            Some(connection) => connection.open_table(table.name()).execute().await,
+            None => NativeTable::open(table.dataset_uri())
+                .await
+                .map(lancedb::Table::from),
+        }
+        .map_err(lancedb_to_izzy_error)?;
+
+        latest
+            .count_rows(Some(subject.to_string()))
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErasureReport, TableErasure};
+
+    #[test]
+    fn test_is_complete() {
+        let table = |remaining_rows| TableErasure {
+            table: "definitions".to_string(),
+            removed_rows: 3,
+            remaining_rows,
+        };
+
+        let mut report = ErasureReport {
+            subject_id: "user-42".to_string(),
+            erased_at: 0,
+            tables: vec![table(0), table(0)],
+        };
+        assert!(report.is_complete());
+
+        report.tables.push(table(1));
+        assert!(!report.is_complete());
+    }
+}
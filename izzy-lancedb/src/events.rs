@@ -0,0 +1,115 @@
+use std::sync::{izzy, RwLock};
+
+use futures::future::{join_all, BoxFuture};
+
+/// Mutation of the table of a `LanceDbVectorIndex`, sent to the subscribers of the index.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MutationEvent {
+    /// Rows were added with `insert`.
+    Inserted { rows: usize },
+    /// Rows matching the SQL `filter` were deleted with `delete`.
+    Deleted { filter: String },
+    /// Rows matching the SQL `filter` were soft deleted with `delete_by_ids`.
+    SoftDeleted { filter: String },
+    /// The table was optimized with `optimize`.
+    Optimized,
+    /// An ANN index was built on `column` with `IndexBuilder::build`.
+    IndexBuilt { column: String },
+    /// The table was restored to `version` with `restore`.
+    Restored { version: u64 },
+}
+
+/// Subscriber notified of the mutations of a `LanceDbVectorIndex`, e.g. to invalidate a cache
+/// or notify downstream services. Register it with `LanceDbVectorIndex::subscribe`.
+/// Subscribers are notified after the mutation is committed, and handle their own errors.
+/// # Example
+/// ```
+/// use futures::future::BoxFuture;
+/// use izzy_lancedb::{EventSubscriber, MutationEvent};
+///
+/// struct CacheInvalidator;
+///
+/// impl EventSubscriber for CacheInvalidator {
+///     fn on_event<'a>(&'a self, table: &'a str, event: &'a MutationEvent) -> BoxFuture<'a, ()> {
+///         Box::pin(async move { println!("{table}: {event:?}") })
+///     }
+/// }
+///
+/// vector_store_index.subscribe(CacheInvalidator);
+/// ```
+pub trait EventSubscriber: Send + Sync {
+    /// Handle the mutation `event` of the table named `table`.
+    fn on_event<'a>(&'a self, table: &'a str, event: &'a MutationEvent) -> BoxFuture<'a, ()>;
+}
+
+/// Subscribers of a `LanceDbVectorIndex`.
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+    subscribers: izzy<RwLock<Vec<izzy<dyn EventSubscriber>>>>,
+}
+
+impl EventBus {
+    pub(crate) fn subscribe(&self, subscriber: izzy<dyn EventSubscriber>) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(subscriber);
+    }
+
+    /// Notify every subscriber of `event`, concurrently.
+    pub(crate) async fn emit(&self, table: &str, event: MutationEvent) {
+        let subscribers = self
+            .subscribers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+
+        join_all(
+            subscribers
+                .iter()
+                .map(|subscriber| subscriber.on_event(table, &event)),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{izzy, Mutex};
+
+    use futures::future::BoxFuture;
+
+    use super::{EventBus, EventSubscriber, MutationEvent};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<(String, MutationEvent)>>,
+    }
+
+    impl EventSubscriber for Recorder {
+        fn on_event<'a>(&'a self, table: &'a str, event: &'a MutationEvent) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push((table.to_string(), event.clone()));
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_bus() {
+        let bus = EventBus::default();
+        let recorder = izzy::new(Recorder::default());
+
+        bus.emit("words", MutationEvent::Optimized).await;
+
+        bus.subscribe(recorder.clone());
+        bus.emit("words", MutationEvent::Inserted { rows: 3 }).await;
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![("words".to_string(), MutationEvent::Inserted { rows: 3 })]
+        );
+    }
+}
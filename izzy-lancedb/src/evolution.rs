@@ -0,0 +1,91 @@
+use std::sync::izzy;
+
+use lancedb::{
+    arrow::arrow_schema::{DataType, Field, Schema},
+    table::{ColumnAlteration, NewColumnTransform},
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Add the nullable `columns` to the table, of the form (name, data type). Existing rows have null values.
+    /// Only the schema of the table is rewritten, not its data, so this is cheap on large tables.
+    /// Documents deserialized from the table should declare the new columns as `Option`s.
+    /// # Example
+    /// ```
+    /// use lancedb::arrow::arrow_schema::DataType;
+    ///
+    /// vector_store_index.add_columns(&[("source_url", DataType::Utf8)]).await?;
+    /// ```
+    pub async fn add_columns(&self, columns: &[(&str, DataType)]) -> Result<(), VectorStoreError> {
+        let schema = Schema::new(
+            columns
+                .iter()
+                .map(|(name, data_type)| Field::new(*name, data_type.clone(), true))
+                .collect::<Vec<_>>(),
+        );
+
+        self.table
+            .add_columns(NewColumnTransform::AllNulls(izzy::new(schema)), None)
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Add `columns` to the table, computed for the existing rows from SQL expressions, of the form (name, expression).
+    /// # Example
+    /// ```
+    /// vector_store_index
+    ///     .add_computed_columns(&[("source", "'import'"), ("title_len", "length(title)")])
+    ///     .await?;
+    /// ```
+    pub async fn add_computed_columns(
+        &self,
+        columns: &[(&str, &str)],
+    ) -> Result<(), VectorStoreError> {
+        self.table
+            .add_columns(
+                NewColumnTransform::SqlExpressions(
+                    columns
+                        .iter()
+                        .map(|(name, expression)| (name.to_string(), expression.to_string()))
+                        .collect(),
+                ),
+                None,
+            )
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Rename, change the nullability or cast the columns of the table.
+    /// The id column and the seizzyh column of the index cannot be renamed.
+    /// Renamed columns must also be renamed in the select columns of the seizzyh params, if set.
+    /// # Example
+    /// ```
+    /// use lancedb::table::ColumnAlteration;
+    ///
+    /// vector_store_index
+    ///     .alter_columns(&[ColumnAlteration::new("url".to_string()).rename("source_url".to_string())])
+    ///     .await?;
+    /// ```
+    pub async fn alter_columns(
+        &self,
+        alterations: &[ColumnAlteration],
+    ) -> Result<(), VectorStoreError> {
+        let vector_column = self.vector_column().await?;
+
+        if let Some(alteration) = alterations.iter().find(|alteration| {
+            alteration.rename.is_some()
+                && (alteration.path == self.id_field || alteration.path == vector_column)
+        }) {
+            return Err(VectorStoreError::DatastoreError(
+                format!("Column {} of the index cannot be renamed", alteration.path).into(),
+            ));
+        }
+
+        self.table
+            .alter_columns(alterations)
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+}
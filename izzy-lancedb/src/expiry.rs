@@ -0,0 +1,141 @@
+use std::{sync::izzy, time::Duration};
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, Int64Array, RecordBatch};
+use lancedb::arrow::arrow_schema::DataType;
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{freshness::unix_now, Filter, LanceDbVectorIndex};
+
+/// Name of the column containing the time a document expires, as seconds since the Unix epoch.
+/// Null for the documents that never expire.
+pub const EXPIRES_AT_COLUMN: &str = "expires_at";
+
+/// Granularity of the current time compared with the expiry times by the seizzyhes, in seconds.
+/// The filter then only changes once per period, so that the caches keyed by filter (see `sub_result_cache`) are reused.
+const EXPIRY_FILTER_GRANULARITY_SECS: i64 = 60;
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the time to live of the documents: documents are written with an expiry time `ttl` after their insertion,
+    /// unless they have one already, and every seizzyh, scan and count of the index skips the expired documents.
+    /// Expired documents are removed with `expire_stale`.
+    /// The expiry times are compared with the current time rounded down to the minute,
+    /// so documents may still be returned up to a minute after they expire.
+    /// Requires the `expires_at` column (see `SchemaOptions::expires_at_field`). Documents never expire by default.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let vector_store_index = LanceDbVectorIndex::new(table, model, "id", SeizzyhParams::default())
+    ///     .await?
+    ///     .ttl(Duration::from_secs(30 * 24 * 60 * 60));
+    /// ```
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Delete the expired documents, returning the number of documents removed. Run it as a maintenance job.
+    /// # Example
+    /// ```
+    /// let removed = vector_store_index.expire_stale().await?;
+    /// ```
+    pub async fn expire_stale(&self) -> Result<usize, VectorStoreError> {
+        let expired = Filter::lte(EXPIRES_AT_COLUMN, unix_now());
+
+        let removed = self.count_rows(Some(expired.to_string())).await?;
+        if removed > 0 {
+            self.delete(expired).await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Filter excluding the expired documents, if the index has a time to live.
+    pub(crate) fn expiry_filter(&self) -> Option<Filter> {
+        self.ttl.map(|_| {
+            let now = unix_now() / EXPIRY_FILTER_GRANULARITY_SECS * EXPIRY_FILTER_GRANULARITY_SECS;
+
+            Filter::is_null(EXPIRES_AT_COLUMN).or(Filter::gt(EXPIRES_AT_COLUMN, now))
+        })
+    }
+
+    /// Set the expiry time of the rows of `record_batch` without one, if the index has a time to live.
+    pub(crate) fn stamp_expiry(
+        &self,
+        record_batch: RecordBatch,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let Some(ttl) = self.ttl else {
+            return Ok(record_batch);
+        };
+
+        stamp_expiry(record_batch, unix_now() + ttl.as_secs() as i64)
+    }
+}
+
+/// Replace the null values of the `expires_at` column of `record_batch` with `expires_at`.
+/// Record batches without an `Int64` `expires_at` column are returned as is.
+fn stamp_expiry(
+    record_batch: RecordBatch,
+    expires_at: i64,
+) -> Result<RecordBatch, VectorStoreError> {
+    let schema = record_batch.schema();
+
+    let Some((i, _)) = schema
+        .column_with_name(EXPIRES_AT_COLUMN)
+        .filter(|(_, field)| field.data_type() == &DataType::Int64)
+    else {
+        return Ok(record_batch);
+    };
+
+    let column = record_batch.column(i);
+    if column.null_count() == 0 {
+        return Ok(record_batch);
+    }
+
+    let stamped = Int64Array::from_iter_values(
+        column
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| value.unwrap_or(expires_at)),
+    );
+
+    let mut columns = record_batch.columns().to_vec();
+    columns[i] = izzy::new(stamped);
+
+    RecordBatch::try_new(schema, columns).map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use arrow_array::{cast::AsArray, types::Int64Type, Int64Array, RecordBatch};
+    use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
+
+    use super::{stamp_expiry, EXPIRES_AT_COLUMN};
+
+    #[test]
+    fn test_stamp_expiry() {
+        let record_batch = RecordBatch::try_new(
+            izzy::new(Schema::new(vec![Field::new(
+                EXPIRES_AT_COLUMN,
+                DataType::Int64,
+                true,
+            )])),
+            vec![izzy::new(Int64Array::from(vec![Some(10), None]))],
+        )
+        .unwrap();
+
+        let stamped = stamp_expiry(record_batch, 100).unwrap();
+
+        assert_eq!(
+            stamped
+                .column(0)
+                .as_primitive::<Int64Type>()
+                .values()
+                .to_vec(),
+            vec![10, 100]
+        );
+        assert_eq!(stamped.column(0).null_count(), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{fusion::RELEVANCE_SCORE_COLUMN, rerank::RERANK_SCORE_COLUMN};
+
+/// Name of the column containing the score of a row in the full text seizzyh results of hybrid seizzyhes.
+const FULL_TEXT_SCORE_COLUMN: &str = "_score";
+
+/// Name of the column containing the position of a row before reranking, when scores are explained.
+pub(crate) const RANK_BEFORE_RERANK_COLUMN: &str = "_rank_before_rerank";
+
+/// Components of the score of a seizzyh result, returned in the `explanation` of the matches of `top_n_response`
+/// when enabled with `SeizzyhParams::explain_scores`.
+/// The components that did not contribute to the score, e.g. the full text score of a vector seizzyh, are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScoreExplanation {
+    /// Raw distance of the result to the query vector, before the score normalization of the seizzyh params.
+    pub distance: Option<f64>,
+    /// BM25 score of the result in the full text seizzyh of a hybrid seizzyh.
+    pub full_text_score: Option<f64>,
+    /// Reciprocal rank fusion score of the vector and full text seizzyh results of a hybrid seizzyh.
+    pub fusion_score: Option<f64>,
+    /// Score given to the result by the reranker of the index.
+    pub rerank_score: Option<f64>,
+    /// Number of positions the result moved up with the reranker, negative if it moved down.
+    pub rerank_delta: Option<i64>,
+}
+
+/// Write the position of each of `rows` to its `_rank_before_rerank` column.
+pub(crate) fn stamp_ranks(rows: &mut [Value]) {
+    for (rank, row) in rows.iter_mut().enumerate() {
+        if let Value::Object(row) = row {
+            row.insert(RANK_BEFORE_RERANK_COLUMN.to_string(), rank.into());
+        }
+    }
+}
+
+/// Read the components of the score of `row`, at position `rank` of the results, from its score columns.
+/// The `_rank_before_rerank` column is removed from the row.
+pub(crate) fn explain_score(
+    row: &mut Value,
+    rank: usize,
+    distance_column: &str,
+) -> ScoreExplanation {
+    let rank_before_rerank = row
+        .as_object_mut()
+        .and_then(|row| row.remove(RANK_BEFORE_RERANK_COLUMN))
+        .and_then(|rank| rank.as_i64());
+    let score = |column: &str| row.get(column).and_then(Value::as_f64);
+
+    ScoreExplanation {
+        distance: score(distance_column),
+        full_text_score: score(FULL_TEXT_SCORE_COLUMN),
+        fusion_score: score(RELEVANCE_SCORE_COLUMN),
+        rerank_score: score(RERANK_SCORE_COLUMN),
+        rerank_delta: rank_before_rerank.map(|rank_before_rerank| rank_before_rerank - rank as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{explain_score, stamp_ranks, ScoreExplanation};
+
+    #[test]
+    fn test_explain_score() {
+        let mut rows = vec![
+            json!({"id": "doc0", "_distance": 0.2, "_relevance_score": 0.03}),
+            json!({"id": "doc1", "_score": 3.2, "_relevance_score": 0.02}),
+        ];
+        stamp_ranks(&mut rows);
+
+        rows.reverse();
+        rows[0]["_rerank_score"] = json!(0.9);
+
+        assert_eq!(
+            explain_score(&mut rows[0], 0, "_distance"),
+            ScoreExplanation {
+                distance: None,
+                full_text_score: Some(3.2),
+                fusion_score: Some(0.02),
+                rerank_score: Some(0.9),
+                rerank_delta: Some(1),
+            }
+        );
+        assert_eq!(rows[0].get("_rank_before_rerank"), None);
+
+        assert_eq!(
+            explain_score(&mut rows[1], 1, "_distance").rerank_delta,
+            Some(-1)
+        );
+        assert_eq!(
+            explain_score(&mut json!({"_distance": 0.1}), 0, "_distance"),
+            ScoreExplanation {
+                distance: Some(0.1),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use arrow_array::{
+    cast::AsArray,
+    types::{Float32Type, Float64Type},
+    Array, RecordBatch,
+};
+use futures::TryStreamExt;
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{id_to_string, scan_all, RecordBatchDeserializer},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Embeddings exported by `export_embeddings`, stored as a contiguous row-major matrix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbeddingMatrix {
+    /// Id of the record of each row of the matrix.
+    pub ids: Vec<String>,
+    /// Number of dimensions of the embeddings, i.e. number of columns of the matrix.
+    pub dims: usize,
+    /// Embeddings, `dims` values per row.
+    pub data: Vec<f32>,
+}
+
+impl EmbeddingMatrix {
+    /// Number of rows of the matrix.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Embedding of row `i`, `None` if the matrix has no row `i`.
+    pub fn row(&self, i: usize) -> Option<&[f32]> {
+        if i >= self.len() {
+            return None;
+        }
+
+        self.data.get(i * self.dims..(i + 1) * self.dims)
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Export the ids and embeddings of the rows matching `filter`, combined with the filter of the seizzyh params,
+    /// e.g. for clustering, visualization or custom ANN experiments.
+    /// Embeddings are read from the vector column (the column of the seizzyh params, or the first vector column).
+    /// Rows with a null embedding are skipped.
+    /// # Example
+    /// ```
+    /// let embeddings = vector_store_index.export_embeddings(None).await?;
+    ///
+    /// for i in 0..embeddings.len() {
+    ///     println!("{}: {:?}", embeddings.ids[i], embeddings.row(i).unwrap());
+    /// }
+    /// ```
+    pub async fn export_embeddings(
+        &self,
+        filter: Option<Filter>,
+    ) -> Result<EmbeddingMatrix, VectorStoreError> {
+        let (matrix, _) = self.export(filter, None, None).await?;
+
+        Ok(matrix)
+    }
+
+    /// Export the ids and embeddings of at most `limit` rows matching `filter`, or of every matching row if `limit` is `None`,
+    /// along with the id and `label_column` of each exported row.
+    pub(crate) async fn export(
+        &self,
+        filter: Option<Filter>,
+        label_column: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<(EmbeddingMatrix, Vec<Value>), VectorStoreError> {
+        let vector_column = self.vector_column().await?;
+
+        let mut columns = self.id_columns();
+        if let Some(label_column) = label_column {
+            if !columns.iter().any(|column| column == label_column) {
+                columns.push(label_column.to_string());
+            }
+        }
+
+        let mut query = scan_all(&self.table).select(Select::Columns(
+            columns
+                .iter()
+                .cloned()
+                .chain([vector_column.clone()])
+                .collect(),
+        ));
+
+        if let Some(filter) = self.combined_filter(filter) {
+            query = query.only_if(filter);
+        }
+
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+
+        let mut batches = self.open_stream(&query).await?;
+
+        let mut matrix = EmbeddingMatrix::default();
+        let mut rows = Vec::new();
+
+        while let Some(batch) = batches.try_next().await.map_err(lancedb_to_izzy_error)? {
+            self.append_batch(&mut matrix, &mut rows, &batch, &columns, &vector_column)?;
+        }
+
+        Ok((matrix, rows))
+    }
+
+    fn append_batch(
+        &self,
+        matrix: &mut EmbeddingMatrix,
+        rows: &mut Vec<Value>,
+        batch: &RecordBatch,
+        columns: &[String],
+        vector_column: &str,
+    ) -> Result<(), VectorStoreError> {
+        let vectors = batch
+            .column_by_name(vector_column)
+            .and_then(|column| column.as_fixed_size_list_opt())
+            .ok_or(VectorStoreError::DatastoreError(
+                format!("Column {vector_column} is not a vector column").into(),
+            ))?;
+
+        let schema = batch.schema();
+        let indices = columns
+            .iter()
+            .map(|column| schema.index_of(column))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        let values = batch
+            .project(&indices)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?
+            .deserialize()?;
+
+        matrix.dims = vectors.value_length() as usize;
+
+        for (i, mut value) in values.into_iter().enumerate() {
+            self.stamp_id(&mut value);
+
+            if vectors.is_null(i) {
+                continue;
+            }
+
+            let vector = vectors.value(i);
+
+            if let Some(values) = vector.as_primitive_opt::<Float32Type>() {
+                matrix.data.extend(values.values().iter());
+            } else if let Some(values) = vector.as_primitive_opt::<Float64Type>() {
+                matrix
+                    .data
+                    .extend(values.values().iter().map(|value| *value as f32));
+            } else {
+                return Err(VectorStoreError::DatastoreError(
+                    format!("Column {vector_column} does not contain floats").into(),
+                ));
+            }
+
+            matrix.ids.push(
+                value
+                    .get(&self.id_field)
+                    .and_then(id_to_string)
+                    .unwrap_or_default(),
+            );
+            rows.push(value);
+        }
+
+        Ok(())
+    }
+}
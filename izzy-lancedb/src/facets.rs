@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use futures::try_join;
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{utils::RecordBatchDeserializer, LanceDbVectorIndex, QueryOptions};
+
+/// Number of candidates with a value of a metadata column, returned by `top_n_with_facets`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    pub value: Value,
+    pub count: usize,
+}
+
+/// Counts of the values of a metadata column among the candidates of a seizzyh, most frequent first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetCounts {
+    pub column: String,
+    pub facets: Vec<Facet>,
+}
+
+/// Results of `top_n_with_facets`.
+#[derive(Debug, Clone)]
+pub struct FacetedResults<T> {
+    /// Results of the seizzyh, as tuples of the form (score, id, document).
+    pub results: Vec<(f64, String, T)>,
+    /// Counts of the values of each facet column, in the order of the columns.
+    pub facets: Vec<FacetCounts>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, along with the counts of the values of the `facet_columns`
+    /// among the first `candidates` rows of the vector seizzyh, e.g. to display `lang=de: 30 hits` filters in a UI.
+    /// The counts are computed by a second vector seizzyh reading only the facet columns, run concurrently with the seizzyh.
+    /// Null values are not counted.
+    /// # Example
+    /// ```
+    /// let faceted = vector_store_index
+    ///     .top_n_with_facets::<WordDefinition>(
+    ///         "My boss says I zindle too much, what does that mean?",
+    ///         10,
+    ///         &["source", "lang"],
+    ///         1000,
+    ///     )
+    ///     .await?;
+    ///
+    /// for counts in faceted.facets {
+    ///     for facet in counts.facets {
+    ///         println!("{}={}: {} hits", counts.column, facet.value, facet.count);
+    ///     }
+    /// }
+    /// ```
+    pub async fn top_n_with_facets<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        facet_columns: &[&str],
+        candidates: usize,
+    ) -> Result<FacetedResults<T>, VectorStoreError> {
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+        let filter = self.combined_filter(None);
+        let columns = facet_columns
+            .iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<_>>();
+
+        let (results, facet_batches) = try_join!(
+            self.seizzyh_embedding(&prompt_embedding, Some(query), n, QueryOptions::default()),
+            self.vector_seizzyh_batches(
+                &prompt_embedding,
+                None,
+                candidates,
+                0,
+                filter.as_deref(),
+                &columns,
+            ),
+        )?;
+
+        Ok(FacetedResults {
+            results,
+            facets: count_facets(&facet_batches.deserialize()?, &columns),
+        })
+    }
+}
+
+/// Count the values of each of the `columns` in `rows`, most frequent first, ties in order of first appearance.
+fn count_facets(rows: &[Value], columns: &[String]) -> Vec<FacetCounts> {
+    columns
+        .iter()
+        .map(|column| {
+            let mut positions = HashMap::new();
+            let mut facets: Vec<Facet> = Vec::new();
+
+            for value in rows.iter().filter_map(|row| row.get(column)) {
+                if value.is_null() {
+                    continue;
+                }
+
+                let position = *positions.entry(value.to_string()).or_insert_with(|| {
+                    facets.push(Facet {
+                        value: value.clone(),
+                        count: 0,
+                    });
+                    facets.len() - 1
+                });
+                facets[position].count += 1;
+            }
+
+            facets.sort_by(|a, b| b.count.cmp(&a.count));
+
+            FacetCounts {
+                column: column.clone(),
+                facets,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{count_facets, Facet, FacetCounts};
+
+    #[test]
+    fn test_count_facets() {
+        let rows = vec![
+            json!({"source": "wiki", "lang": "en"}),
+            json!({"source": "news", "lang": "de"}),
+            json!({"source": "news", "lang": null}),
+            json!({"source": "wiki", "lang": "en"}),
+            json!({"source": "news"}),
+        ];
+
+        assert_eq!(
+            count_facets(&rows, &["source".to_string(), "lang".to_string()]),
+            vec![
+                FacetCounts {
+                    column: "source".to_string(),
+                    facets: vec![
+                        Facet {
+                            value: json!("news"),
+                            count: 3
+                        },
+                        Facet {
+                            value: json!("wiki"),
+                            count: 2
+                        },
+                    ],
+                },
+                FacetCounts {
+                    column: "lang".to_string(),
+                    facets: vec![
+                        Facet {
+                            value: json!("en"),
+                            count: 2
+                        },
+                        Facet {
+                            value: json!("de"),
+                            count: 1
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use crate::utils::sql_string;
+
+/// Value compared against a column in a `Filter`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    String(String),
+    Int(i64),
+    /// Rendered as a numeric literal, or as a cast of `'NaN'`, `'inf'` or `'-inf'` when not finite.
+    Float(f64),
+    Bool(bool),
+    /// Rendered as a hex literal, compared with the column cast to `BINARY`,
+    /// so that it matches both `Binary` and `FixedSizeBinary` columns (e.g. UUIDs).
+    Binary(Vec<u8>),
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterValue::String(value) => write!(f, "{}", sql_string(value)),
+            FilterValue::Int(value) => write!(f, "{value}"),
+            FilterValue::Float(value) if value.is_nan() => write!(f, "CAST('NaN' AS DOUBLE)"),
+            FilterValue::Float(value) if value.is_infinite() => {
+                let sign = if value.is_sign_negative() { "-" } else { "" };
+                write!(f, "CAST('{sign}inf' AS DOUBLE)")
+            }
+            FilterValue::Float(value) => write!(f, "{value:?}"),
+            FilterValue::Bool(value) => write!(f, "{value}"),
+            FilterValue::Binary(value) => write!(
+                f,
+                "X'{}'",
+                value
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>()
+            ),
+        }
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::String(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::String(value)
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        FilterValue::Int(value)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(value: i32) -> Self {
+        FilterValue::Int(value.into())
+    }
+}
+
+impl From<u32> for FilterValue {
+    fn from(value: u32) -> Self {
+        FilterValue::Int(value.into())
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        FilterValue::Float(value)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        FilterValue::Bool(value)
+    }
+}
+
+impl From<Vec<u8>> for FilterValue {
+    fn from(value: Vec<u8>) -> Self {
+        FilterValue::Binary(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Sql(String),
+    Compare(String, &'static str, FilterValue),
+    In(String, Vec<FilterValue>),
+    IsNull(String),
+    IsNotNull(String),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+/// Type-safe SQL filter applied to LanceDB queries.
+/// String values are escaped when the filter is rendered, so user input can safely be used as a value.
+/// # Example
+/// ```
+/// use izzy_lancedb::Filter;
+///
+/// let filter = Filter::eq("lang", "en").and(Filter::gt("year", 2020));
+///
+/// assert_eq!(filter.to_string(), "(lang = 'en') AND (year > 2020)");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Expr);
+
+impl Filter {
+    /// Raw SQL predicate. The predicate is used as is, without any escaping.
+    pub fn sql(predicate: &str) -> Self {
+        Self(Expr::Sql(predicate.to_string()))
+    }
+
+    /// `column = value`
+    pub fn eq(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, "=", value)
+    }
+
+    /// `column != value`
+    pub fn ne(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, "!=", value)
+    }
+
+    /// `column > value`
+    pub fn gt(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, ">", value)
+    }
+
+    /// `column >= value`
+    pub fn gte(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, ">=", value)
+    }
+
+    /// `column < value`
+    pub fn lt(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, "<", value)
+    }
+
+    /// `column <= value`
+    pub fn lte(column: &str, value: impl Into<FilterValue>) -> Self {
+        Self::compare(column, "<=", value)
+    }
+
+    /// `column IN (values)`. Empty `values` match no row, rendered as `false`.
+    pub fn is_in<V: Into<FilterValue>>(column: &str, values: impl IntoIterator<Item = V>) -> Self {
+        Self(Expr::In(
+            column.to_string(),
+            values.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// `column IS NULL`
+    pub fn is_null(column: &str) -> Self {
+        Self(Expr::IsNull(column.to_string()))
+    }
+
+    /// `column IS NOT NULL`
+    pub fn is_not_null(column: &str) -> Self {
+        Self(Expr::IsNotNull(column.to_string()))
+    }
+
+    /// Both this filter and `other` must match.
+    pub fn and(self, other: Filter) -> Self {
+        Self(Expr::And(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// This filter or `other` must match.
+    pub fn or(self, other: Filter) -> Self {
+        Self(Expr::Or(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// This filter must not match.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self(Expr::Not(Box::new(self.0)))
+    }
+
+    fn compare(column: &str, op: &'static str, value: impl Into<FilterValue>) -> Self {
+        Self(Expr::Compare(column.to_string(), op, value.into()))
+    }
+}
+
+impl From<&str> for Filter {
+    fn from(predicate: &str) -> Self {
+        Filter::sql(predicate)
+    }
+}
+
+impl From<String> for Filter {
+    fn from(predicate: String) -> Self {
+        Self(Expr::Sql(predicate))
+    }
+}
+
+/// Render a column name compared with `values`, cast to `BINARY` if one of the values is binary.
+fn compared_column<'a>(name: &str, mut values: impl Iterator<Item = &'a FilterValue>) -> String {
+    match values.any(|value| matches!(value, FilterValue::Binary(_))) {
+        true => format!("CAST({} AS BINARY)", column(name)),
+        false => column(name),
+    }
+}
+
+/// Render a column name, escaping it with backticks if it is not a plain identifier.
+fn column(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Sql(predicate) => write!(f, "{predicate}"),
+            Expr::Compare(name, op, value) => write!(
+                f,
+                "{} {op} {value}",
+                compared_column(name, [value].into_iter())
+            ),
+            Expr::In(_, values) if values.is_empty() => write!(f, "false"),
+            Expr::In(name, values) => write!(
+                f,
+                "{} IN ({})",
+                compared_column(name, values.iter()),
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expr::IsNull(name) => write!(f, "{} IS NULL", column(name)),
+            Expr::IsNotNull(name) => write!(f, "{} IS NOT NULL", column(name)),
+            Expr::And(left, right) => write!(f, "({left}) AND ({right})"),
+            Expr::Or(left, right) => write!(f, "({left}) OR ({right})"),
+            Expr::Not(expr) => write!(f, "NOT ({expr})"),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+
+    #[test]
+    fn test_filter_rendering() {
+        let filter = Filter::eq("lang", "en")
+            .and(Filter::gt("year", 2020).or(Filter::is_null("year")))
+            .and(Filter::is_in("tenant id", ["a", "b"]).not());
+
+        assert_eq!(
+            filter.to_string(),
+            "((lang = 'en') AND ((year > 2020) OR (year IS NULL))) AND (NOT (`tenant id` IN ('a', 'b')))"
+        );
+    }
+
+    #[test]
+    fn test_filter_value_escaping() {
+        assert_eq!(
+            Filter::eq("author", "O'Brien' OR 1=1 --").to_string(),
+            "author = 'O''Brien'' OR 1=1 --'"
+        );
+        assert_eq!(Filter::lte("score", 0.5).to_string(), "score <= 0.5");
+        assert_eq!(
+            Filter::lt("score", f64::INFINITY).to_string(),
+            "score < CAST('inf' AS DOUBLE)"
+        );
+        assert_eq!(
+            Filter::gt("score", f64::NEG_INFINITY).to_string(),
+            "score > CAST('-inf' AS DOUBLE)"
+        );
+        assert_eq!(
+            Filter::ne("score", f64::NAN).to_string(),
+            "score != CAST('NaN' AS DOUBLE)"
+        );
+        assert_eq!(
+            Filter::is_in("id", [vec![0x01, 0xab], vec![0xff]]).to_string(),
+            "CAST(id AS BINARY) IN (X'01ab', X'ff')"
+        );
+        assert_eq!(
+            Filter::is_in("id", Vec::<String>::new()).not().to_string(),
+            "NOT (false)"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use arrow_array::{cast::AsArray, types::Int64Type};
+use futures::TryStreamExt;
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, utils::scan_all, LanceDbVectorIndex};
+
+/// Column containing the time a document was written or last updated, as seconds since the Unix epoch (`Int64`).
+pub const TIMESTAMP_COLUMN: &str = "timestamp";
+
+/// Key of the `_meta` entry containing the time of the last ingest, as seconds since the Unix epoch.
+pub const LAST_INGEST_META_KEY: &str = "last_ingest";
+
+/// Upper bounds of the age buckets used by `freshness`: one day, one week, 30 days and one year.
+pub const DEFAULT_AGE_BUCKETS: [Duration; 4] = [
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+    Duration::from_secs(30 * 24 * 60 * 60),
+    Duration::from_secs(365 * 24 * 60 * 60),
+];
+
+/// Number of documents of a given age.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgeBucket {
+    /// Maximum age of the documents in the bucket, exclusive.
+    /// `None` for the last bucket, which contains the documents older than every other bucket.
+    pub max_age: Option<Duration>,
+    pub count: usize,
+}
+
+/// Freshness of the corpus of a `LanceDbVectorIndex`, returned by `freshness`.
+/// All timestamps are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Freshness {
+    /// Timestamp of the newest document, `None` if the table is empty.
+    pub newest: Option<i64>,
+    /// Timestamp of the oldest document, `None` if the table is empty.
+    pub oldest: Option<i64>,
+    /// Time of the last ingest, recorded by `insert` and the ingestion pipeline for indexes with a connection,
+    /// or with `record_ingest`, if any.
+    pub last_ingest: Option<i64>,
+    /// Number of documents by age, from the newest to the oldest bucket.
+    pub age_buckets: Vec<AgeBucket>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Report the freshness of the corpus, using `DEFAULT_AGE_BUCKETS`.
+    /// Requires the `timestamp` column (see `SchemaOptions::timestamp_field`).
+    /// Rows with a null timestamp are ignored.
+    /// # Example
+    /// ```
+    /// let freshness = vector_store_index.freshness().await?;
+    ///
+    /// println!("Newest document: {:?}", freshness.newest);
+    /// ```
+    pub async fn freshness(&self) -> Result<Freshness, VectorStoreError> {
+        self.freshness_with_buckets(&DEFAULT_AGE_BUCKETS).await
+    }
+
+    /// Same as `freshness`, with the upper bounds of the age buckets given by `buckets`, in increasing order.
+    pub async fn freshness_with_buckets(
+        &self,
+        buckets: &[Duration],
+    ) -> Result<Freshness, VectorStoreError> {
+        let query =
+            scan_all(&self.table).select(Select::Columns(vec![TIMESTAMP_COLUMN.to_string()]));
+        let mut batches = self.open_stream(&query).await?;
+
+        let mut timestamps = Vec::new();
+
+        while let Some(batch) = batches.try_next().await.map_err(lancedb_to_izzy_error)? {
+            let column =
+                batch
+                    .column_by_name(TIMESTAMP_COLUMN)
+                    .ok_or(VectorStoreError::DatastoreError(
+                        format!("Table has no {TIMESTAMP_COLUMN} column").into(),
+                    ))?;
+
+            let column =
+                column
+                    .as_primitive_opt::<Int64Type>()
+                    .ok_or(VectorStoreError::DatastoreError(
+                        format!("Column {TIMESTAMP_COLUMN} is not of type Int64").into(),
+                    ))?;
+
+            timestamps.extend(column.iter().flatten());
+        }
+
+        let last_ingest = match self.connection {
+            Some(_) => self
+                .get_meta(LAST_INGEST_META_KEY)
+                .await?
+                .and_then(|value| value.parse().ok()),
+            None => None,
+        };
+
+        Ok(Freshness {
+            newest: timestamps.iter().max().copied(),
+            oldest: timestamps.iter().min().copied(),
+            last_ingest,
+            age_buckets: age_buckets(&timestamps, unix_now(), buckets),
+        })
+    }
+
+    /// Record the current time as the time of the last ingest, reported by `freshness`.
+    /// Called by `insert`, so only needed for the rows written to the table by other means.
+    /// Requires a connection (see `LanceDbVectorIndex::connection`).
+    pub async fn record_ingest(&self) -> Result<(), VectorStoreError> {
+        self.set_meta(LAST_INGEST_META_KEY, &unix_now().to_string())
+            .await
+    }
+}
+
+/// Current time as seconds since the Unix epoch.
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Count `timestamps` by age at time `now`. Timestamps in the future count as age zero.
+fn age_buckets(timestamps: &[i64], now: i64, buckets: &[Duration]) -> Vec<AgeBucket> {
+    let mut counts = buckets
+        .iter()
+        .map(|max_age| AgeBucket {
+            max_age: Some(*max_age),
+            count: 0,
+        })
+        .chain([AgeBucket {
+            max_age: None,
+            count: 0,
+        }])
+        .collect::<Vec<_>>();
+
+    for timestamp in timestamps {
+        let age = Duration::from_secs((now - timestamp).max(0) as u64);
+        let position = buckets
+            .iter()
+            .position(|max_age| age < *max_age)
+            .unwrap_or(buckets.len());
+        counts[position].count += 1;
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{age_buckets, AgeBucket};
+
+    #[test]
+    fn test_age_buckets() {
+        let buckets = [Duration::from_secs(10), Duration::from_secs(100)];
+
+        assert_eq!(
+            age_buckets(&[1000, 995, 990, 950, 500, 1010], 1000, &buckets),
+            vec![
+                AgeBucket {
+                    max_age: Some(Duration::from_secs(10)),
+                    count: 3,
+                },
+                AgeBucket {
+                    max_age: Some(Duration::from_secs(100)),
+                    count: 2,
+                },
+                AgeBucket {
+                    max_age: None,
+                    count: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::utils::id_to_string;
+
+/// Name of the column containing the fused score of a row.
+/// This is the column name used by LanceDB for hybrid seizzyh results.
+pub(crate) const RELEVANCE_SCORE_COLUMN: &str = "_relevance_score";
+
+/// Default `k` constant of reciprocal rank fusion.
+pub(crate) const DEFAULT_RRF_K: f64 = 60.0;
+
+/// Fuse ranked lists of rows with reciprocal rank fusion.
+/// Rows are identified by the value of `id_field`. The score of a row is the sum of `1 / (k + rank)`
+/// over every list the row appears in, where `rank` starts at 1.
+/// The fused score is written to the `_relevance_score` column of each row. The columns of a row are those of
+/// its first occurrence, completed with the columns missing from it of its other occurrences, e.g. the full text score.
+/// The returned rows are sorted by decreasing fused score.
+pub(crate) fn reciprocal_rank_fusion(lists: Vec<Vec<Value>>, id_field: &str, k: f64) -> Vec<Value> {
+    weighted_reciprocal_rank_fusion(
+        lists.into_iter().map(|list| (1.0, list)).collect(),
+        id_field,
+        k,
+    )
+}
+
+/// Same as `reciprocal_rank_fusion`, with a weight per list: the score of a row is the sum of `weight / (k + rank)`.
+pub(crate) fn weighted_reciprocal_rank_fusion(
+    lists: Vec<(f64, Vec<Value>)>,
+    id_field: &str,
+    k: f64,
+) -> Vec<Value> {
+    let mut fused: Vec<(f64, Value)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (weight, list) in lists {
+        for (rank, row) in list.into_iter().enumerate() {
+            let score = weight / (k + (rank + 1) as f64);
+
+            let Some(key) = row.get(id_field).and_then(id_to_string) else {
+                continue;
+            };
+
+            match positions.get(&key) {
+                Some(&position) => {
+                    fused[position].0 += score;
+
+                    if let (Value::Object(fused_row), Value::Object(row)) =
+                        (&mut fused[position].1, row)
+                    {
+                        for (column, value) in row {
+                            fused_row.entry(column).or_insert(value);
+                        }
+                    }
+                }
+                None => {
+                    positions.insert(key, fused.len());
+                    fused.push((score, row));
+                }
+            }
+        }
+    }
+
+    fused.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    fused
+        .into_iter()
+        .map(|(score, mut row)| {
+            if let Value::Object(map) = &mut row {
+                map.insert(RELEVANCE_SCORE_COLUMN.to_string(), score.into());
+            }
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        reciprocal_rank_fusion, weighted_reciprocal_rank_fusion, DEFAULT_RRF_K,
+        RELEVANCE_SCORE_COLUMN,
+    };
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let vector_results = vec![
+            json!({"id": "doc0", "_distance": 0.1}),
+            json!({"id": "doc1", "_distance": 0.2}),
+        ];
+        let full_text_results = vec![
+            json!({"id": "doc1", "_score": 3.2}),
+            json!({"id": "doc2", "_score": 1.4}),
+        ];
+
+        let fused =
+            reciprocal_rank_fusion(vec![vector_results, full_text_results], "id", DEFAULT_RRF_K);
+
+        let ids = fused
+            .iter()
+            .map(|row| row["id"].as_str().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec!["doc1", "doc0", "doc2"]);
+        assert_eq!(
+            fused[0][RELEVANCE_SCORE_COLUMN].as_f64().unwrap(),
+            1.0 / 62.0 + 1.0 / 61.0
+        );
+        // The first occurrence of a row is kept, completed with the columns of its other occurrences.
+        assert_eq!(fused[0]["_distance"], json!(0.2));
+        assert_eq!(fused[0]["_score"], json!(3.2));
+    }
+
+    #[test]
+    fn test_weighted_reciprocal_rank_fusion() {
+        let title_results = vec![json!({"id": "doc0"}), json!({"id": "doc1"})];
+        let body_results = vec![json!({"id": "doc1"}), json!({"id": "doc0"})];
+
+        let fused = weighted_reciprocal_rank_fusion(
+            vec![(2.0, title_results), (1.0, body_results)],
+            "id",
+            DEFAULT_RRF_K,
+        );
+
+        assert_eq!(fused[0]["id"], json!("doc0"));
+        assert_eq!(
+            fused[0][RELEVANCE_SCORE_COLUMN].as_f64().unwrap(),
+            2.0 / 61.0 + 1.0 / 62.0
+        );
+    }
+}
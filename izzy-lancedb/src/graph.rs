@@ -0,0 +1,224 @@
+use std::{collections::HashSet, future::Future, sync::izzy};
+
+use futures::{stream, StreamExt, TryStreamExt};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{serde_to_izzy_error, LanceDbVectorIndex, QueryOptions};
+
+/// Node linked to a retrieved result, returned by a `ResultExpander`.
+#[derive(Debug, Clone)]
+pub struct Neighbor {
+    pub id: String,
+    /// Weight of the link between the retrieved result and the node, used to score the node.
+    pub weight: f64,
+    pub document: Value,
+}
+
+/// Extension point used by `top_n_graph` to expand each retrieved result with linked nodes,
+/// e.g. by fetching the neighbors of the result in a graph database.
+pub trait ResultExpander: Send + Sync {
+    /// Get the nodes linked to the result with id `id` and document `document`.
+    fn expand(
+        &self,
+        id: &str,
+        document: &Value,
+    ) -> impl Future<Output = Result<Vec<Neighbor>, VectorStoreError>> + Send;
+}
+
+type MergeScore = izzy<dyn Fn(f64, f64) -> f64 + Send + Sync>;
+
+/// Options of `top_n_graph`.
+#[derive(Clone)]
+pub struct GraphOptions {
+    concurrency: usize,
+    merge_score: MergeScore,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            merge_score: izzy::new(|score: f64, _: f64| score),
+        }
+    }
+}
+
+impl GraphOptions {
+    /// Sets the maximum number of results expanded concurrently. The default is 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Sets the function computing the score of a neighbor from the score of the result it was linked from
+    /// and the weight of the link. By default, a neighbor has the score of its result.
+    pub fn merge_score(
+        mut self,
+        merge_score: impl Fn(f64, f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        self.merge_score = izzy::new(merge_score);
+        self
+    }
+}
+
+/// Result of `top_n_graph`: a retrieved result with the nodes linked to it.
+#[derive(Debug, Clone)]
+pub struct GraphResult<T> {
+    pub score: f64,
+    pub id: String,
+    pub document: T,
+    /// Linked nodes as tuples of the form (score, id, document).
+    pub neighbors: Vec<(f64, String, T)>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, but each result is expanded by `expander`
+    /// with its linked nodes, for GraphRAG-style retrieval.
+    /// Nodes that are already a result, or a neighbor of a better result, are not returned again.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::GraphOptions;
+    ///
+    /// let results = vector_store_index
+    ///     .top_n_graph::<serde_json::Value, _>(
+    ///         "Who founded the company?",
+    ///         5,
+    ///         &graph_expander, // <-- Replace with your `ResultExpander` here.
+    ///         GraphOptions::default().concurrency(8),
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn top_n_graph<T, X>(
+        &self,
+        query: &str,
+        n: usize,
+        expander: &X,
+        options: GraphOptions,
+    ) -> Result<Vec<GraphResult<T>>, VectorStoreError>
+    where
+        T: for<'a> Deserialize<'a> + Send,
+        X: ResultExpander,
+    {
+        let results = self
+            .seizzyh::<Value>(query, n, QueryOptions::default())
+            .await?;
+
+        let expanded = stream::iter(results)
+            .map(|(score, id, document)| async move {
+                let neighbors = expander.expand(&id, &document).await?;
+                Ok::<_, VectorStoreError>((score, id, document, neighbors))
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        merge_neighbors(expanded, &options.merge_score)
+            .into_iter()
+            .map(|result| {
+                Ok(GraphResult {
+                    score: result.score,
+                    id: result.id,
+                    document: serde_json::from_value(result.document)
+                        .map_err(serde_to_izzy_error)?,
+                    neighbors: result
+                        .neighbors
+                        .into_iter()
+                        .map(|(score, id, document)| {
+                            Ok((
+                                score,
+                                id,
+                                serde_json::from_value(document).map_err(serde_to_izzy_error)?,
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, VectorStoreError>>()?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Score the neighbors of each result and drop the neighbors that are a result or a neighbor of a previous result.
+fn merge_neighbors(
+    expanded: Vec<(f64, String, Value, Vec<Neighbor>)>,
+    merge_score: &MergeScore,
+) -> Vec<GraphResult<Value>> {
+    let mut seen = expanded
+        .iter()
+        .map(|(_, id, ..)| id.to_string())
+        .collect::<HashSet<_>>();
+
+    expanded
+        .into_iter()
+        .map(|(score, id, document, neighbors)| GraphResult {
+            score,
+            id,
+            document,
+            neighbors: neighbors
+                .into_iter()
+                .filter(|neighbor| seen.insert(neighbor.id.clone()))
+                .map(|neighbor| {
+                    (
+                        merge_score(score, neighbor.weight),
+                        neighbor.id,
+                        neighbor.document,
+                    )
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use serde_json::json;
+
+    use super::{merge_neighbors, MergeScore, Neighbor};
+
+    fn neighbor(id: &str, weight: f64) -> Neighbor {
+        Neighbor {
+            id: id.to_string(),
+            weight,
+            document: json!({}),
+        }
+    }
+
+    #[test]
+    fn test_merge_neighbors() {
+        let merge_score: MergeScore = izzy::new(|score: f64, weight: f64| score * weight);
+
+        let merged = merge_neighbors(
+            vec![
+                (
+                    1.0,
+                    "a".to_string(),
+                    json!({}),
+                    vec![neighbor("b", 0.5), neighbor("c", 0.5)],
+                ),
+                (
+                    0.8,
+                    "b".to_string(),
+                    json!({}),
+                    vec![neighbor("c", 1.0), neighbor("d", 0.25)],
+                ),
+            ],
+            &merge_score,
+        );
+
+        let neighbors = merged
+            .iter()
+            .map(|result| {
+                result
+                    .neighbors
+                    .iter()
+                    .map(|(score, id, _)| (id.as_str(), *score))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(neighbors, vec![vec![("c", 0.5)], vec![("d", 0.2)]]);
+    }
+}
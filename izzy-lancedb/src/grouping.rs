@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Default number of candidates fetched per result when the results are grouped.
+pub(crate) const DEFAULT_GROUP_FETCH_FACTOR: usize = 4;
+
+/// Keep the first `n` rows of `rows`, in order, with at most `per_group_limit` rows per value of their `column`,
+/// e.g. the best chunk of each parent document. Rows without a value are groups of their own.
+/// The column is removed from the rows if `strip_column` is true.
+pub(crate) fn group_rows(
+    rows: Vec<Value>,
+    column: &str,
+    per_group_limit: usize,
+    n: usize,
+    strip_column: bool,
+) -> Vec<Value> {
+    let mut group_sizes = HashMap::new();
+
+    rows.into_iter()
+        .filter(|row| match row.get(column) {
+            None | Some(Value::Null) => true,
+            Some(value) => {
+                let size = group_sizes.entry(value.to_string()).or_insert(0);
+                *size += 1;
+                *size <= per_group_limit
+            }
+        })
+        .take(n)
+        .map(|mut row| {
+            if let (true, Value::Object(map)) = (strip_column, &mut row) {
+                map.remove(column);
+            }
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::group_rows;
+
+    #[test]
+    fn test_group_rows() {
+        let rows = vec![
+            json!({"id": "a#0", "parent_id": "a"}),
+            json!({"id": "a#1", "parent_id": "a"}),
+            json!({"id": "b#0", "parent_id": "b"}),
+            json!({"id": "c"}),
+            json!({"id": "a#2", "parent_id": "a"}),
+            json!({"id": "d#0", "parent_id": "d"}),
+        ];
+
+        let ids = |rows: Vec<serde_json::Value>| {
+            rows.iter()
+                .map(|row| row["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(group_rows(rows.clone(), "parent_id", 1, 3, false)),
+            vec!["a#0", "b#0", "c"]
+        );
+        assert_eq!(
+            ids(group_rows(rows.clone(), "parent_id", 2, 10, false)),
+            vec!["a#0", "a#1", "b#0", "c", "d#0"]
+        );
+        assert_eq!(
+            group_rows(rows, "parent_id", 1, 1, true),
+            vec![json!({"id": "a#0"})]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{LanceDbVectorIndex, QueryOptions};
+
+/// Result of `top_n_with_vectors`: a seizzyh result along with its stored vector.
+#[derive(Debug, Clone)]
+pub struct SeizzyhHit<T> {
+    pub score: f64,
+    pub id: String,
+    pub document: T,
+    /// Vector of the record, read from the vector column.
+    /// `None` unless vectors are returned by the seizzyh params (see `SeizzyhParams::return_vectors`).
+    pub vector: Option<Vec<f64>>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, returning the vector of each result separately from its document,
+    /// e.g. for client-side reranking or MMR. Vectors are only returned if enabled with `SeizzyhParams::return_vectors`.
+    /// # Example
+    /// ```
+    /// let vector_store_index = LanceDbVectorIndex::new(
+    ///     table,
+    ///     model,
+    ///     "id",
+    ///     SeizzyhParams::default().return_vectors(true),
+    /// )
+    /// .await?;
+    ///
+    /// let hits = vector_store_index
+    ///     .top_n_with_vectors::<WordDefinition>("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_with_vectors<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<SeizzyhHit<T>>, VectorStoreError> {
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        let (results, score_column) = self
+            .seizzyh_rows(&prompt_embedding, Some(query), n, QueryOptions::default())
+            .await?;
+
+        let vector_column = match self.seizzyh_params.return_vectors {
+            true => Some(self.vector_column().await?),
+            false => None,
+        };
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut value)| {
+                let vector = vector_column
+                    .as_ref()
+                    .and_then(|vector_column| take_vector(&mut value, vector_column));
+
+                let (score, id, document) = self.seizzyh_result(i, value, score_column)?;
+
+                Ok(SeizzyhHit {
+                    score,
+                    id,
+                    document,
+                    vector,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Remove the `vector_column` from the `row` and return its values.
+pub(crate) fn take_vector(row: &mut Value, vector_column: &str) -> Option<Vec<f64>> {
+    match row.as_object_mut()?.remove(vector_column)? {
+        Value::Array(values) => Some(values.iter().filter_map(Value::as_f64).collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::take_vector;
+
+    #[test]
+    fn test_take_vector() {
+        let mut row = json!({"id": "doc0", "embedding": [0.5, 1.0]});
+
+        assert_eq!(take_vector(&mut row, "embedding"), Some(vec![0.5, 1.0]));
+        assert_eq!(row, json!({"id": "doc0"}));
+        assert_eq!(take_vector(&mut row, "embedding"), None);
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::izzy;
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::{Map, Value};
+
+use crate::{
+    utils::{id_literal, id_to_string},
+    Filter, FilterValue, LanceDbVectorIndex,
+};
+
+/// Codec of the ids of a table without a single unique column, rendering the string id of a record from
+/// several columns and parsing it back. Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::id_codec`.
+pub trait IdCodec: Send + Sync {
+    /// Columns the id is made of.
+    fn columns(&self) -> Vec<String>;
+
+    /// Render the id of `row`. Returns `None` if a column of the id is missing from the row.
+    fn encode(&self, row: &Map<String, Value>) -> Option<String>;
+
+    /// Parse `id` into the value of each of its columns. Returns `None` if `id` was not rendered by the codec.
+    fn decode(&self, id: &str) -> Option<Vec<(String, String)>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Column(String),
+}
+
+/// `IdCodec` rendering the id from a template of the form `"{doc_id}#{chunk_index}"`,
+/// where the columns are between braces. Consecutive columns must be separated by a literal.
+/// When a value contains the separator that follows it, the id is parsed from the right, so that only
+/// the first column may contain separators.
+/// # Example
+/// ```
+/// use izzy_lancedb::TemplateIdCodec;
+///
+/// let vector_store_index = vector_store_index.id_codec(TemplateIdCodec::new("{doc_id}#{chunk_index}")?);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateIdCodec {
+    segments: Vec<Segment>,
+}
+
+impl TemplateIdCodec {
+    /// Codec of the ids rendered from `template`.
+    /// Fails if the template has no column, unbalanced braces, or consecutive columns without a separator.
+    pub fn new(template: &str) -> Result<Self, VectorStoreError> {
+        let invalid = |reason: &str| {
+            VectorStoreError::DatastoreError(
+                format!("Invalid id template {template}: {reason}").into(),
+            )
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = template;
+
+        while !rest.is_empty() {
+            match rest.find(['{', '}']) {
+                Some(start) if rest[start..].starts_with('{') => {
+                    if start > 0 {
+                        segments.push(Segment::Literal(rest[..start].to_string()));
+                    }
+
+                    let end = rest[start..]
+                        .find('}')
+                        .ok_or_else(|| invalid("unclosed brace"))?
+                        + start;
+                    let column = &rest[start + 1..end];
+
+                    if column.is_empty() || column.contains('{') {
+                        return Err(invalid("invalid column name"));
+                    }
+                    if matches!(segments.last(), Some(Segment::Column(_))) {
+                        return Err(invalid("columns must be separated by a literal"));
+                    }
+
+                    segments.push(Segment::Column(column.to_string()));
+                    rest = &rest[end + 1..];
+                }
+                Some(_) => return Err(invalid("unopened brace")),
+                None => {
+                    segments.push(Segment::Literal(rest.to_string()));
+                    rest = "";
+                }
+            }
+        }
+
+        if !segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Column(_)))
+        {
+            return Err(invalid("no column"));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl IdCodec for TemplateIdCodec {
+    fn columns(&self) -> Vec<String> {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Column(column) => Some(column.clone()),
+                Segment::Literal(_) => None,
+            })
+            .collect()
+    }
+
+    fn encode(&self, row: &Map<String, Value>) -> Option<String> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => Some(literal.clone()),
+                Segment::Column(column) => match row.get(column)? {
+                    Value::Null => None,
+                    value => id_to_string(value).or_else(|| Some(value.to_string())),
+                },
+            })
+            .collect()
+    }
+
+    fn decode(&self, id: &str) -> Option<Vec<(String, String)>> {
+        let mut values = Vec::new();
+        let mut rest = id;
+
+        // Parsed from the right: a column ends where the literal following it ends, and starts
+        // after the last occurrence of the literal preceding it.
+        for (i, segment) in self.segments.iter().enumerate().rev() {
+            match segment {
+                Segment::Literal(literal) => rest = rest.strip_suffix(literal.as_str())?,
+                Segment::Column(column) => {
+                    let start = match i.checked_sub(1).map(|i| &self.segments[i]) {
+                        Some(Segment::Literal(literal)) => {
+                            rest.rfind(literal.as_str())? + literal.len()
+                        }
+                        _ => 0,
+                    };
+
+                    values.push((column.clone(), rest[start..].to_string()));
+                    rest = &rest[..start];
+                }
+            }
+        }
+
+        if !rest.is_empty() {
+            return None;
+        }
+
+        values.reverse();
+        Some(values)
+    }
+}
+
+/// Write the id of `row` rendered by `codec` to its `id_field`.
+pub(crate) fn stamp_id(row: &mut Value, id_field: &str, codec: &dyn IdCodec) {
+    if let Value::Object(row) = row {
+        if let Some(id) = codec.encode(row) {
+            row.insert(id_field.to_string(), id.into());
+        }
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the codec of the ids of the table, for tables without a single unique column.
+    /// The ids of the results are rendered by the codec and returned in the id field of the index,
+    /// which must not be a column of the table. Lookups by id (e.g. `delete_by_ids`, `top_n_by_id`, `neighbors`)
+    /// are parsed by the codec into a filter on its columns.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::TemplateIdCodec;
+    ///
+    /// let vector_store_index = LanceDbVectorIndex::new(table, model, "id", SeizzyhParams::default())
+    ///     .await?
+    ///     .id_codec(TemplateIdCodec::new("{doc_id}#{chunk_index}")?);
+    /// ```
+    pub fn id_codec(mut self, id_codec: impl IdCodec + 'static) -> Self {
+        self.id_codec = Some(izzy::new(id_codec));
+        self
+    }
+
+    /// Columns of the table the ids are read from: the columns of the id codec, or the id field.
+    pub(crate) fn id_columns(&self) -> Vec<String> {
+        match &self.id_codec {
+            Some(id_codec) => id_codec.columns(),
+            None => vec![self.id_field.clone()],
+        }
+    }
+
+    /// Write the id of `row` rendered by the id codec, if any, to the id field.
+    pub(crate) fn stamp_id(&self, row: &mut Value) {
+        if let Some(id_codec) = &self.id_codec {
+            stamp_id(row, &self.id_field, id_codec.as_ref());
+        }
+    }
+
+    /// Filter matching the row with `id`.
+    /// The id is compared as a literal of the type of the id columns, e.g. a number or a UUID (see `id_to_string`).
+    pub(crate) fn id_filter(&self, id: &str) -> Result<Filter, VectorStoreError> {
+        let Some(id_codec) = &self.id_codec else {
+            return Ok(Filter::eq(
+                &self.id_field,
+                self.id_value(&self.id_field, id),
+            ));
+        };
+
+        id_codec
+            .decode(id)
+            .and_then(|values| {
+                values
+                    .into_iter()
+                    .map(|(column, value)| Filter::eq(&column, self.id_value(&column, &value)))
+                    .reduce(Filter::and)
+            })
+            .ok_or_else(|| VectorStoreError::DatastoreError(format!("Invalid id {id}").into()))
+    }
+
+    /// Filter matching the rows with one of `ids`. Empty `ids` match no row.
+    pub(crate) fn ids_filter<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Filter, VectorStoreError> {
+        if self.id_codec.is_none() {
+            return Ok(Filter::is_in(
+                &self.id_field,
+                ids.into_iter().map(|id| self.id_value(&self.id_field, id)),
+            ));
+        }
+
+        ids.into_iter()
+            .map(|id| self.id_filter(id))
+            .reduce(|a, b| Ok(a?.or(b?)))
+            .unwrap_or_else(|| Ok(Filter::is_in(&self.id_field, Vec::<FilterValue>::new())))
+    }
+
+    /// Literal of the value `id` of the id column `column`, of the type of the column.
+    fn id_value(&self, column: &str, id: &str) -> FilterValue {
+        id_literal(
+            id,
+            self.schema
+                .field_with_name(column)
+                .ok()
+                .map(|field| field.data_type()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{stamp_id, IdCodec, TemplateIdCodec};
+
+    #[test]
+    fn test_template_id_codec() {
+        let codec = TemplateIdCodec::new("{doc_id}#{chunk_index}").unwrap();
+        assert_eq!(codec.columns(), vec!["doc_id", "chunk_index"]);
+
+        let mut row = json!({"doc_id": "a#b", "chunk_index": 3, "text": "..."});
+        stamp_id(&mut row, "id", &codec);
+        assert_eq!(row["id"], json!("a#b#3"));
+
+        assert_eq!(
+            codec.decode("a#b#3"),
+            Some(vec![
+                ("doc_id".to_string(), "a#b".to_string()),
+                ("chunk_index".to_string(), "3".to_string())
+            ])
+        );
+        assert_eq!(codec.decode("a"), None);
+
+        let codec = TemplateIdCodec::new("doc-{doc_id}").unwrap();
+        assert_eq!(
+            codec.decode("doc-a"),
+            Some(vec![("doc_id".to_string(), "a".to_string())])
+        );
+        assert_eq!(codec.decode("a"), None);
+        assert_eq!(
+            codec.encode(json!({"doc_id": null}).as_object().unwrap()),
+            None
+        );
+
+        assert!(TemplateIdCodec::new("{doc_id}{chunk_index}").is_err());
+        assert!(TemplateIdCodec::new("{doc_id").is_err());
+        assert!(TemplateIdCodec::new("doc_id}").is_err());
+        assert!(TemplateIdCodec::new("doc_id").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::izzy;
+
+use arrow_array::{new_null_array, ArrayRef, LargeStringArray, RecordBatch, StringArray};
+use lancedb::arrow::arrow_schema::DataType;
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{FilterTableColumns, RecordBatchDeserializer},
+    LanceDbVectorIndex,
+};
+
+/// How the ids of the rows ingested without an id are generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IdGeneration {
+    /// Random, time-ordered UUIDv7 ids. This is the default.
+    #[default]
+    UuidV7,
+    /// UUIDv5 ids hashed from the content of the row, so that re-ingesting the same content yields the same id.
+    ContentHash,
+}
+
+impl IdGeneration {
+    /// Generate the id of a row with the given `content`. The content is only used by `IdGeneration::ContentHash`.
+    pub fn generate(&self, content: &str) -> String {
+        match self {
+            Self::UuidV7 => Uuid::now_v7().to_string(),
+            Self::ContentHash => Uuid::new_v5(&Uuid::NAMESPACE_OID, content.as_bytes()).to_string(),
+        }
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `insert`, for record batches without the id column: an id is generated for each row
+    /// with `id_generation`, and the generated ids are returned in the order of the rows.
+    /// Content hashes are computed from the columns of the row, except the embedding columns.
+    /// The other columns of the table missing from the record batches must be nullable.
+    /// The id column of the table must be a string column.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::IdGeneration;
+    ///
+    /// let ids = vector_store_index
+    ///     .insert_with_generated_ids(vec![record_batch], IdGeneration::UuidV7)
+    ///     .await?;
+    /// ```
+    pub async fn insert_with_generated_ids(
+        &self,
+        record_batches: Vec<RecordBatch>,
+        id_generation: IdGeneration,
+    ) -> Result<Vec<String>, VectorStoreError> {
+        let schema = self.table.schema().await.map_err(lancedb_to_izzy_error)?;
+        let id_type = schema
+            .field_with_name(&self.id_field)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?
+            .data_type()
+            .clone();
+
+        if !matches!(id_type, DataType::Utf8 | DataType::LargeUtf8) {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Ids can only be generated for a string id column, {} is {id_type}",
+                    self.id_field
+                )
+                .into(),
+            ));
+        }
+
+        let vector_column = self.vector_column().await?;
+        let content_columns = schema
+            .clone()
+            .filter_embeddings()
+            .into_iter()
+            .filter(|column| column != &vector_column && column != &self.id_field)
+            .collect::<Vec<_>>();
+
+        let mut ids = Vec::new();
+        let mut with_ids = Vec::with_capacity(record_batches.len());
+
+        for record_batch in record_batches {
+            let batch_ids = match id_generation {
+                IdGeneration::UuidV7 => (0..record_batch.num_rows())
+                    .map(|_| id_generation.generate(""))
+                    .collect::<Vec<_>>(),
+                IdGeneration::ContentHash => record_batch
+                    .deserialize()?
+                    .into_iter()
+                    .map(|row| id_generation.generate(&row_content(row, &content_columns)))
+                    .collect(),
+            };
+
+            let columns = schema
+                .fields()
+                .iter()
+                .map(|field| -> ArrayRef {
+                    match (field.name() == &self.id_field, &id_type) {
+                        (true, DataType::LargeUtf8) => {
+                            izzy::new(LargeStringArray::from_iter_values(&batch_ids))
+                        }
+                        (true, _) => izzy::new(StringArray::from_iter_values(&batch_ids)),
+                        (false, _) => match record_batch.column_by_name(field.name()) {
+                            Some(column) => column.clone(),
+                            None => new_null_array(field.data_type(), record_batch.num_rows()),
+                        },
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            with_ids.push(
+                RecordBatch::try_new(schema.clone(), columns)
+                    .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?,
+            );
+            ids.extend(batch_ids);
+        }
+
+        self.insert(with_ids).await?;
+
+        Ok(ids)
+    }
+}
+
+/// Content of a `row` hashed by `IdGeneration::ContentHash`: its `content_columns`, serialized as JSON with sorted keys.
+fn row_content(mut row: Value, content_columns: &[String]) -> String {
+    if let Value::Object(map) = &mut row {
+        map.retain(|column, _| content_columns.contains(column));
+    }
+
+    row.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{row_content, IdGeneration};
+
+    #[test]
+    fn test_id_generation() {
+        let first = IdGeneration::UuidV7.generate("");
+        let second = IdGeneration::UuidV7.generate("");
+
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 36);
+        assert_eq!(&first[14..15], "7");
+
+        assert_eq!(
+            IdGeneration::ContentHash.generate("zindle"),
+            IdGeneration::ContentHash.generate("zindle")
+        );
+        assert_ne!(
+            IdGeneration::ContentHash.generate("zindle"),
+            IdGeneration::ContentHash.generate("flurbo")
+        );
+    }
+
+    #[test]
+    fn test_row_content() {
+        assert_eq!(
+            row_content(
+                json!({"word": "zindle", "embedding": [0.5, 1.0], "lang": "en"}),
+                &["word".to_string(), "lang".to_string()]
+            ),
+            json!({"lang": "en", "word": "zindle"}).to_string()
+        );
+    }
+}
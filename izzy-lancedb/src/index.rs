@@ -0,0 +1,293 @@
+use std::time::Instant;
+
+use lancedb::{
+    index::{
+        vector::{
+            IvfFlatIndexBuilder, IvfHnswPqIndexBuilder, IvfHnswSqIndexBuilder, IvfPqIndexBuilder,
+        },
+        Index,
+    },
+    DistanceType,
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    lancedb_to_izzy_error,
+    trace::{elapsed_ms, record},
+    Distance, LanceDbVectorIndex, MutationEvent,
+};
+
+/// Type of ANN index that can be created on the vector column of a LanceDB table.
+/// See [LanceDB ANN indexes](https://lancedb.github.io/lancedb/ann_indexes/) for more information.
+#[derive(Debug, Clone)]
+pub enum VectorIndexType {
+    /// Inverted file index with product quantization.
+    IvfPq,
+    /// Inverted file index storing the raw vectors.
+    IvfFlat,
+    /// Inverted file index with an HNSW graph in each partition.
+    Hnsw,
+}
+
+/// Quantization of the vectors of an ANN index, trading recall for storage and latency.
+/// See [LanceDB ANN indexes](https://lancedb.github.io/lancedb/ann_indexes/) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quantization {
+    /// Default quantization of the index type: product quantization for `IvfPq`, none for `IvfFlat`,
+    /// scalar quantization for `Hnsw`.
+    Default,
+    /// Product quantization: each vector is split in sub-vectors (see `IndexBuilder::num_sub_vectors`), each stored as a code.
+    /// Supported by `IvfPq` and `Hnsw` indexes.
+    Product,
+    /// Scalar quantization: each dimension is stored on 8 bits. Supported by `Hnsw` indexes.
+    Scalar,
+    /// Binary vectors, compared with the hamming distance. Supported by `IvfFlat` indexes
+    /// on columns of packed bits (`FixedSizeList<UInt8>`), with `Distance::Hamming`.
+    Binary,
+}
+
+/// Builder used to create an ANN index on the vector column of the table of a `LanceDbVectorIndex`.
+/// # Example
+/// ```
+/// use izzy_lancedb::VectorIndexType;
+///
+/// vector_store_index
+///     .index_builder(VectorIndexType::IvfPq)
+///     .num_partitions(256)
+///     .num_sub_vectors(96)
+///     .build()
+///     .await?;
+/// ```
+pub struct IndexBuilder<'a, M: EmbeddingModel> {
+    vector_index: &'a LanceDbVectorIndex<M>,
+    index_type: VectorIndexType,
+    column: Option<String>,
+    distance_type: Option<Distance>,
+    num_partitions: Option<u32>,
+    num_sub_vectors: Option<u32>,
+    quantization: Quantization,
+    replace: bool,
+}
+
+impl<'a, M: EmbeddingModel> IndexBuilder<'a, M> {
+    pub(crate) fn new(
+        vector_index: &'a LanceDbVectorIndex<M>,
+        index_type: VectorIndexType,
+    ) -> Self {
+        Self {
+            vector_index,
+            index_type,
+            column: None,
+            distance_type: None,
+            num_partitions: None,
+            num_sub_vectors: None,
+            quantization: Quantization::Default,
+            replace: true,
+        }
+    }
+
+    /// Sets the column on which the index is created.
+    /// Defaults to the column of the seizzyh params of the vector index.
+    pub fn column(mut self, column: &str) -> Self {
+        self.column = Some(column.to_string());
+        self
+    }
+
+    /// Sets the distance type used to train the index.
+    /// Defaults to the distance type of the seizzyh params of the vector index.
+    /// The distance type must match the distance type of the seizzyh params.
+    pub fn distance_type(mut self, distance_type: impl Into<Distance>) -> Self {
+        self.distance_type = Some(distance_type.into());
+        self
+    }
+
+    /// Sets the number of IVF partitions of the index.
+    /// If not set, LanceDB chooses a value based on the number of rows in the table.
+    pub fn num_partitions(mut self, num_partitions: u32) -> Self {
+        self.num_partitions = Some(num_partitions);
+        self
+    }
+
+    /// Sets the number of PQ sub-vectors of the index.
+    /// Only set this value when the index uses product quantization.
+    pub fn num_sub_vectors(mut self, num_sub_vectors: u32) -> Self {
+        self.num_sub_vectors = Some(num_sub_vectors);
+        self
+    }
+
+    /// Sets the quantization of the vectors of the index. The default is `Quantization::Default`.
+    pub fn quantization(mut self, quantization: Quantization) -> Self {
+        self.quantization = quantization;
+        self
+    }
+
+    /// Sets whether an existing index on the column is replaced. The default is true.
+    pub fn replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self
+    }
+
+    /// Create the index on the table.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.build_index",
+            skip_all,
+            fields(
+                table = self.vector_index.table.name(),
+                index_type = ?self.index_type,
+                column = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn build(self) -> Result<(), VectorStoreError> {
+        let _operation = self.vector_index.lifecycle.begin()?;
+        let start = Instant::now();
+
+        let seizzyh_params = &self.vector_index.seizzyh_params;
+
+        let column = self.column.or(seizzyh_params.column.clone()).ok_or(
+            VectorStoreError::DatastoreError(
+                "No vector column set on the index builder or the seizzyh params".into(),
+            ),
+        )?;
+
+        // LanceDB uses L2 when no distance type is set on the query.
+        let params_distance_type = seizzyh_params.distance_type.unwrap_or_default();
+        let distance_type = self.distance_type.unwrap_or(params_distance_type);
+
+        if distance_type != params_distance_type {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Index distance type {distance_type:?} does not match the seizzyh params distance type {params_distance_type:?}"
+                )
+                .into(),
+            ));
+        }
+
+        let quantization = resolve_quantization(&self.index_type, &self.quantization)?;
+
+        if self.num_sub_vectors.is_some() && quantization != Quantization::Product {
+            return Err(VectorStoreError::DatastoreError(
+                "num_sub_vectors is only supported by product quantized indexes".into(),
+            ));
+        }
+
+        if quantization == Quantization::Binary && distance_type != Distance::Hamming {
+            return Err(VectorStoreError::DatastoreError(
+                "Binary quantization requires the hamming distance type".into(),
+            ));
+        }
+
+        // Without quantization, the distances of the index are exact and there is nothing to refine.
+        if seizzyh_params.refine_factor.is_some()
+            && matches!(quantization, Quantization::Default | Quantization::Binary)
+        {
+            return Err(VectorStoreError::DatastoreError(
+                "The refine_factor of the seizzyh params requires a product or scalar quantized index"
+                    .into(),
+            ));
+        }
+
+        let distance_type = DistanceType::from(distance_type);
+
+        let index = match (&self.index_type, quantization) {
+            (VectorIndexType::IvfPq, _) => {
+                let mut builder = IvfPqIndexBuilder::default().distance_type(distance_type);
+                if let Some(num_partitions) = self.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                if let Some(num_sub_vectors) = self.num_sub_vectors {
+                    builder = builder.num_sub_vectors(num_sub_vectors);
+                }
+                Index::IvfPq(builder)
+            }
+            (VectorIndexType::IvfFlat, _) => {
+                let mut builder = IvfFlatIndexBuilder::default().distance_type(distance_type);
+                if let Some(num_partitions) = self.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                Index::IvfFlat(builder)
+            }
+            (VectorIndexType::Hnsw, Quantization::Product) => {
+                let mut builder = IvfHnswPqIndexBuilder::default().distance_type(distance_type);
+                if let Some(num_partitions) = self.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                if let Some(num_sub_vectors) = self.num_sub_vectors {
+                    builder = builder.num_sub_vectors(num_sub_vectors);
+                }
+                Index::IvfHnswPq(builder)
+            }
+            (VectorIndexType::Hnsw, _) => {
+                let mut builder = IvfHnswSqIndexBuilder::default().distance_type(distance_type);
+                if let Some(num_partitions) = self.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                Index::IvfHnswSq(builder)
+            }
+        };
+
+        self.vector_index
+            .table
+            .create_index(&[column.as_str()], index)
+            .replace(self.replace)
+            .execute()
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+        record!(column = column.as_str(), latency_ms = elapsed_ms(start));
+
+        self.vector_index
+            .emit(MutationEvent::IndexBuilt { column })
+            .await;
+
+        Ok(())
+    }
+}
+
+/// Resolve the `quantization` of an index of type `index_type`, replacing `Quantization::Default`
+/// with the quantization of the index type (`Quantization::Default` for unquantized indexes).
+fn resolve_quantization(
+    index_type: &VectorIndexType,
+    quantization: &Quantization,
+) -> Result<Quantization, VectorStoreError> {
+    match (index_type, quantization) {
+        (VectorIndexType::IvfPq, Quantization::Default | Quantization::Product) => {
+            Ok(Quantization::Product)
+        }
+        (VectorIndexType::IvfFlat, Quantization::Default) => Ok(Quantization::Default),
+        (VectorIndexType::IvfFlat, Quantization::Binary) => Ok(Quantization::Binary),
+        (VectorIndexType::Hnsw, Quantization::Default | Quantization::Scalar) => {
+            Ok(Quantization::Scalar)
+        }
+        (VectorIndexType::Hnsw, Quantization::Product) => Ok(Quantization::Product),
+        (index_type, quantization) => Err(VectorStoreError::DatastoreError(
+            format!("{quantization:?} quantization is not supported by {index_type:?} indexes")
+                .into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_quantization, Quantization, VectorIndexType};
+
+    #[test]
+    fn test_resolve_quantization() {
+        assert_eq!(
+            resolve_quantization(&VectorIndexType::IvfPq, &Quantization::Default).unwrap(),
+            Quantization::Product
+        );
+        assert_eq!(
+            resolve_quantization(&VectorIndexType::Hnsw, &Quantization::Default).unwrap(),
+            Quantization::Scalar
+        );
+        assert_eq!(
+            resolve_quantization(&VectorIndexType::IvfFlat, &Quantization::Binary).unwrap(),
+            Quantization::Binary
+        );
+        assert!(resolve_quantization(&VectorIndexType::IvfPq, &Quantization::Scalar).is_err());
+        assert!(resolve_quantization(&VectorIndexType::Hnsw, &Quantization::Binary).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::izzy;
+
+/// Reason why a chunk was rejected or flagged by an `IngestFilter`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkIssue {
+    /// The chunk is shorter than the minimum length.
+    TooShort,
+    /// The chunk is longer than the maximum length.
+    TooLong,
+    /// The detected language of the chunk is not in the language allowlist.
+    Language(Option<String>),
+    /// The boilerplate detector matched the chunk.
+    Boilerplate,
+}
+
+/// Defines what happens to chunks that do not pass an `IngestFilter`.
+#[derive(Debug, Clone, Default)]
+pub enum IngestFilterAction {
+    /// Drop the chunk.
+    #[default]
+    Reject,
+    /// Keep the chunk and report the issue alongside it.
+    Flag,
+}
+
+/// Counts of the chunks checked by an `IngestFilter`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngestReport {
+    /// Number of chunks that passed all checks.
+    pub accepted: usize,
+    /// Number of chunks that were dropped.
+    pub rejected: usize,
+    /// Number of chunks that were kept with an issue.
+    pub flagged: usize,
+    /// Number of chunks shorter than the minimum length.
+    pub too_short: usize,
+    /// Number of chunks longer than the maximum length.
+    pub too_long: usize,
+    /// Number of chunks whose detected language is not in the language allowlist.
+    pub wrong_language: usize,
+    /// Number of chunks matched by the boilerplate detector.
+    pub boilerplate: usize,
+    /// Number of chunks whose content hash already existed, skipped or replaced by the dedup mode of the ingestion pipeline.
+    pub duplicates: usize,
+}
+
+impl IngestReport {
+    fn record(&mut self, issue: &ChunkIssue) {
+        match issue {
+            ChunkIssue::TooShort => self.too_short += 1,
+            ChunkIssue::TooLong => self.too_long += 1,
+            ChunkIssue::Language(_) => self.wrong_language += 1,
+            ChunkIssue::Boilerplate => self.boilerplate += 1,
+        }
+    }
+}
+
+type LanguageDetector = izzy<dyn Fn(&str) -> Option<String> + Send + Sync>;
+type BoilerplateDetector = izzy<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Ingest-time guards that keep low quality chunks out of the table.
+/// Lengths are measured in characters.
+/// # Example
+/// ```
+/// let ingest_filter = izzy_lancedb::IngestFilter::default()
+///     .min_length(20)
+///     .max_length(8000)
+///     .boilerplate_detector(|text| text.contains("All rights reserved"));
+///
+/// let (chunks, report) = ingest_filter.apply(chunks, |chunk: &String| chunk.as_str());
+/// ```
+#[derive(Clone, Default)]
+pub struct IngestFilter {
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    languages: Option<Vec<String>>,
+    language_detector: Option<LanguageDetector>,
+    boilerplate_detector: Option<BoilerplateDetector>,
+    action: IngestFilterAction,
+}
+
+impl IngestFilter {
+    /// Sets the minimum length of a chunk.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the maximum length of a chunk.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets the allowed languages of a chunk, together with the function used to detect the language of a chunk.
+    /// Chunks for which no language is detected do not pass the check.
+    pub fn languages(
+        mut self,
+        languages: &[&str],
+        detector: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.languages = Some(languages.iter().map(|lang| lang.to_string()).collect());
+        self.language_detector = Some(izzy::new(detector));
+        self
+    }
+
+    /// Sets the function used to detect boilerplate chunks (e.g. cookie banners, navigation menus).
+    pub fn boilerplate_detector(
+        mut self,
+        detector: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.boilerplate_detector = Some(izzy::new(detector));
+        self
+    }
+
+    /// Sets what happens to chunks that do not pass the checks. The default is `IngestFilterAction::Reject`.
+    pub fn action(mut self, action: IngestFilterAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Check a single chunk. Returns the first issue found, if any.
+    pub fn check(&self, text: &str) -> Option<ChunkIssue> {
+        let length = text.chars().count();
+
+        if self
+            .min_length
+            .is_some_and(|min_length| length < min_length)
+        {
+            return Some(ChunkIssue::TooShort);
+        }
+
+        if self
+            .max_length
+            .is_some_and(|max_length| length > max_length)
+        {
+            return Some(ChunkIssue::TooLong);
+        }
+
+        if let (Some(languages), Some(detector)) = (&self.languages, &self.language_detector) {
+            let language = detector(text);
+            if !language
+                .as_ref()
+                .is_some_and(|language| languages.contains(language))
+            {
+                return Some(ChunkIssue::Language(language));
+            }
+        }
+
+        if let Some(detector) = &self.boilerplate_detector {
+            if detector(text) {
+                return Some(ChunkIssue::Boilerplate);
+            }
+        }
+
+        None
+    }
+
+    /// Check every chunk in `chunks`, using `text` to get the text of a chunk.
+    /// Returns the chunks that are kept, each with its issue when it was flagged, and the report of the checks.
+    pub fn apply<T>(
+        &self,
+        chunks: Vec<T>,
+        text: impl Fn(&T) -> &str,
+    ) -> (Vec<(T, Option<ChunkIssue>)>, IngestReport) {
+        let mut report = IngestReport::default();
+
+        let chunks = chunks
+            .into_iter()
+            .filter_map(|chunk| match self.check(text(&chunk)) {
+                None => {
+                    report.accepted += 1;
+                    Some((chunk, None))
+                }
+                Some(issue) => {
+                    report.record(&issue);
+                    match self.action {
+                        IngestFilterAction::Reject => {
+                            report.rejected += 1;
+                            None
+                        }
+                        IngestFilterAction::Flag => {
+                            report.flagged += 1;
+                            Some((chunk, Some(issue)))
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        (chunks, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkIssue, IngestFilter, IngestFilterAction, IngestReport};
+
+    fn chunks() -> Vec<String> {
+        vec![
+            "ok".to_string(),
+            "A chunk of text long enough to be kept.".to_string(),
+            "Cookie settings: accept all cookies to continue.".to_string(),
+            "Ein deutscher Satz, der lang genug ist.".to_string(),
+        ]
+    }
+
+    fn ingest_filter() -> IngestFilter {
+        IngestFilter::default()
+            .min_length(10)
+            .languages(&["en"], |text| {
+                Some(if text.starts_with("Ein") { "de" } else { "en" }.to_string())
+            })
+            .boilerplate_detector(|text| text.starts_with("Cookie"))
+    }
+
+    #[test]
+    fn test_reject_chunks() {
+        let (kept, report) = ingest_filter().apply(chunks(), |chunk| chunk.as_str());
+
+        assert_eq!(
+            kept,
+            vec![("A chunk of text long enough to be kept.".to_string(), None)]
+        );
+        assert_eq!(
+            report,
+            IngestReport {
+                accepted: 1,
+                rejected: 3,
+                flagged: 0,
+                too_short: 1,
+                too_long: 0,
+                wrong_language: 1,
+                boilerplate: 1,
+                duplicates: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_flag_chunks() {
+        let (kept, report) = ingest_filter()
+            .action(IngestFilterAction::Flag)
+            .apply(chunks(), |chunk| chunk.as_str());
+
+        assert_eq!(
+            kept.into_iter().map(|(_, issue)| issue).collect::<Vec<_>>(),
+            vec![
+                Some(ChunkIssue::TooShort),
+                None,
+                Some(ChunkIssue::Boilerplate),
+                Some(ChunkIssue::Language(Some("de".to_string()))),
+            ]
+        );
+        assert_eq!(report.flagged, 3);
+        assert_eq!(report.rejected, 0);
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::izzy;
+
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray, UInt64Array};
+use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{scan_all, QueryToJson},
+    LanceDbVectorIndex,
+};
+
+/// Run of the ingestion pipeline, recorded in the `<table>_ingest_jobs` companion table
+/// (see `IngestionPipeline::record_job`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IngestJob {
+    pub id: String,
+    /// Source of the ingested documents, e.g. the name of a crawl or an import.
+    pub source: String,
+    /// Start of the run, as seconds since the Unix epoch.
+    pub started_at: i64,
+    pub duration_ms: i64,
+    /// Number of documents ingested by the run.
+    pub documents: i64,
+    /// Number of chunks written to the table.
+    pub rows_written: i64,
+    /// Number of chunks dropped by the ingest filter of the pipeline.
+    pub rejected: i64,
+    /// Number of chunks kept with an issue by the ingest filter of the pipeline.
+    pub flagged: i64,
+    /// Number of chunks whose content hash already existed.
+    pub duplicates: i64,
+    /// Error of the run, `None` if it succeeded.
+    pub error: Option<String>,
+    /// Version of the table after the run, `None` if it could not be read.
+    pub table_version: Option<u64>,
+}
+
+fn jobs_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("source", DataType::Utf8, false),
+        Field::new("started_at", DataType::Int64, false),
+        Field::new("duration_ms", DataType::Int64, false),
+        Field::new("documents", DataType::Int64, false),
+        Field::new("rows_written", DataType::Int64, false),
+        Field::new("rejected", DataType::Int64, false),
+        Field::new("flagged", DataType::Int64, false),
+        Field::new("duplicates", DataType::Int64, false),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("table_version", DataType::UInt64, true),
+    ])
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// The most recent `limit` runs of the ingestion pipeline recorded for the table, newest first.
+    /// Returns no runs if the `_ingest_jobs` companion table does not exist.
+    /// # Example
+    /// ```
+    /// for job in vector_store_index.ingest_jobs(10).await? {
+    ///     println!("{} {}: {} rows in {}ms", job.started_at, job.source, job.rows_written, job.duration_ms);
+    /// }
+    /// ```
+    pub async fn ingest_jobs(&self, limit: usize) -> Result<Vec<IngestJob>, VectorStoreError> {
+        let Some(table) = self.jobs_table().await? else {
+            return Ok(vec![]);
+        };
+
+        let mut jobs = scan_all(&table)
+            .execute_query()
+            .await?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<IngestJob>, _>>()?;
+
+        jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        jobs.truncate(limit);
+
+        Ok(jobs)
+    }
+
+    /// Add `job` to the `<table>_ingest_jobs` companion table, created on first use.
+    pub(crate) async fn record_ingest_job(&self, job: &IngestJob) -> Result<(), VectorStoreError> {
+        let table = match self.jobs_table().await? {
+            Some(table) => table,
+            None => {
+                let connection = self.meta_connection()?;
+                match connection
+                    .create_empty_table(self.jobs_table_name(), izzy::new(jobs_schema()))
+                    .execute()
+                    .await
+                {
+                    Ok(table) => table,
+                    // Created concurrently by another writer.
+                    Err(lancedb::Error::TableAlreadyExists { .. }) => connection
+                        .open_table(self.jobs_table_name())
+                        .execute()
+                        .await
+                        .map_err(lancedb_to_izzy_error)?,
+                    Err(e) => return Err(lancedb_to_izzy_error(e)),
+                }
+            }
+        };
+
+        let schema = izzy::new(jobs_schema());
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                izzy::new(StringArray::from(vec![job.id.as_str()])),
+                izzy::new(StringArray::from(vec![job.source.as_str()])),
+                izzy::new(Int64Array::from(vec![job.started_at])),
+                izzy::new(Int64Array::from(vec![job.duration_ms])),
+                izzy::new(Int64Array::from(vec![job.documents])),
+                izzy::new(Int64Array::from(vec![job.rows_written])),
+                izzy::new(Int64Array::from(vec![job.rejected])),
+                izzy::new(Int64Array::from(vec![job.flagged])),
+                izzy::new(Int64Array::from(vec![job.duplicates])),
+                izzy::new(StringArray::from(vec![job.error.as_deref()])),
+                izzy::new(UInt64Array::from(vec![job.table_version])),
+            ],
+        )
+        .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        table
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    fn jobs_table_name(&self) -> String {
+        format!("{}_ingest_jobs", self.table.name())
+    }
+
+    /// Open the `_ingest_jobs` companion table, if it exists.
+    async fn jobs_table(&self) -> Result<Option<lancedb::Table>, VectorStoreError> {
+        match self
+            .meta_connection()?
+            .open_table(self.jobs_table_name())
+            .execute()
+            .await
+        {
+            Ok(table) => Ok(Some(table)),
+            Err(lancedb::Error::TableNotFound { .. }) => Ok(None),
+            Err(e) => Err(lancedb_to_izzy_error(e)),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    serde_to_izzy_error,
+    utils::{id_literal, id_to_string},
+    Filter, LanceDbVectorIndex, QueryOptions,
+};
+
+/// Entity matched by `top_n_joined`, with the chunks that reference it.
+#[derive(Debug, Clone)]
+pub struct EntityMatch<E, C> {
+    /// Score of the entity in the entity seizzyh.
+    pub score: f64,
+    /// Id of the entity.
+    pub id: String,
+    pub entity: E,
+    /// Matching chunks of the entity, as tuples of the form (score, id, document), best match first.
+    pub chunks: Vec<(f64, String, C)>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Two-index retrieval over an entity table (e.g. people, products) and a chunk table.
+    /// The `n_entities` best entities for `query` are seizzyhed in this index, then the `n_chunks` best chunks
+    /// for `query` are seizzyhed in `chunks`, prefiltered on the `foreign_key` column of the chunk table
+    /// containing the id of the entity a chunk belongs to. Foreign keys are compared with the ids of the entities
+    /// as values of the type of the column, e.g. numbers or UUIDs.
+    /// Chunks are returned grouped under their entity. Entities are ordered by their score,
+    /// and entities without any matching chunk are returned with no chunks.
+    /// # Example
+    /// ```
+    /// let matches = products_index
+    ///     .top_n_joined::<Product, Review, _>(&reviews_index, "product_id", "durable hiking boots", 5, 20)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_joined<E, C, CM>(
+        &self,
+        chunks: &LanceDbVectorIndex<CM>,
+        foreign_key: &str,
+        query: &str,
+        n_entities: usize,
+        n_chunks: usize,
+    ) -> Result<Vec<EntityMatch<E, C>>, VectorStoreError>
+    where
+        E: for<'a> Deserialize<'a> + Send,
+        C: for<'a> Deserialize<'a> + Send,
+        CM: EmbeddingModel,
+    {
+        let entities = self
+            .seizzyh::<E>(query, n_entities, QueryOptions::default())
+            .await?;
+
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let foreign_key_type = chunks
+            .schema
+            .field_with_name(foreign_key)
+            .ok()
+            .map(|field| field.data_type());
+        let entity_filter = Filter::is_in(
+            foreign_key,
+            entities
+                .iter()
+                .map(|(_, id, _)| id_literal(id, foreign_key_type)),
+        );
+
+        let chunks = chunks
+            .seizzyh::<Value>(
+                query,
+                n_chunks,
+                QueryOptions::default().filter(entity_filter),
+            )
+            .await?;
+
+        group_chunks(entities, chunks, foreign_key)
+            .into_iter()
+            .map(|entity_match| {
+                Ok(EntityMatch {
+                    score: entity_match.score,
+                    id: entity_match.id,
+                    entity: entity_match.entity,
+                    chunks: entity_match
+                        .chunks
+                        .into_iter()
+                        .map(|(score, id, value)| {
+                            Ok((
+                                score,
+                                id,
+                                serde_json::from_value(value).map_err(serde_to_izzy_error)?,
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, VectorStoreError>>()?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Group `chunks` under the entity referenced by their `foreign_key` column, rendered as an id (see `id_to_string`).
+/// Chunks referencing none of the `entities` are dropped.
+fn group_chunks<E>(
+    entities: Vec<(f64, String, E)>,
+    chunks: Vec<(f64, String, Value)>,
+    foreign_key: &str,
+) -> Vec<EntityMatch<E, Value>> {
+    let mut matches = entities
+        .into_iter()
+        .map(|(score, id, entity)| EntityMatch {
+            score,
+            id,
+            entity,
+            chunks: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for chunk in chunks {
+        let Some(entity_id) = chunk.2.get(foreign_key).and_then(id_to_string) else {
+            continue;
+        };
+
+        if let Some(entity_match) = matches.iter_mut().find(|m| m.id == entity_id) {
+            entity_match.chunks.push(chunk);
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::group_chunks;
+
+    #[test]
+    fn test_group_chunks() {
+        let entities = vec![
+            (0.1, "p0".to_string(), ()),
+            (0.2, "p1".to_string(), ()),
+            (0.3, "7".to_string(), ()),
+        ];
+        let chunks = vec![
+            (0.3, "c0".to_string(), json!({"product_id": "p1"})),
+            (0.4, "c1".to_string(), json!({"product_id": "p2"})),
+            (0.5, "c2".to_string(), json!({"product_id": "p1"})),
+            (0.6, "c3".to_string(), json!({"product_id": 7})),
+        ];
+
+        let matches = group_chunks(entities, chunks, "product_id");
+
+        let grouped = matches
+            .iter()
+            .map(|m| {
+                (
+                    m.id.as_str(),
+                    m.chunks
+                        .iter()
+                        .map(|(_, id, _)| id.as_str())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            grouped,
+            vec![("p0", vec![]), ("p1", vec!["c0", "c2"]), ("7", vec!["c3"])]
+        );
+    }
+}
@@ -1,16 +1,186 @@
+use std::{
+    sync::{izzy, Mutex},
+    time::{Duration, Instant},
+};
+
+use arrow_array::{ArrayRef, Float32Array, Float64Array, RecordBatch};
+use cache::EmbeddingCache;
+use coordination::OptimizeGate;
+use events::EventBus;
+use explain::stamp_ranks;
+use fusion::{reciprocal_rank_fusion, DEFAULT_RRF_K, RELEVANCE_SCORE_COLUMN};
+use grouping::{group_rows, DEFAULT_GROUP_FETCH_FACTOR};
 use lancedb::{
-    query::{QueryBase, VectorQuery},
-    DistanceType,
+    arrow::arrow_schema::{DataType, Field, SchemaRef},
+    query::{QueryBase, QueryExecutionOptions, Select, VectorQuery},
 };
+use lifecycle::Lifecycle;
+use mmr::{mmr_rerank, DEFAULT_MMR_FETCH_FACTOR};
+use ordering::order_rows;
+use popularity::PopularityCounter;
+use rerank::{rerank, RERANK_SCORE_COLUMN};
 use izzy::{
     embeddings::embedding::EmbeddingModel,
     vector_store::{VectorStoreError, VectorStoreIndex},
 };
 use serde::Deserialize;
 use serde_json::Value;
-use utils::{FilterTableColumns, QueryToJson};
+use sub_results::{Lru, SubResultCache};
+use swr::ResultCache;
+use throttle::Throttle;
+use tokens::Tokenizer;
+use trace::{elapsed_ms, record, warning};
+use utils::{
+    deserialize_typed, id_to_string, is_vector_index, FilterTableColumns, QuerySettings,
+    QueryToJson, RecordBatchDeserializer,
+};
 
+mod batch;
+mod batching;
+mod cache;
+mod chunks;
+mod connection;
+mod consistency;
+mod coordination;
+mod dedup;
+mod diagnostics;
+mod distance;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod erasure;
+mod events;
+mod evolution;
+mod expiry;
+mod explain;
+mod export;
+mod facets;
+mod filter;
+mod freshness;
+mod fusion;
+mod graph;
+mod grouping;
+mod hit;
+mod id_codec;
+mod ids;
+mod index;
+mod ingest;
+mod jobs;
+mod join;
+mod lifecycle;
+mod limits;
+mod lock;
+mod meta;
+mod mmr;
+mod multi_column;
+mod multivector;
+mod mutation;
+mod observer;
+mod ordering;
+mod outliers;
+mod overflow;
+mod partitions;
+mod pipeline;
+mod popularity;
+mod projection;
+mod range;
+mod reconcile;
+mod record;
+mod relaxation;
+#[cfg(feature = "remote")]
+mod remote;
+mod rerank;
+mod result;
+mod retention;
+mod retry;
+mod rewrite;
+mod schema;
+mod score;
+mod self_test;
+mod session;
+mod similar;
+mod snapshot;
+mod soft_delete;
+mod stats;
+mod stream;
+mod sub_results;
+mod swr;
+mod throttle;
+mod tiering;
+mod timeout;
+mod tokens;
+mod trace;
+mod transform;
+mod utf8;
 mod utils;
+mod wire;
+mod zero_results;
+
+pub use batching::EmbeddingBatching;
+pub use chunks::{
+    chunk_positions, ChunkPosition, Passage, CHUNK_INDEX_COLUMN, PARENT_ID_COLUMN,
+    TOTAL_CHUNKS_COLUMN,
+};
+pub use connection::ConnectionBuilder;
+pub use consistency::ReadConsistency;
+pub use coordination::BulkIngest;
+pub use dedup::{Dedup, CONTENT_HASH_COLUMN};
+pub use diagnostics::{PlanNode, QueryDiagnostics, QueryPlan};
+pub use distance::{Distance, UnknownDistance};
+#[cfg(feature = "encryption")]
+pub use encryption::{FieldEncryption, KeyProvider, StaticKey, ENCRYPTED_PREFIX};
+pub use erasure::{ErasureReport, SubjectErasure, TableErasure, SUBJECT_COLUMN};
+pub use events::{EventSubscriber, MutationEvent};
+pub use expiry::EXPIRES_AT_COLUMN;
+pub use explain::ScoreExplanation;
+pub use export::EmbeddingMatrix;
+pub use facets::{Facet, FacetCounts, FacetedResults};
+pub use filter::{Filter, FilterValue};
+pub use freshness::{
+    AgeBucket, Freshness, DEFAULT_AGE_BUCKETS, LAST_INGEST_META_KEY, TIMESTAMP_COLUMN,
+};
+pub use graph::{GraphOptions, GraphResult, Neighbor, ResultExpander};
+pub use hit::SeizzyhHit;
+pub use id_codec::{IdCodec, TemplateIdCodec};
+pub use ids::IdGeneration;
+pub use index::{IndexBuilder, Quantization, VectorIndexType};
+pub use ingest::{ChunkIssue, IngestFilter, IngestFilterAction, IngestReport};
+pub use jobs::IngestJob;
+pub use join::EntityMatch;
+pub use limits::{Resource, ResourceExhausted, ResourceLimits};
+pub use lock::{LockHeld, WriterLock, WRITER_LOCK_META_KEY};
+pub use multivector::{mean_pool, multivector_array};
+pub use mutation::{CleanupReport, OptimizeReport};
+pub use observer::{QueryInfo, QueryKind, QueryOutcome, SeizzyhObserver};
+pub use ordering::SortOrder;
+pub use outliers::{Outlier, OutlierOptions};
+pub use overflow::{EmbeddingInputLimit, OverflowAction};
+pub use partitions::{PartitionPeriod, TimePartitionedIndex};
+pub use pipeline::IngestionPipeline;
+pub use popularity::RETRIEVAL_COUNT_COLUMN;
+pub use projection::ProjectedPoint;
+pub use reconcile::{IndexDefinition, IndexSpec, ReconcileReport, ScalarIndexType};
+pub use record::LanceRecord;
+pub use relaxation::{FilterRelaxation, RelaxedResults};
+pub use rerank::Reranker;
+pub use result::SeizzyhResult;
+pub use retention::{RetentionOutcome, RetentionPolicy, RetentionReport, LEGAL_HOLD_COLUMN};
+pub use retry::{is_transient, RetryPolicy};
+pub use rewrite::{CondenseQuestion, QueryRewriter, DEFAULT_CONDENSE_PROMPT};
+pub use schema::SchemaOptions;
+pub use score::ScoreNormalization;
+pub use self_test::{SelfTestReport, SELF_TEST_CANARY};
+pub use session::SeizzyhSession;
+pub use snapshot::{SnapshotRef, SNAPSHOT_META_PREFIX};
+pub use soft_delete::DELETED_AT_COLUMN;
+pub use stats::{GroupStats, TableStats};
+pub use throttle::EmbeddingRateLimit;
+pub use tiering::TieredVectorIndex;
+pub use timeout::TimedOut;
+pub use tokens::{ApproximateTokenizer, Tokenizer};
+pub use transform::ResultTransformer;
+pub use utf8::Utf8Policy;
+pub use wire::{Provenance, SeizzyhMatch, SeizzyhResponse, Timings, WIRE_SCHEMA_VERSION};
+pub use zero_results::ZeroResultDiagnostics;
 
 fn lancedb_to_izzy_error(e: lancedb::Error) -> VectorStoreError {
     VectorStoreError::DatastoreError(Box::new(e))
@@ -20,6 +190,36 @@ fn serde_to_izzy_error(e: serde_json::Error) -> VectorStoreError {
     VectorStoreError::JsonError(e)
 }
 
+/// Returns true if `field` is a vector column: a fixed size list of floats.
+fn is_vector_field(field: &Field) -> bool {
+    matches!(
+        field.data_type(),
+        DataType::FixedSizeList(item, _)
+            if matches!(item.data_type(), DataType::Float16 | DataType::Float32 | DataType::Float64)
+    )
+}
+
+/// Check that `field` is a vector column of `ndims` dimensions.
+fn check_vector_dims(field: &Field, ndims: usize) -> Result<(), lancedb::Error> {
+    match field.data_type() {
+        DataType::FixedSizeList(_, dims) if is_vector_field(field) => match *dims as usize == ndims {
+            true => Ok(()),
+            false => Err(lancedb::Error::InvalidInput {
+                message: format!(
+                    "Vector column {} has {dims} dimensions, but the embedding model produces {ndims} dimensions",
+                    field.name()
+                ),
+            }),
+        },
+        data_type => Err(lancedb::Error::InvalidInput {
+            message: format!(
+                "Column {} is not a vector column: expected a fixed size list of floats, found {data_type}",
+                field.name()
+            ),
+        }),
+    }
+}
+
 /// Type on which vector seizzyhes can be performed for a lanceDb table.
 /// # Example
 /// ```
@@ -32,6 +232,7 @@ fn serde_to_izzy_error(e: serde_json::Error) -> VectorStoreError {
 /// let model: EmbeddingModel = openai_client.embedding_model(TEXT_EMBEDDING_ADA_002); // <-- Replace with your embedding model here.
 /// let vector_store_index = LanceDbVectorIndex::new(table, model, "id", SeizzyhParams::default()).await?;
 /// ```
+#[derive(Clone)]
 pub struct LanceDbVectorIndex<M: EmbeddingModel> {
     /// Defines which model is used to generate embeddings for the vector store.
     model: M,
@@ -39,28 +240,182 @@ pub struct LanceDbVectorIndex<M: EmbeddingModel> {
     table: lancedb::Table,
     /// Column name in `table` that contains the id of a record.
     id_field: String,
+    /// Schema of `table` when the index was created, typing the ids of the lookups by id.
+    schema: SchemaRef,
     /// Vector seizzyh params that are used during vector seizzyh operations.
     seizzyh_params: SeizzyhParams,
+    /// Connection of `table`, used to access the companion tables of the index (e.g. the `_meta` table).
+    connection: Option<lancedb::Connection>,
+    /// Reranker applied to the results of `top_n`.
+    reranker: Option<izzy<dyn Reranker>>,
+    /// Subscribers notified of the mutations of `table`.
+    events: EventBus,
+    /// In-flight mutations of `table`, awaited by `close`.
+    lifecycle: izzy<Lifecycle>,
+    /// Coordination of `optimize` with the bulk ingests and the pause signals.
+    optimize_gate: izzy<OptimizeGate>,
+    /// Cache of the query embeddings, in front of `model`.
+    embedding_cache: Option<izzy<EmbeddingCache>>,
+    /// Query rewriter applied to follow-up questions by `top_n_with_history`.
+    query_rewriter: Option<izzy<dyn QueryRewriter>>,
+    /// Limits on the calls made to `model`.
+    throttle: Option<izzy<Throttle>>,
+    /// Retry policy of the LanceDB queries and writes.
+    retry_policy: RetryPolicy,
+    /// Timeout of the writes to `table`.
+    write_timeout: Option<Duration>,
+    /// Transformation applied to the documents of the rows returned by the index.
+    result_transformer: Option<izzy<dyn ResultTransformer>>,
+    /// Client-side encryption of the sensitive columns of `table`.
+    #[cfg(feature = "encryption")]
+    field_encryption: Option<FieldEncryption>,
+    /// Observer of the seizzyhes of the index.
+    seizzyh_observer: Option<izzy<dyn SeizzyhObserver>>,
+    /// Data retention policy enforced by `purge_expired`.
+    retention_policy: Option<RetentionPolicy>,
+    /// Read consistency of the queries, when other processes write to `table`.
+    read_consistency: ReadConsistency,
+    /// Tables and columns erased by `erase_subject`.
+    subject_erasure: SubjectErasure,
+    /// Caches of the full text seizzyh results and filter row counts, keyed by table version.
+    sub_result_cache: Option<izzy<SubResultCache>>,
+    /// Retrieval counts of the documents, written to the `retrieval_count` column in batches.
+    popularity: Option<izzy<PopularityCounter>>,
+    /// Cache of the results of `top_n_stale_while_revalidate`.
+    result_cache: Option<izzy<ResultCache>>,
+    /// Tokenizer counting the tokens of the results, the approximate tokenizer if unset.
+    tokenizer: Option<izzy<dyn Tokenizer>>,
+    /// Handling of the invalid UTF-8 values of the string columns read from `table`, unvalidated if unset.
+    utf8_policy: Option<Utf8Policy>,
+    /// Codec rendering the ids from several columns of `table`, read from `id_field` if unset.
+    id_codec: Option<izzy<dyn IdCodec>>,
+    /// Whether `delete_by_ids` soft deletes the documents, skipped by every query.
+    soft_delete: bool,
+    /// Context limit of the embedding model, applied to the ingested documents.
+    embedding_input_limit: Option<EmbeddingInputLimit>,
+    /// Time to live of the documents, skipped by every query once expired.
+    ttl: Option<Duration>,
+    /// Limits of the embedding requests made during ingestion, detected from the model if unset.
+    embedding_batching: Option<EmbeddingBatching>,
+    /// Filter modes chosen by `FilterMode::Auto`, keyed by table version and filter.
+    filter_modes: izzy<Mutex<Lru<(u64, String), FilterMode>>>,
 }
 
 impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
     /// Create an instance of `LanceDbVectorIndex` with an existing table and model.
     /// Define the id field name of the table.
     /// Define seizzyh parameters that will be used to perform vector seizzyhes on the table.
+    /// Fails with `lancedb::Error::InvalidInput` if the vector column (the column of the seizzyh params,
+    /// or the first column of the table that contains fixed size lists of floats) is not a list of floats
+    /// or has a different number of dimensions than `model`.
     pub async fn new(
         table: lancedb::Table,
         model: M,
         id_field: &str,
         seizzyh_params: SeizzyhParams,
     ) -> Result<Self, lancedb::Error> {
+        let schema = table.schema().await?;
+        let vector_field = match &seizzyh_params.column {
+            Some(column) => schema.field_with_name(column).ok(),
+            None => schema
+                .fields()
+                .iter()
+                .find(|field| is_vector_field(field))
+                .map(|field| field.as_ref()),
+        };
+
+        if let Some(field) = vector_field {
+            check_vector_dims(field, model.ndims())?;
+        }
+
         Ok(Self {
             table,
             model,
             id_field: id_field.to_string(),
+            schema,
             seizzyh_params,
+            connection: None,
+            reranker: None,
+            events: EventBus::default(),
+            lifecycle: izzy::default(),
+            optimize_gate: izzy::default(),
+            embedding_cache: None,
+            query_rewriter: None,
+            throttle: None,
+            retry_policy: RetryPolicy::none(),
+            write_timeout: None,
+            result_transformer: None,
+            #[cfg(feature = "encryption")]
+            field_encryption: None,
+            seizzyh_observer: None,
+            retention_policy: None,
+            read_consistency: ReadConsistency::default(),
+            subject_erasure: SubjectErasure::default(),
+            sub_result_cache: None,
+            popularity: None,
+            result_cache: None,
+            tokenizer: None,
+            utf8_policy: None,
+            id_codec: None,
+            soft_delete: false,
+            embedding_input_limit: None,
+            ttl: None,
+            embedding_batching: None,
+            filter_modes: izzy::default(),
         })
     }
 
+    /// Sets the connection of the table.
+    /// Required by the features that store data in companion tables, such as `set_meta` and `get_meta`.
+    /// Indexes created with `create_table` already have a connection.
+    pub fn connection(mut self, connection: lancedb::Connection) -> Self {
+        self.connection = Some(connection);
+        self
+    }
+
+    /// Sets the reranker applied to the candidates of the vector seizzyh of `top_n` before they are returned.
+    /// The score of a result is then the score given by the reranker, and higher scores rank first.
+    pub fn reranker(mut self, reranker: impl Reranker + 'static) -> Self {
+        self.reranker = Some(izzy::new(reranker));
+        self
+    }
+
+    /// Create a new table named `name` on the connection `conn` and return an instance of `LanceDbVectorIndex` for it.
+    /// The schema of the table is derived from `schema_opts`, and the vector column is sized from `model.ndims()`.
+    /// The vector column of the new table is used as the seizzyh column.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::{LanceDbVectorIndex, SchemaOptions};
+    /// use izzy::providers::openai::{Client, TEXT_EMBEDDING_ADA_002};
+    ///
+    /// let openai_client = Client::from_env();
+    /// let model = openai_client.embedding_model(TEXT_EMBEDDING_ADA_002);
+    ///
+    /// let db = lancedb::connect("data/lancedb-store").execute().await?;
+    /// let vector_store_index =
+    ///     LanceDbVectorIndex::create_table(&db, "definitions", model, SchemaOptions::default()).await?;
+    /// ```
+    pub async fn create_table(
+        conn: &lancedb::Connection,
+        name: &str,
+        model: M,
+        schema_opts: SchemaOptions,
+    ) -> Result<Self, lancedb::Error> {
+        let table = conn
+            .create_empty_table(name, izzy::new(schema_opts.to_schema(model.ndims())))
+            .execute()
+            .await?;
+
+        Ok(Self::new(
+            table,
+            model,
+            &schema_opts.id_field,
+            SeizzyhParams::default().column(&schema_opts.vector_field),
+        )
+        .await?
+        .connection(conn.clone()))
+    }
+
     /// Apply the seizzyh_params to the vector query.
     /// This is a helper function used by the methods `top_n` and `top_n_ids` of the `VectorStoreIndex` trait.
     fn build_query(&self, mut query: VectorQuery) -> VectorQuery {
@@ -69,12 +424,16 @@ impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
             seizzyh_type,
             nprobes,
             refine_factor,
-            post_filter,
+            ef_search,
+            fast_seizzyh,
             column,
+            min_distance,
+            max_distance,
+            ..
         } = self.seizzyh_params.clone();
 
         if let Some(distance_type) = distance_type {
-            query = query.distance_type(distance_type);
+            query = query.distance_type(distance_type.into());
         }
 
         if let Some(SeizzyhType::Flat) = seizzyh_type {
@@ -88,18 +447,298 @@ impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
             if let Some(refine_factor) = refine_factor {
                 query = query.refine_factor(refine_factor);
             }
+            if let Some(ef_search) = ef_search {
+                query = query.ef(ef_search);
+            }
         }
 
-        if let Some(true) = post_filter {
-            query = query.postfilter();
+        if fast_seizzyh {
+            query = query.fast_seizzyh();
         }
 
         if let Some(column) = column {
             query = query.column(column.as_str())
         }
 
+        if min_distance.is_some() || max_distance.is_some() {
+            query = query.distance_range(min_distance, max_distance);
+        }
+
         query
     }
+
+    /// Names of the columns of the table that are returned as the payload of a record.
+    /// These are the selected columns of the seizzyh params, and the id columns, if columns are selected.
+    /// Otherwise, every column except the embedding columns, the vector columns of any float type
+    /// and the column of the seizzyh params.
+    /// The vector column is added if the seizzyh params return vectors.
+    async fn payload_columns(&self) -> Result<Vec<String>, VectorStoreError> {
+        let mut columns = match &self.seizzyh_params.select_columns {
+            Some(columns) => {
+                let mut columns = columns.clone();
+                for id_column in self.id_columns() {
+                    if !columns.contains(&id_column) {
+                        columns.push(id_column);
+                    }
+                }
+                columns
+            }
+            None => {
+                let schema = self.table.schema().await.map_err(lancedb_to_izzy_error)?;
+
+                schema
+                    .clone()
+                    .filter_embeddings()
+                    .into_iter()
+                    .filter(|name| Some(name) != self.seizzyh_params.column.as_ref())
+                    .filter(|name| !schema.field_with_name(name).is_ok_and(is_vector_field))
+                    .collect()
+            }
+        };
+
+        if self.seizzyh_params.return_vectors {
+            let vector_column = self.vector_column().await?;
+            if !columns.contains(&vector_column) {
+                columns.push(vector_column);
+            }
+        }
+
+        Ok(columns)
+    }
+
+    /// Name of the vector column: the column of the seizzyh params,
+    /// or the first column of the table that contains lists of floats.
+    async fn vector_column(&self) -> Result<String, VectorStoreError> {
+        if let Some(column) = &self.seizzyh_params.column {
+            return Ok(column.to_string());
+        }
+
+        self.table
+            .schema()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .fields()
+            .iter()
+            .find(|field| is_vector_field(field))
+            .map(|field| field.name().to_string())
+            .ok_or(VectorStoreError::DatastoreError(
+                "Table has no vector column".into(),
+            ))
+    }
+
+    /// Combine the filter of the seizzyh params with the per-call `filter` and render it to SQL.
+    /// Soft deleted and expired documents are excluded when soft deletes and the time to live are enabled.
+    fn combined_filter(&self, filter: Option<Filter>) -> Option<String> {
+        [
+            self.soft_delete_filter(),
+            self.expiry_filter(),
+            self.seizzyh_params.filter.clone(),
+            filter,
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(Filter::and)
+        .map(|filter| filter.to_string())
+    }
+
+    /// Same as `combined_filter`, for a per-call `filter` that is always set.
+    fn combined_filter_with(&self, filter: Filter) -> String {
+        self.combined_filter(Some(filter)).unwrap_or_default()
+    }
+
+    /// Apply the SQL `filter` to the vector query, using the filter mode of the seizzyh params.
+    /// This is a helper function used by the methods `top_n_with_filter` and `top_n_ids_with_filter`.
+    async fn apply_filter(
+        &self,
+        query: VectorQuery,
+        filter: Option<&str>,
+    ) -> Result<VectorQuery, VectorStoreError> {
+        let Some(filter) = filter else {
+            return Ok(query);
+        };
+
+        let query = query.only_if(filter);
+
+        Ok(match self.resolve_filter_mode(filter).await? {
+            FilterMode::Post => query.postfilter(),
+            _ => query,
+        })
+    }
+
+    /// Run a full text seizzyh for `query` and fuse its results with the vector seizzyh results
+    /// using reciprocal rank fusion, when the seizzyh type is `SeizzyhType::Hybrid`.
+    /// The full text seizzyh projects the same `columns` as the vector seizzyh, and its results are cached
+    /// by the sub-result cache if set.
+    /// The first `offset` fused results are skipped.
+    /// For other seizzyh types, or when there is no query text, the vector seizzyh results are returned unchanged.
+    async fn fuse_full_text_results(
+        &self,
+        query: Option<&str>,
+        n: usize,
+        offset: usize,
+        filter: Option<&str>,
+        columns: Vec<String>,
+        vector_results: Vec<Value>,
+    ) -> Result<Vec<Value>, VectorStoreError> {
+        let (Some(SeizzyhType::Hybrid), Some(query)) = (&self.seizzyh_params.seizzyh_type, query)
+        else {
+            return Ok(vector_results);
+        };
+
+        let full_text_results = self
+            .full_text_results(query, filter, columns, offset + n)
+            .await?;
+
+        Ok(reciprocal_rank_fusion(
+            vec![vector_results, full_text_results],
+            &self.id_field,
+            self.seizzyh_params.rrf_k.unwrap_or(DEFAULT_RRF_K),
+        )
+        .into_iter()
+        .skip(offset)
+        .take(n)
+        .collect())
+    }
+
+    /// Execution options of the queries, built from the seizzyh params.
+    fn execution_options(&self) -> QueryExecutionOptions {
+        let mut options = QueryExecutionOptions::default();
+        if let Some(max_batch_length) = self.seizzyh_params.max_batch_length {
+            options.max_batch_length = max_batch_length;
+        }
+        options
+    }
+
+    /// Settings of the execution of the queries, built from the seizzyh params and the retry policy of the index.
+    fn query_settings(&self) -> QuerySettings<'_> {
+        QuerySettings {
+            limits: &self.seizzyh_params.resource_limits,
+            options: self.execution_options(),
+            retry: &self.retry_policy,
+            timeout: self.seizzyh_params.timeout,
+            latest_table: self.latest_table(),
+            utf8_policy: self.utf8_policy,
+            id_codec: self
+                .id_codec
+                .as_deref()
+                .map(|id_codec| (self.id_field.as_str(), id_codec)),
+        }
+    }
+
+    /// Offset of a seizzyh: the per-call `offset` if set, otherwise the offset of the seizzyh params.
+    fn resolve_offset(&self, offset: Option<usize>) -> usize {
+        offset.or(self.seizzyh_params.offset).unwrap_or_default()
+    }
+
+    /// Apply the limit `n` and the `offset` to the vector query.
+    /// For hybrid seizzyhes, the offset is applied after fusion, so the vector seizzyh fetches the first `offset + n` results.
+    fn apply_page(
+        &self,
+        query: VectorQuery,
+        text: Option<&str>,
+        n: usize,
+        offset: usize,
+    ) -> VectorQuery {
+        match (&self.seizzyh_params.seizzyh_type, text) {
+            (Some(SeizzyhType::Hybrid), Some(_)) => query.limit(offset + n),
+            _ => query.limit(n).offset(offset),
+        }
+    }
+
+    /// Create a builder for an ANN index of type `index_type` on the vector column of the table.
+    /// The distance type of the index is validated against the seizzyh params of the vector index.
+    /// See [LanceDB ANN indexes](https://lancedb.github.io/lancedb/ann_indexes/) for more information.
+    pub fn index_builder(&self, index_type: VectorIndexType) -> IndexBuilder<'_, M> {
+        IndexBuilder::new(self, index_type)
+    }
+
+    /// Resolve the filter mode that is used when applying `filter` to a vector seizzyh.
+    /// When the filter mode is `FilterMode::Auto`, post-filtering is chosen only if the seizzyh column
+    /// has a vector index and the filter matches at least half of the rows of the table.
+    /// Otherwise, pre-filtering is chosen.
+    /// The choice is cached per table version and filter, so that it is only computed once between writes.
+    pub async fn resolve_filter_mode(&self, filter: &str) -> Result<FilterMode, VectorStoreError> {
+        match &self.seizzyh_params.filter_mode {
+            Some(FilterMode::Auto) => (),
+            Some(filter_mode) => return Ok(filter_mode.clone()),
+            None => return Ok(FilterMode::Pre),
+        };
+
+        let key = (self.table_version().await?, filter.to_string());
+
+        if let Some(filter_mode) = self
+            .filter_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return Ok(filter_mode);
+        }
+
+        let filter_mode = self.auto_filter_mode(filter).await?;
+
+        self.filter_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, filter_mode.clone(), FILTER_MODE_CACHE_CAPACITY);
+
+        Ok(filter_mode)
+    }
+
+    /// Filter mode chosen by `FilterMode::Auto` for `filter`, from the indexes and the row counts of the table.
+    async fn auto_filter_mode(&self, filter: &str) -> Result<FilterMode, VectorStoreError> {
+        let column = self.seizzyh_params.column.as_ref();
+        let indexed = self
+            .table
+            .list_indices()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .iter()
+            .any(|index| {
+                is_vector_index(&index.index_type)
+                    && column.map_or(true, |column| index.columns.contains(column))
+            });
+
+        if !indexed {
+            return Ok(FilterMode::Pre);
+        }
+
+        let total_rows = self.filter_rows(None).await?;
+
+        if total_rows == 0 {
+            return Ok(FilterMode::Pre);
+        }
+
+        let filtered_rows = self.filter_rows(Some(filter)).await?;
+
+        if filtered_rows as f64 / total_rows as f64 >= AUTO_POST_FILTER_SELECTIVITY {
+            Ok(FilterMode::Post)
+        } else {
+            Ok(FilterMode::Pre)
+        }
+    }
+}
+
+/// Name of the column containing the distance of a result to the query, as returned by LanceDB.
+const DEFAULT_DISTANCE_COLUMN: &str = "_distance";
+
+/// Minimum fraction of rows that a filter must match for `FilterMode::Auto` to choose post-filtering.
+const AUTO_POST_FILTER_SELECTIVITY: f64 = 0.5;
+
+/// Maximum number of filters whose filter mode is cached by `FilterMode::Auto`.
+const FILTER_MODE_CACHE_CAPACITY: usize = 256;
+
+/// Defines when filtering happens relative to the vector seizzyh.
+/// See [LanceDb pre/post filtering](https://lancedb.github.io/lancedb/sql/#pre-and-post-filtering) for more information.
+#[derive(Debug, Clone)]
+pub enum FilterMode {
+    /// Filter the table before the vector seizzyh. This is the LanceDB default.
+    Pre,
+    /// Filter the results of the vector seizzyh.
+    Post,
+    /// Choose pre- or post-filtering per query, based on the selectivity of the filter
+    /// and on whether the seizzyh column has a vector index.
+    Auto,
 }
 
 /// See [LanceDB vector seizzyh](https://lancedb.github.io/lancedb/seizzyh/) for more information.
@@ -109,29 +748,67 @@ pub enum SeizzyhType {
     Flat,
     /// Approximal Nearest Neighbor seizzyh, also called ANN.
     Approximate,
+    /// Vector seizzyh combined with a BM25 full text seizzyh.
+    /// The results of both seizzyhes are fused with reciprocal rank fusion.
+    /// Requires a full text seizzyh index on the table.
+    /// See [LanceDB hybrid seizzyh](https://lancedb.github.io/lancedb/hybrid_seizzyh/hybrid_seizzyh/) for more information.
+    Hybrid,
+}
+
+/// Precision of the query vectors passed to LanceDB.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum QueryPrecision {
+    /// Convert the query vectors to `f32`, the precision of the vector columns created by this crate. This is the default.
+    #[default]
+    F32,
+    /// Pass the query vectors as `f64`, e.g. for `Float64` vector columns.
+    F64,
 }
 
 /// Parameters used to perform a vector seizzyh on a LanceDb table.
 /// # Example
 /// ```
-/// let seizzyh_params = izzy_lancedb::SeizzyhParams::default().distance_type(lancedb::DistanceType::Cosine);
+/// let seizzyh_params = izzy_lancedb::SeizzyhParams::default().distance_type(izzy_lancedb::Distance::Cosine);
 /// ```
 #[derive(Debug, Clone, Default)]
 pub struct SeizzyhParams {
-    distance_type: Option<DistanceType>,
+    distance_type: Option<Distance>,
     seizzyh_type: Option<SeizzyhType>,
     nprobes: Option<usize>,
     refine_factor: Option<u32>,
-    post_filter: Option<bool>,
+    ef_search: Option<usize>,
+    fast_seizzyh: bool,
+    filter_mode: Option<FilterMode>,
     column: Option<String>,
+    rrf_k: Option<f64>,
+    filter: Option<Filter>,
+    offset: Option<usize>,
+    min_distance: Option<f32>,
+    max_distance: Option<f32>,
+    mmr_lambda: Option<f64>,
+    mmr_fetch_factor: Option<usize>,
+    resource_limits: ResourceLimits,
+    score_normalization: Option<ScoreNormalization>,
+    max_batch_length: Option<u32>,
+    distance_column: Option<String>,
+    select_columns: Option<Vec<String>>,
+    return_vectors: bool,
+    return_row_ids: bool,
+    explain_scores: bool,
+    diagnose_zero_results: bool,
+    query_precision: QueryPrecision,
+    timeout: Option<Duration>,
+    order_by: Option<(String, SortOrder)>,
+    group_by: Option<(String, usize)>,
+    group_fetch_factor: Option<usize>,
 }
 
 impl SeizzyhParams {
     /// Sets the distance type of the seizzyh params.
     /// Always set the distance_type to match the value used to train the index.
-    /// The default is DistanceType::L2.
-    pub fn distance_type(mut self, distance_type: DistanceType) -> Self {
-        self.distance_type = Some(distance_type);
+    /// The default is `Distance::L2`. Also accepts a `lancedb::DistanceType`.
+    pub fn distance_type(mut self, distance_type: impl Into<Distance>) -> Self {
+        self.distance_type = Some(distance_type.into());
         self
     }
 
@@ -159,11 +836,39 @@ impl SeizzyhParams {
         self
     }
 
+    /// Sets the number of candidates considered by the seizzyh of an HNSW index (`IvfHnswPq` or `IvfHnswSq`).
+    /// Higher values improve recall at the cost of latency. LanceDB defaults to 1.5 times the number of results.
+    /// Only set this value only when seizzyh type is ANN.
+    pub fn ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = Some(ef_search);
+        self
+    }
+
+    /// Sets whether the vector seizzyh only reads the rows covered by the ANN index, skipping the flat seizzyh
+    /// of the rows added since the index was last built or optimized, for latency-critical seizzyhes.
+    /// Recent rows are missing from the results until `optimize` adds them to the index. Disabled by default.
+    pub fn fast_seizzyh(mut self, fast_seizzyh: bool) -> Self {
+        self.fast_seizzyh = fast_seizzyh;
+        self
+    }
+
     /// Sets the post filter of the seizzyh params.
     /// If set to true, filtering will happen after the vector seizzyh instead of before.
     /// See [LanceDb pre/post filtering](https://lancedb.github.io/lancedb/sql/#pre-and-post-filtering) for more information.
     pub fn post_filter(mut self, post_filter: bool) -> Self {
-        self.post_filter = Some(post_filter);
+        self.filter_mode = Some(if post_filter {
+            FilterMode::Post
+        } else {
+            FilterMode::Pre
+        });
+        self
+    }
+
+    /// Sets the filter mode of the seizzyh params.
+    /// Use `FilterMode::Auto` to let the index choose between pre- and post-filtering for each query.
+    /// See [LanceDb pre/post filtering](https://lancedb.github.io/lancedb/sql/#pre-and-post-filtering) for more information.
+    pub fn filter_mode(mut self, filter_mode: FilterMode) -> Self {
+        self.filter_mode = Some(filter_mode);
         self
     }
 
@@ -174,6 +879,863 @@ impl SeizzyhParams {
         self.column = Some(column.to_string());
         self
     }
+
+    /// Sets the `k` constant of the reciprocal rank fusion used by hybrid seizzyh and `top_n_multi_column`.
+    /// The default is 60.
+    pub fn rrf_k(mut self, rrf_k: f64) -> Self {
+        self.rrf_k = Some(rrf_k);
+        self
+    }
+
+    /// Sets the filter of the seizzyh params.
+    /// The filter is applied to every query, combined with the filter passed to `top_n_with_filter` and `top_n_ids_with_filter`.
+    /// See [LanceDb SQL filters](https://lancedb.github.io/lancedb/sql/) for more information.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sets the number of results to skip before the first returned result, to page through the results.
+    /// The offset can be overridden per call with `top_n_page` and `top_n_ids_page`.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the minimum distance of a result to the query, e.g. to skip near-duplicates of the query.
+    /// Results closer to the query are dropped by LanceDB, so fewer than `n` results may be returned.
+    /// The distance depends on the distance type of the seizzyh params.
+    pub fn min_distance(mut self, min_distance: f32) -> Self {
+        self.min_distance = Some(min_distance);
+        self
+    }
+
+    /// Sets the maximum distance of a result to the query.
+    /// Results further away are dropped by LanceDB, so fewer than `n` results may be returned.
+    /// The distance depends on the distance type of the seizzyh params.
+    pub fn max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = Some(max_distance);
+        self
+    }
+
+    /// Enables maximal marginal relevance (MMR) re-ranking of the results of `top_n`, to diversify near duplicate results.
+    /// `lambda` balances relevance and diversity: 1 ranks by relevance only, 0 by diversity only.
+    /// `n * fetch_factor` candidates are fetched with their vectors and diversified down to `n` results
+    /// (see `mmr_fetch_factor`).
+    pub fn mmr(mut self, lambda: f64) -> Self {
+        self.mmr_lambda = Some(lambda);
+        self
+    }
+
+    /// Sets the number of candidates fetched per result when MMR re-ranking is enabled. The default is 4.
+    pub fn mmr_fetch_factor(mut self, mmr_fetch_factor: usize) -> Self {
+        self.mmr_fetch_factor = Some(mmr_fetch_factor);
+        self
+    }
+
+    /// Sets the limits on the data read by each query of a seizzyh. There are no limits by default.
+    pub fn resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    /// Sets how the distances of the vector seizzyh are converted to the scores returned by `top_n` and `top_n_ids`.
+    /// Use it to get comparable `0..1` scores regardless of the distance type. The default is `ScoreNormalization::None`.
+    /// Scores of hybrid seizzyhes and of rerankers are not normalized.
+    pub fn score_normalization(mut self, score_normalization: ScoreNormalization) -> Self {
+        self.score_normalization = Some(score_normalization);
+        self
+    }
+
+    /// Sets the maximum number of rows of the record batches read from LanceDB. The LanceDB default is 1024.
+    /// Smaller batches lower the peak memory of large `n` seizzyhes and streams, at the cost of throughput.
+    pub fn max_batch_length(mut self, max_batch_length: u32) -> Self {
+        self.max_batch_length = Some(max_batch_length);
+        self
+    }
+
+    /// Sets the name of the column containing the distance of a result to the query,
+    /// read by `top_n` and `top_n_ids`. The default is `_distance`, the column name used by LanceDB.
+    /// A seizzyh fails if a result has no distance column.
+    pub fn distance_column(mut self, distance_column: &str) -> Self {
+        self.distance_column = Some(distance_column.to_string());
+        self
+    }
+
+    /// Sets the columns returned in the documents of `top_n` and the other seizzyh methods.
+    /// The id column is always returned. By default, every column except the embedding columns is returned.
+    /// Only select the fields your payload struct needs to avoid reading large unused columns.
+    pub fn select_columns(mut self, columns: &[&str]) -> Self {
+        self.select_columns = Some(columns.iter().map(|column| column.to_string()).collect());
+        self
+    }
+
+    /// Sets whether the vector column is returned in the documents of `top_n` and the other seizzyh methods.
+    /// Use `top_n_with_vectors` to get the vector of each result separately from its document,
+    /// e.g. for client-side reranking. Disabled by default.
+    pub fn return_vectors(mut self, return_vectors: bool) -> Self {
+        self.return_vectors = return_vectors;
+        self
+    }
+
+    /// Sets whether the row ids of the results are read, returned in the `row_id` of the results of `top_n_results`.
+    /// The row ids are returned in the `_rowid` column of the documents of the other seizzyh methods. Disabled by default.
+    pub fn return_row_ids(mut self, return_row_ids: bool) -> Self {
+        self.return_row_ids = return_row_ids;
+        self
+    }
+
+    /// Sets whether the components of the score of each result (distance, full text score, fusion score, rerank score
+    /// and the positions moved by the reranker) are returned in the matches of `top_n_response`, for debugging.
+    /// The positions of the results before reranking are returned in the `_rank_before_rerank` column of the documents
+    /// of the other seizzyh methods. Disabled by default.
+    pub fn explain_scores(mut self, explain_scores: bool) -> Self {
+        self.explain_scores = explain_scores;
+        self
+    }
+
+    /// Sets whether diagnostics are run when `top_n_response` returns no results: row counts with and without the filter,
+    /// the ANN index of the vector column and the distance of the nearest row without the filter.
+    /// Their findings are attached to the response. Disabled by default.
+    pub fn diagnose_zero_results(mut self, diagnose_zero_results: bool) -> Self {
+        self.diagnose_zero_results = diagnose_zero_results;
+        self
+    }
+
+    /// Sets the precision of the query vectors passed to LanceDB.
+    /// Embedding models return `f64` vectors, which are converted to `f32` by default to match the vector column.
+    pub fn query_precision(mut self, query_precision: QueryPrecision) -> Self {
+        self.query_precision = query_precision;
+        self
+    }
+
+    /// Sets the timeout of each LanceDB query of a seizzyh, retries included.
+    /// A query that does not complete in time fails with a `TimedOut` error, wrapped in `VectorStoreError::DatastoreError`, e.g. on a hung object store read.
+    /// The timeout of the writes is set with `LanceDbVectorIndex::write_timeout`.
+    /// It does not cover the embedding of the query by the embedding model, which is bounded by the
+    /// timeouts of the model, e.g. those of its HTTP client.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a metadata column by which the results are ordered, e.g. a timestamp column for timeline views.
+    /// The top-n results are still retrieved by relevance, then returned in the `order` of their value of `column`.
+    /// Results without a value come last. Results are ordered by relevance by default.
+    pub fn order_by(mut self, column: &str, order: SortOrder) -> Self {
+        self.order_by = Some((column.to_string(), order));
+        self
+    }
+
+    /// Sets a grouping column limiting the results to `per_group_limit` per value of the column,
+    /// e.g. `group_by("parent_id", 1)` to return the best chunk of each document instead of many chunks of the same document.
+    /// More candidates are fetched to fill the results after grouping (see `group_fetch_factor`),
+    /// so fewer results than requested are returned when the candidates span too few groups.
+    /// Results without a value are groups of their own. Results are not grouped by default.
+    pub fn group_by(mut self, column: &str, per_group_limit: usize) -> Self {
+        self.group_by = Some((column.to_string(), per_group_limit));
+        self
+    }
+
+    /// Sets the number of candidates fetched per result when the results are grouped. The default is 4.
+    pub fn group_fetch_factor(mut self, group_fetch_factor: usize) -> Self {
+        self.group_fetch_factor = Some(group_fetch_factor);
+        self
+    }
+}
+
+/// Columns of the table read by a seizzyh.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Projection {
+    /// The payload columns of the seizzyh params, as read by `top_n`. This is the default.
+    #[default]
+    Payload,
+    /// The id column only, as read by `top_n_ids`.
+    /// The payload columns are still read when a reranker is set, since it scores the documents.
+    Ids,
+}
+
+/// Per-call options of a seizzyh, overriding or combined with the seizzyh params.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    /// Filter combined with the filter of the seizzyh params.
+    pub(crate) filter: Option<Filter>,
+    /// Offset overriding the offset of the seizzyh params.
+    pub(crate) offset: Option<usize>,
+    /// Columns read by the seizzyh.
+    pub(crate) projection: Projection,
+}
+
+impl QueryOptions {
+    /// Sets the filter of the seizzyh, combined with the filter of the seizzyh params.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sets the number of results to skip, overriding the offset of the seizzyh params.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the columns read by the seizzyh. The default is `Projection::Payload`.
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, with a `filter` applied to the vector seizzyh.
+    /// The filter can either be a `Filter` or a raw SQL predicate.
+    /// The filter is applied before or after the vector seizzyh depending on the filter mode of the seizzyh params.
+    /// See [LanceDb SQL filters](https://lancedb.github.io/lancedb/sql/) for more information.
+    /// # Example
+    /// ```
+    /// let result = vector_store_index
+    ///     .top_n_with_filter::<WordDefinition>(
+    ///         "My boss says I zindle too much, what does that mean?",
+    ///         1,
+    ///         Filter::eq("tenant_id", "x").and(Filter::eq("lang", "en")),
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn top_n_with_filter<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        filter: impl Into<Filter>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.seizzyh(query, n, QueryOptions::default().filter(filter.into()))
+            .await
+    }
+
+    /// Same as `top_n_ids` of the `VectorStoreIndex` trait, with a `filter` applied to the vector seizzyh.
+    /// The filter can either be a `Filter` or a raw SQL predicate.
+    /// The filter is applied before or after the vector seizzyh depending on the filter mode of the seizzyh params.
+    /// See [LanceDb SQL filters](https://lancedb.github.io/lancedb/sql/) for more information.
+    pub async fn top_n_ids_with_filter(
+        &self,
+        query: &str,
+        n: usize,
+        filter: impl Into<Filter>,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        self.seizzyh_ids(query, n, QueryOptions::default().filter(filter.into()))
+            .await
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, skipping the first `offset` results.
+    /// Use it to page through the results: page `p` (starting at 0) of size `n` has offset `p * n`.
+    /// The offset overrides the offset of the seizzyh params.
+    /// # Example
+    /// ```
+    /// // Second page of 10 results.
+    /// let result = vector_store_index
+    ///     .top_n_page::<WordDefinition>("My boss says I zindle too much, what does that mean?", 10, 10)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_page<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        offset: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.seizzyh(query, n, QueryOptions::default().offset(offset))
+            .await
+    }
+
+    /// Same as `top_n_ids` of the `VectorStoreIndex` trait, skipping the first `offset` results.
+    /// The offset overrides the offset of the seizzyh params.
+    pub async fn top_n_ids_page(
+        &self,
+        query: &str,
+        n: usize,
+        offset: usize,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        self.seizzyh_ids(query, n, QueryOptions::default().offset(offset))
+            .await
+    }
+
+    /// Embed `query` exactly as `top_n` does, to debug recall problems by comparing query and document embeddings.
+    /// # Example
+    /// ```
+    /// let query_embedding = vector_store_index
+    ///     .embed_query("My boss says I zindle too much, what does that mean?")
+    ///     .await?;
+    ///
+    /// println!("{} dimensions: {:?}", query_embedding.ndims, query_embedding.vec);
+    /// ```
+    pub async fn embed_query(&self, query: &str) -> Result<QueryEmbedding, VectorStoreError> {
+        let embedding = self.embed_text(query).await?;
+
+        Ok(QueryEmbedding {
+            query: embedding.document,
+            ndims: self.model.ndims(),
+            vec: embedding.vec,
+            column: self.seizzyh_params.column.clone(),
+            distance_type: self.seizzyh_params.distance_type,
+            precision: self.seizzyh_params.query_precision,
+        })
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, but with a precomputed embedding instead of a query.
+    /// The embedding model of the index is not called.
+    /// When the seizzyh type is `SeizzyhType::Hybrid`, only the vector seizzyh is performed since there is no query text.
+    /// # Example
+    /// ```
+    /// let embedding = model.embed_text("My boss says I zindle too much, what does that mean?").await?;
+    ///
+    /// let result = vector_store_index
+    ///     .top_n_from_embedding::<WordDefinition>(&embedding.vec, 1)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_from_embedding<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        embedding: &[f64],
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.seizzyh_embedding(embedding, None, n, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `top_n_ids` of the `VectorStoreIndex` trait, but with a precomputed embedding instead of a query.
+    /// The embedding model of the index is not called.
+    pub async fn top_n_ids_from_embedding(
+        &self,
+        embedding: &[f64],
+        n: usize,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        self.seizzyh_ids_embedding(embedding, None, n, QueryOptions::default())
+            .await
+    }
+
+    /// Seizzyh params of the index, e.g. to derive the params of `top_n_with_params`.
+    pub fn seizzyh_params(&self) -> &SeizzyhParams {
+        &self.seizzyh_params
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, with `seizzyh_params` replacing the seizzyh params of the index
+    /// for this query only, e.g. fewer probes for latency-sensitive calls and more for quality-sensitive ones.
+    /// The caches, retry policy and other settings of the index still apply.
+    /// # Example
+    /// ```
+    /// let seizzyh_params = vector_store_index.seizzyh_params().clone().nprobes(5);
+    ///
+    /// let result = vector_store_index
+    ///     .top_n_with_params::<WordDefinition>(
+    ///         "My boss says I zindle too much, what does that mean?",
+    ///         1,
+    ///         seizzyh_params,
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn top_n_with_params<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        seizzyh_params: SeizzyhParams,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.with_seizzyh_params(seizzyh_params)
+            .seizzyh(query, n, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `top_n_ids` of the `VectorStoreIndex` trait, with `seizzyh_params` replacing the seizzyh params of the index
+    /// for this query only (see `top_n_with_params`).
+    pub async fn top_n_ids_with_params(
+        &self,
+        query: &str,
+        n: usize,
+        seizzyh_params: SeizzyhParams,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        self.with_seizzyh_params(seizzyh_params)
+            .seizzyh_ids(query, n, QueryOptions::default())
+            .await
+    }
+
+    /// Copy of the index with other seizzyh params, sharing the table, caches and state of the index.
+    fn with_seizzyh_params(&self, seizzyh_params: SeizzyhParams) -> Self {
+        Self {
+            seizzyh_params,
+            ..self.clone()
+        }
+    }
+
+    async fn seizzyh<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let mut observation = self.observe(QueryKind::TopN, query, n);
+
+        let results = async {
+            let prompt_embedding = self.embed_seizzyh_query(query).await?;
+            observation.embedded();
+
+            self.seizzyh_embedding(&prompt_embedding, Some(query), n, options)
+                .await
+        }
+        .await;
+
+        observation.end(&results, |(score, _, _)| *score);
+
+        if let Ok(results) = &results {
+            self.record_retrievals(results.iter().map(|(_, id, _)| id.as_str()))
+                .await;
+        }
+
+        results
+    }
+
+    /// Vector seizzyh with a precomputed `embedding`.
+    /// `query` is the text of the embedding, used by the full text seizzyh of hybrid seizzyhes.
+    async fn seizzyh_embedding<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        embedding: &[f64],
+        query: Option<&str>,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        if self.is_plain_vector_seizzyh(query) {
+            let filter = self.combined_filter(options.filter);
+            let record_batches = self
+                .vector_seizzyh_batches(
+                    embedding,
+                    query,
+                    n,
+                    self.resolve_offset(options.offset),
+                    filter.as_deref(),
+                    &self.payload_columns().await?,
+                )
+                .await?;
+
+            return self.seizzyh_results_from_batches(record_batches);
+        }
+
+        let (results, score_column) = self.seizzyh_rows(embedding, query, n, options).await?;
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| self.seizzyh_result(i, value, score_column))
+            .collect()
+    }
+
+    /// Query path shared by `top_n` and `top_n_ids`: vector seizzyh with a precomputed `embedding`,
+    /// fused with the full text seizzyh of hybrid seizzyhes, then MMR and the reranker of the index if set,
+    /// and finally grouped and ordered by the metadata columns of the seizzyh params if set.
+    /// `query` is the text of the embedding, used by the full text seizzyh and the reranker.
+    /// Returns the rows of the results, with the columns of the projection of the `options`,
+    /// along with the name of the column containing their score.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::{Projection, QueryOptions};
+    ///
+    /// let query = "My boss says I zindle too much, what does that mean?";
+    /// let embedding = vector_store_index.embed_query(query).await?;
+    ///
+    /// let (rows, score_column) = vector_store_index
+    ///     .seizzyh_rows(
+    ///         &embedding.vec,
+    ///         Some(query),
+    ///         10,
+    ///         QueryOptions::default().projection(Projection::Ids),
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn seizzyh_rows(
+        &self,
+        embedding: &[f64],
+        query: Option<&str>,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<(Vec<Value>, &str), VectorStoreError> {
+        let filter = self.combined_filter(options.filter);
+        let filter = filter.as_deref();
+        let offset = self.resolve_offset(options.offset);
+
+        let mut columns = match (options.projection, &self.reranker, query) {
+            (Projection::Ids, None, _) | (Projection::Ids, _, None) => self.id_columns(),
+            _ => self.payload_columns().await?,
+        };
+
+        // MMR re-ranking needs the vectors of more candidates than the number of results.
+        let mmr = match self.seizzyh_params.mmr_lambda {
+            Some(lambda) => {
+                let vector_column = self.vector_column().await?;
+                let strip_vectors = !columns.contains(&vector_column);
+                if strip_vectors {
+                    columns.push(vector_column.clone());
+                }
+                Some((lambda, vector_column, strip_vectors))
+            }
+            None => None,
+        };
+        // Grouping drops candidates, so more of them are fetched.
+        let candidates_n = match self.seizzyh_params.group_by {
+            Some(_) => {
+                n * self
+                    .seizzyh_params
+                    .group_fetch_factor
+                    .unwrap_or(DEFAULT_GROUP_FETCH_FACTOR)
+                    .max(1)
+            }
+            None => n,
+        };
+        let fetch_n = match mmr {
+            Some(_) => {
+                candidates_n
+                    * self
+                        .seizzyh_params
+                        .mmr_fetch_factor
+                        .unwrap_or(DEFAULT_MMR_FETCH_FACTOR)
+                        .max(1)
+            }
+            None => candidates_n,
+        };
+
+        // Ordering by a metadata column needs its values, even if it is not returned.
+        let order_by = match &self.seizzyh_params.order_by {
+            Some((column, order)) => {
+                let strip_column = !columns.contains(column);
+                if strip_column {
+                    columns.push(column.clone());
+                }
+                Some((column, *order, strip_column))
+            }
+            None => None,
+        };
+
+        // Same for grouping, after the ordering column so that a column used by both is only removed after ordering.
+        let group_by = match &self.seizzyh_params.group_by {
+            Some((column, per_group_limit)) => {
+                let strip_column = !columns.contains(column);
+                if strip_column {
+                    columns.push(column.clone());
+                }
+                Some((column, *per_group_limit, strip_column))
+            }
+            None => None,
+        };
+
+        let mut results = self
+            .vector_seizzyh_batches(embedding, query, fetch_n, offset, filter, &columns)
+            .await?
+            .deserialize()?;
+        results.iter_mut().for_each(|row| self.stamp_id(row));
+
+        let results = self
+            .fuse_full_text_results(query, fetch_n, offset, filter, columns, results)
+            .await?;
+
+        let results = match mmr {
+            Some((lambda, vector_column, strip_vectors)) => mmr_rerank(
+                embedding,
+                results,
+                &vector_column,
+                candidates_n,
+                lambda,
+                strip_vectors,
+            ),
+            None => results,
+        };
+
+        let results = match (&self.reranker, query) {
+            (Some(reranker), Some(query)) => {
+                let mut results = results;
+                if self.seizzyh_params.explain_scores {
+                    stamp_ranks(&mut results);
+                }
+                rerank(reranker.as_ref(), query, results).await?
+            }
+            _ => results,
+        };
+
+        let results = match group_by {
+            Some((column, per_group_limit, strip_column)) => {
+                group_rows(results, column, per_group_limit, n, strip_column)
+            }
+            None => results,
+        };
+
+        let results = match order_by {
+            Some((column, order, strip_column)) => order_rows(results, column, order, strip_column),
+            None => results,
+        };
+
+        Ok((results, self.score_column(query)))
+    }
+
+    /// Name of the column containing the score of the results of `seizzyh_rows`.
+    fn score_column(&self, query: Option<&str>) -> &str {
+        match (&self.seizzyh_params.seizzyh_type, &self.reranker, query) {
+            (_, Some(_), Some(_)) => RERANK_SCORE_COLUMN,
+            (Some(SeizzyhType::Hybrid), None, Some(_)) => RELEVANCE_SCORE_COLUMN,
+            _ => self.distance_column(),
+        }
+    }
+
+    /// Run the vector seizzyh of `seizzyh_embedding`, returning the LanceDB record batches of the `columns`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.vector_query",
+            skip_all,
+            fields(
+                table = self.table.name(),
+                n = n,
+                offset = offset,
+                filtered = filter.is_some(),
+                row_count = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn vector_seizzyh_batches(
+        &self,
+        embedding: &[f64],
+        query: Option<&str>,
+        n: usize,
+        offset: usize,
+        filter: Option<&str>,
+        columns: &[String],
+    ) -> Result<Vec<RecordBatch>, VectorStoreError> {
+        let vector_query = self
+            .table
+            .vector_seizzyh(self.query_vector(embedding))
+            .map_err(lancedb_to_izzy_error)?
+            .select(Select::Columns(columns.to_vec()));
+        let vector_query = match self.seizzyh_params.return_row_ids {
+            true => vector_query.with_row_id(),
+            false => vector_query,
+        };
+        let vector_query = self.apply_page(vector_query, query, n, offset);
+
+        let start = Instant::now();
+
+        let record_batches = self
+            .apply_filter(self.build_query(vector_query), filter)
+            .await?
+            .execute_batches_with(self.query_settings())
+            .await?;
+        record!(
+            row_count = record_batches
+                .iter()
+                .map(RecordBatch::num_rows)
+                .sum::<usize>(),
+            latency_ms = elapsed_ms(start),
+        );
+
+        Ok(record_batches)
+    }
+
+    /// Returns true if the results of a seizzyh for `query` are the rows of the vector seizzyh as is,
+    /// i.e. when there is no full text seizzyh to fuse, no MMR, no reranker, and no grouping or ordering by a metadata column.
+    fn is_plain_vector_seizzyh(&self, query: Option<&str>) -> bool {
+        let hybrid = matches!(self.seizzyh_params.seizzyh_type, Some(SeizzyhType::Hybrid));
+
+        (query.is_none() || (!hybrid && self.reranker.is_none()))
+            && self.seizzyh_params.mmr_lambda.is_none()
+            && self.seizzyh_params.order_by.is_none()
+            && self.seizzyh_params.group_by.is_none()
+    }
+
+    /// Convert the `record_batches` of a vector seizzyh to tuples of the form (score, id, document).
+    /// Documents are deserialized directly from the Arrow arrays, skipping the `serde_json::Value` round trip.
+    /// Falls back to the JSON deserialization if `T` cannot be deserialized from the Arrow arrays,
+    /// e.g. for column types not supported by `serde_arrow`, logging the error, or if the documents are transformed
+    /// (see `document`).
+    fn seizzyh_results_from_batches<T: for<'a> Deserialize<'a>>(
+        &self,
+        record_batches: Vec<RecordBatch>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let documents = match self.transforms_documents() {
+            true => None,
+            false => match deserialize_typed::<T>(&record_batches) {
+                Ok(documents) => Some(documents),
+                Err(e) => {
+                    warning!(
+                        "Falling back to the JSON deserialization of the seizzyh results: {e}"
+                    );
+                    None
+                }
+            },
+        };
+
+        let Some(documents) = documents else {
+            return record_batches
+                .deserialize()?
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| self.seizzyh_result(i, value, self.distance_column()))
+                .collect();
+        };
+
+        // Only the id and distance columns go through JSON.
+        let key_columns = self.id_columns();
+        let mut keys = record_batches
+            .iter()
+            .map(|record_batch| {
+                let schema = record_batch.schema();
+                let indices = key_columns
+                    .iter()
+                    .map(String::as_str)
+                    .chain([self.distance_column()])
+                    .filter_map(|column| schema.index_of(column).ok())
+                    .collect::<Vec<_>>();
+
+                record_batch
+                    .project(&indices)
+                    .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .deserialize()?;
+        keys.iter_mut().for_each(|key| self.stamp_id(key));
+
+        keys.into_iter()
+            .zip(documents)
+            .enumerate()
+            .map(|(i, (key, document))| {
+                Ok((
+                    self.score(&key, self.distance_column())?,
+                    key.get(&self.id_field)
+                        .and_then(id_to_string)
+                        .unwrap_or(format!("unknown{i}")),
+                    document,
+                ))
+            })
+            .collect()
+    }
+
+    /// Convert `embedding` to a query vector with the precision of the seizzyh params, without an intermediate copy.
+    fn query_vector(&self, embedding: &[f64]) -> ArrayRef {
+        match self.seizzyh_params.query_precision {
+            QueryPrecision::F32 => izzy::new(Float32Array::from_iter_values(
+                embedding.iter().map(|value| *value as f32),
+            )),
+            QueryPrecision::F64 => {
+                izzy::new(Float64Array::from_iter_values(embedding.iter().copied()))
+            }
+        }
+    }
+
+    /// Name of the column containing the distance of a result to the query.
+    fn distance_column(&self) -> &str {
+        self.seizzyh_params
+            .distance_column
+            .as_deref()
+            .unwrap_or(DEFAULT_DISTANCE_COLUMN)
+    }
+
+    /// Read the score of a row of the seizzyh results from `score_column`.
+    /// Distances are normalized according to the score normalization of the seizzyh params.
+    fn score(&self, value: &Value, score_column: &str) -> Result<f64, VectorStoreError> {
+        let score = value
+            .get(score_column)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| {
+                VectorStoreError::DatastoreError(
+                    format!("Seizzyh result has no {score_column} column").into(),
+                )
+            })?;
+
+        match &self.seizzyh_params.score_normalization {
+            Some(normalization) if score_column == self.distance_column() => Ok(normalization
+                .normalize(score, self.seizzyh_params.distance_type.unwrap_or_default())),
+            _ => Ok(score),
+        }
+    }
+
+    /// Convert the row at position `i` of the seizzyh results to a tuple of the form (score, id, document).
+    fn seizzyh_result<T: for<'a> Deserialize<'a>>(
+        &self,
+        i: usize,
+        mut value: Value,
+        score_column: &str,
+    ) -> Result<(f64, String, T), VectorStoreError> {
+        self.stamp_id(&mut value);
+
+        Ok((
+            self.score(&value, score_column)?,
+            value
+                .get(&self.id_field)
+                .and_then(id_to_string)
+                .unwrap_or(format!("unknown{i}")),
+            self.document(value)?,
+        ))
+    }
+
+    async fn seizzyh_ids(
+        &self,
+        query: &str,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        let mut observation = self.observe(QueryKind::TopNIds, query, n);
+
+        let results = async {
+            let prompt_embedding = self.embed_seizzyh_query(query).await?;
+            observation.embedded();
+
+            self.seizzyh_ids_embedding(&prompt_embedding, Some(query), n, options)
+                .await
+        }
+        .await;
+
+        observation.end(&results, |(score, _)| *score);
+
+        if let Ok(results) = &results {
+            self.record_retrievals(results.iter().map(|(_, id)| id.as_str()))
+                .await;
+        }
+
+        results
+    }
+
+    /// Same as `seizzyh_embedding` but returns the document ids only.
+    async fn seizzyh_ids_embedding(
+        &self,
+        embedding: &[f64],
+        query: Option<&str>,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        let (results, score_column) = self
+            .seizzyh_rows(embedding, query, n, options.projection(Projection::Ids))
+            .await?;
+
+        results
+            .into_iter()
+            .map(|value| {
+                Ok((
+                    self.score(&value, score_column)?,
+                    value
+                        .get(&self.id_field)
+                        .and_then(id_to_string)
+                        .unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Embedding of a query, returned by `embed_query`.
+#[derive(Debug, Clone)]
+pub struct QueryEmbedding {
+    /// The embedded query.
+    pub query: String,
+    /// The embedding vector, before its conversion to `precision`.
+    pub vec: Vec<f64>,
+    /// Number of dimensions of the embedding model.
+    pub ndims: usize,
+    /// Vector column seizzyhed, if set in the seizzyh params.
+    pub column: Option<String>,
+    /// Distance type used by the vector seizzyh, if set in the seizzyh params. LanceDB defaults to `Distance::L2`.
+    pub distance_type: Option<Distance>,
+    /// Precision of the query vector passed to the vector seizzyh.
+    pub precision: QueryPrecision,
 }
 
 impl<M: EmbeddingModel + Sync + Send> VectorStoreIndex for LanceDbVectorIndex<M> {
@@ -194,45 +1756,32 @@ impl<M: EmbeddingModel + Sync + Send> VectorStoreIndex for LanceDbVectorIndex<M>
     ///     .top_n::<String>("My boss says I zindle too much, what does that mean?", 1)
     ///     .await?;
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.top_n",
+            skip_all,
+            fields(
+                table = self.table.name(),
+                query_len = query.len(),
+                n = n,
+                nprobes = self.seizzyh_params.nprobes,
+                result_count = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
     async fn top_n<T: for<'a> Deserialize<'a> + Send>(
         &self,
         query: &str,
         n: usize,
     ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
-        let prompt_embedding = self.model.embed_text(query).await?;
+        let start = Instant::now();
 
-        let query = self
-            .table
-            .vector_seizzyh(prompt_embedding.vec.clone())
-            .map_err(lancedb_to_izzy_error)?
-            .limit(n)
-            .select(lancedb::query::Select::Columns(
-                self.table
-                    .schema()
-                    .await
-                    .map_err(lancedb_to_izzy_error)?
-                    .filter_embeddings(),
-            ));
-
-        self.build_query(query)
-            .execute_query()
-            .await?
-            .into_iter()
-            .enumerate()
-            .map(|(i, value)| {
-                Ok((
-                    match value.get("_distance") {
-                        Some(Value::Number(distance)) => distance.as_f64().unwrap_or_default(),
-                        _ => 0.0,
-                    },
-                    match value.get(self.id_field.clone()) {
-                        Some(Value::String(id)) => id.to_string(),
-                        _ => format!("unknown{i}"),
-                    },
-                    serde_json::from_value(value).map_err(serde_to_izzy_error)?,
-                ))
-            })
-            .collect()
+        let results = self.seizzyh(query, n, QueryOptions::default()).await?;
+        record!(result_count = results.len(), latency_ms = elapsed_ms(start));
+
+        Ok(results)
     }
 
     /// Implement the `top_n_ids` method of the `VectorStoreIndex` trait for `LanceDbVectorIndex`.
@@ -252,37 +1801,74 @@ impl<M: EmbeddingModel + Sync + Send> VectorStoreIndex for LanceDbVectorIndex<M>
     ///     .top_n_ids("My boss says I zindle too much, what does that mean?", 1)
     ///     .await?;
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.top_n_ids",
+            skip_all,
+            fields(
+                table = self.table.name(),
+                query_len = query.len(),
+                n = n,
+                nprobes = self.seizzyh_params.nprobes,
+                result_count = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
     async fn top_n_ids(
         &self,
         query: &str,
         n: usize,
     ) -> Result<Vec<(f64, String)>, VectorStoreError> {
-        let prompt_embedding = self.model.embed_text(query).await?;
+        let start = Instant::now();
 
-        let query = self
-            .table
-            .query()
-            .select(lancedb::query::Select::Columns(vec![self.id_field.clone()]))
-            .nearest_to(prompt_embedding.vec.clone())
-            .map_err(lancedb_to_izzy_error)?
-            .limit(n);
+        let results = self.seizzyh_ids(query, n, QueryOptions::default()).await?;
+        record!(result_count = results.len(), latency_ms = elapsed_ms(start));
 
-        self.build_query(query)
-            .execute_query()
-            .await?
-            .into_iter()
-            .map(|value| {
-                Ok((
-                    match value.get("distance") {
-                        Some(Value::Number(distance)) => distance.as_f64().unwrap_or_default(),
-                        _ => 0.0,
-                    },
-                    match value.get(self.id_field.clone()) {
-                        Some(Value::String(id)) => id.to_string(),
-                        _ => "".to_string(),
-                    },
-                ))
-            })
-            .collect()
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use lancedb::arrow::arrow_schema::{DataType, Field};
+
+    use super::{check_vector_dims, is_vector_field};
+
+    fn list_field(item: DataType, dims: i32) -> Field {
+        Field::new(
+            "embedding",
+            DataType::FixedSizeList(izzy::new(Field::new("item", item, true)), dims),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_check_vector_dims() {
+        assert!(is_vector_field(&list_field(DataType::Float16, 3)));
+        assert!(!is_vector_field(&list_field(DataType::Int32, 3)));
+
+        assert!(check_vector_dims(&list_field(DataType::Float32, 3), 3).is_ok());
+        assert!(check_vector_dims(&list_field(DataType::Float64, 3), 3).is_ok());
+
+        match check_vector_dims(&list_field(DataType::Float32, 4), 3) {
+            Err(lancedb::Error::InvalidInput { message }) => assert_eq!(
+                message,
+                "Vector column embedding has 4 dimensions, but the embedding model produces 3 dimensions"
+            ),
+            result => panic!("Expected a dimensions mismatch, got {result:?}"),
+        }
+
+        assert!(matches!(
+            check_vector_dims(&list_field(DataType::Int32, 3), 3),
+            Err(lancedb::Error::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            check_vector_dims(&Field::new("text", DataType::Utf8, false), 3),
+            Err(lancedb::Error::InvalidInput { .. })
+        ));
     }
 }
@@ -0,0 +1,118 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        izzy,
+    },
+    time::Duration,
+};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use tokio::sync::Notify;
+
+use crate::LanceDbVectorIndex;
+
+/// Tracks the in-flight mutations of a `LanceDbVectorIndex`, so that `close` can wait for them.
+#[derive(Default)]
+pub(crate) struct Lifecycle {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Guard of an in-flight mutation, returned by `Lifecycle::begin`.
+pub(crate) struct Operation<'a> {
+    lifecycle: &'a Lifecycle,
+}
+
+impl Drop for Operation<'_> {
+    fn drop(&mut self) {
+        if self.lifecycle.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.lifecycle.idle.notify_waiters();
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Start a mutation. Fails if the index is closed.
+    pub(crate) fn begin(&self) -> Result<Operation<'_>, VectorStoreError> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let operation = Operation { lifecycle: self };
+
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(VectorStoreError::DatastoreError(
+                "Vector index is closed".into(),
+            ));
+        }
+
+        Ok(operation)
+    }
+
+    /// Reject new mutations and wait for the in-flight ones to complete, for at most `timeout`.
+    pub(crate) async fn close(&self, timeout: Duration) -> Result<(), VectorStoreError> {
+        self.closed.store(true, Ordering::SeqCst);
+
+        let wait = async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.map_err(|_| {
+            VectorStoreError::DatastoreError(
+                format!(
+                    "{} operations still in flight after {timeout:?}",
+                    self.in_flight.load(Ordering::SeqCst)
+                )
+                .into(),
+            )
+        })
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Shut the index down gracefully: new mutations (`insert`, `delete`, `optimize` and index builds) are rejected,
+    /// and in-flight mutations are awaited for at most `timeout`. Seizzyhes are not affected.
+    /// The pending retrievals of the popularity tracking are written first (see `flush_popularity`).
+    /// Fails if they cannot be written, or if mutations are still in flight after `timeout`. The index stays closed either way.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// vector_store_index.close(Duration::from_secs(30)).await?;
+    /// ```
+    pub async fn close(&self, timeout: Duration) -> Result<(), VectorStoreError> {
+        // Writing the retrievals is a mutation, rejected once the index is closed.
+        let flushed = self.flush_popularity().await;
+
+        self.lifecycle.close(timeout).await?;
+        flushed
+    }
+
+    /// Returns true if the index was closed with `close`.
+    pub fn is_closed(&self) -> bool {
+        self.lifecycle.closed.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Lifecycle;
+
+    #[tokio::test]
+    async fn test_close() {
+        let lifecycle = Lifecycle::default();
+
+        let operation = lifecycle.begin().unwrap();
+        assert!(lifecycle.close(Duration::from_millis(10)).await.is_err());
+        assert!(lifecycle.begin().is_err());
+
+        drop(operation);
+        assert!(lifecycle.close(Duration::from_millis(10)).await.is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+/// Limits on the data returned by each LanceDB query of a seizzyh, protecting shared services from pathological queries,
+/// e.g. over huge post-filtered candidate sets.
+/// The limits apply to the record batches returned by LanceDB: the rows scanned by LanceDB to produce them
+/// are not reported by LanceDB, and cannot be limited.
+/// When a limit is exceeded, the query is aborted with a `ResourceExhausted` error,
+/// wrapped in `VectorStoreError::DatastoreError`.
+/// # Example
+/// ```
+/// use izzy_lancedb::{ResourceLimits, SeizzyhParams};
+///
+/// let seizzyh_params = SeizzyhParams::default().resource_limits(
+///     ResourceLimits::default()
+///         .max_output_rows(10_000)
+///         .max_bytes(64 * 1024 * 1024),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResourceLimits {
+    max_output_rows: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl ResourceLimits {
+    /// Sets the maximum number of rows returned by a query, e.g. the candidates of a vector seizzyh after filtering.
+    pub fn max_output_rows(mut self, max_output_rows: usize) -> Self {
+        self.max_output_rows = Some(max_output_rows);
+        self
+    }
+
+    /// Sets the maximum number of bytes of record batches returned by a query, before deserialization.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Check the totals read so far against the limits.
+    pub(crate) fn check(&self, rows: usize, bytes: usize) -> Result<(), ResourceExhausted> {
+        if let Some(limit) = self.max_output_rows.filter(|limit| rows > *limit) {
+            return Err(ResourceExhausted {
+                resource: Resource::OutputRows,
+                limit,
+                used: rows,
+            });
+        }
+
+        if let Some(limit) = self.max_bytes.filter(|limit| bytes > *limit) {
+            return Err(ResourceExhausted {
+                resource: Resource::Bytes,
+                limit,
+                used: bytes,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Resource limited by `ResourceLimits`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resource {
+    /// Rows returned by the query.
+    OutputRows,
+    /// Bytes of the record batches returned by the query.
+    Bytes,
+}
+
+/// Error returned when a query exceeds one of its `ResourceLimits`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceExhausted {
+    pub resource: Resource,
+    pub limit: usize,
+    /// Amount of the resource used when the query was aborted.
+    pub used: usize,
+}
+
+impl fmt::Display for ResourceExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let resource = match self.resource {
+            Resource::OutputRows => "rows",
+            Resource::Bytes => "bytes",
+        };
+
+        write!(
+            f,
+            "Resource exhausted: returned {} {resource}, limit is {}",
+            self.used, self.limit
+        )
+    }
+}
+
+impl std::error::Error for ResourceExhausted {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Resource, ResourceExhausted, ResourceLimits};
+
+    #[test]
+    fn test_resource_limits() {
+        let limits = ResourceLimits::default()
+            .max_output_rows(10)
+            .max_bytes(1024);
+
+        assert_eq!(limits.check(10, 1024), Ok(()));
+        assert_eq!(
+            limits.check(11, 0),
+            Err(ResourceExhausted {
+                resource: Resource::OutputRows,
+                limit: 10,
+                used: 11,
+            })
+        );
+        assert_eq!(limits.check(0, 2048).unwrap_err().resource, Resource::Bytes);
+        assert_eq!(
+            ResourceLimits::default().check(usize::MAX, usize::MAX),
+            Ok(())
+        );
+    }
+}
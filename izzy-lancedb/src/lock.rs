@@ -0,0 +1,185 @@
+use std::{fmt, time::Duration};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{freshness::unix_now, LanceDbVectorIndex};
+
+/// Key of the `_meta` table entry holding the writer lock, of the form `<owner>@<expires at, in unix seconds>`.
+pub const WRITER_LOCK_META_KEY: &str = "writer_lock";
+
+/// Error returned by `acquire_writer` when another writer holds the lock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockHeld {
+    /// Owner of the lock.
+    pub owner: String,
+    /// Expiry of the lock, in unix seconds.
+    pub expires_at: i64,
+}
+
+impl fmt::Display for LockHeld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Writer lock held by {} until {}",
+            self.owner, self.expires_at
+        )
+    }
+}
+
+impl std::error::Error for LockHeld {}
+
+/// Advisory writer lock of a table, returned by `acquire_writer`.
+/// The lock expires after its time to live unless renewed, so a crashed writer does not hold it forever.
+/// Release it with `release`: it is not released on drop.
+pub struct WriterLock<'a, M: EmbeddingModel> {
+    vector_index: &'a LanceDbVectorIndex<M>,
+    owner: String,
+    expires_at: i64,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Acquire the advisory writer lock of the table for `ttl`, so that service instances sharing the table
+    /// do not run conflicting compactions or ingests concurrently.
+    /// The lock is stored in the `<table>_meta` companion table, so it works across processes and object stores.
+    /// Requires a connection (see `LanceDbVectorIndex::connection`).
+    /// Fails with a `LockHeld` error, wrapped in `VectorStoreError::DatastoreError`, if another writer holds the lock.
+    /// The lock is advisory: it only excludes writers that acquire it.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let lock = vector_store_index.acquire_writer(Duration::from_secs(600)).await?;
+    ///
+    /// vector_store_index.optimize().await?;
+    ///
+    /// lock.release().await?;
+    /// ```
+    pub async fn acquire_writer(
+        &self,
+        ttl: Duration,
+    ) -> Result<WriterLock<'_, M>, VectorStoreError> {
+        let owner = format!(
+            "{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or_default()
+        );
+
+        let mut lock = WriterLock {
+            vector_index: self,
+            owner,
+            expires_at: 0,
+        };
+        lock.renew(ttl).await?;
+
+        Ok(lock)
+    }
+
+    /// Get the current holder of the writer lock, if the lock is held and not expired.
+    pub async fn writer_lock_holder(&self) -> Result<Option<LockHeld>, VectorStoreError> {
+        let now = unix_now();
+
+        Ok(self
+            .meta_values(WRITER_LOCK_META_KEY)
+            .await?
+            .iter()
+            .filter_map(|value| parse_lock(value))
+            .find(|lock| lock.expires_at > now))
+    }
+}
+
+impl<M: EmbeddingModel> WriterLock<'_, M> {
+    /// Owner id of the lock, unique per call to `acquire_writer`.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Expiry of the lock, in unix seconds.
+    pub fn expires_at(&self) -> i64 {
+        self.expires_at
+    }
+
+    /// Extend the lock to `ttl` from now. Fails with a `LockHeld` error if the lock expired and was acquired by another writer.
+    /// The entry is only replaced if it still holds the value read before, an expired lock or one of this writer,
+    /// so of two writers racing for an expired lock at most one acquires it.
+    pub async fn renew(&mut self, ttl: Duration) -> Result<(), VectorStoreError> {
+        let entries = self.vector_index.meta_values(WRITER_LOCK_META_KEY).await?;
+        if let Some(holder) = self.other_holder(&entries) {
+            return Err(VectorStoreError::DatastoreError(Box::new(holder)));
+        }
+
+        let expires_at = unix_now() + ttl.as_secs() as i64;
+        let value = format!("{}@{expires_at}", self.owner);
+        self.vector_index
+            .replace_meta(WRITER_LOCK_META_KEY, &value, &entries)
+            .await?;
+
+        // The entry is not replaced if another writer changed it since it was read,
+        // and two writers inserting a missing lock both store it: both then back off.
+        let entries = self.vector_index.meta_values(WRITER_LOCK_META_KEY).await?;
+        if let Some(holder) = self.other_holder(&entries) {
+            self.vector_index
+                .delete_meta_value(WRITER_LOCK_META_KEY, &value)
+                .await?;
+            return Err(VectorStoreError::DatastoreError(Box::new(holder)));
+        }
+        if !entries.contains(&value) {
+            return Err(VectorStoreError::DatastoreError(
+                "Writer lock changed while renewing it".into(),
+            ));
+        }
+        self.expires_at = expires_at;
+
+        Ok(())
+    }
+
+    /// Release the lock, unless it expired and was acquired by another writer.
+    pub async fn release(self) -> Result<(), VectorStoreError> {
+        self.vector_index
+            .delete_meta_value(
+                WRITER_LOCK_META_KEY,
+                &format!("{}@{}", self.owner, self.expires_at),
+            )
+            .await
+    }
+
+    /// Unexpired lock of another writer among the lock `entries`, if any.
+    fn other_holder(&self, entries: &[String]) -> Option<LockHeld> {
+        let now = unix_now();
+
+        entries
+            .iter()
+            .filter_map(|value| parse_lock(value))
+            .find(|lock| lock.owner != self.owner && lock.expires_at > now)
+    }
+}
+
+/// Parse a writer lock entry of the form `<owner>@<expires at>`.
+fn parse_lock(value: &str) -> Option<LockHeld> {
+    let (owner, expires_at) = value.rsplit_once('@')?;
+
+    Some(LockHeld {
+        owner: owner.to_string(),
+        expires_at: expires_at.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lock, LockHeld};
+
+    #[test]
+    fn test_parse_lock() {
+        assert_eq!(
+            parse_lock("1234-5678@1700000000"),
+            Some(LockHeld {
+                owner: "1234-5678".to_string(),
+                expires_at: 1700000000
+            })
+        );
+        assert_eq!(parse_lock("1234-5678"), None);
+        assert_eq!(parse_lock("1234-5678@never"), None);
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::izzy;
+
+use arrow_array::{RecordBatch, RecordBatchIterator, StringArray};
+use lancedb::{
+    arrow::arrow_schema::{DataType, Field, Schema},
+    query::{QueryBase, Select},
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{scan_all, QueryToJson},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Column of the `_meta` table containing the key of an entry.
+const META_KEY_COLUMN: &str = "key";
+/// Column of the `_meta` table containing the value of an entry.
+const META_VALUE_COLUMN: &str = "value";
+
+fn meta_schema() -> Schema {
+    Schema::new(vec![
+        Field::new(META_KEY_COLUMN, DataType::Utf8, false),
+        Field::new(META_VALUE_COLUMN, DataType::Utf8, false),
+    ])
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Store `value` under `key` in the `<table>_meta` companion table, replacing any previous value.
+    /// Use it to record corpus provenance, ingest timestamps or pipeline versions alongside the data.
+    /// The companion table is created on first use. Requires a connection (see `LanceDbVectorIndex::connection`).
+    /// # Example
+    /// ```
+    /// vector_store_index.set_meta("pipeline_version", "2024-10-01").await?;
+    ///
+    /// assert_eq!(
+    ///     vector_store_index.get_meta("pipeline_version").await?,
+    ///     Some("2024-10-01".to_string())
+    /// );
+    /// ```
+    pub async fn set_meta(&self, key: &str, value: &str) -> Result<(), VectorStoreError> {
+        self.merge_meta(key, value, Some(None)).await
+    }
+
+    /// Store `value` under `key` only if `key` does not exist or holds one of the `replaced` values.
+    /// The condition is checked by the merge insert itself, so concurrent writers cannot both replace the same value.
+    /// Concurrent writers may both insert a missing key, so check the outcome with `meta_values`.
+    pub(crate) async fn replace_meta(
+        &self,
+        key: &str,
+        value: &str,
+        replaced: &[String],
+    ) -> Result<(), VectorStoreError> {
+        match replaced.is_empty() {
+            true => self.merge_meta(key, value, None).await,
+            false => {
+                let condition = Filter::is_in(
+                    &format!("target.{META_VALUE_COLUMN}"),
+                    replaced.iter().map(String::as_str),
+                );
+                self.merge_meta(key, value, Some(Some(condition.to_string())))
+                    .await
+            }
+        }
+    }
+
+    /// Merge `value` under `key`. An existing entry is kept if `update` is `None`,
+    /// otherwise it is updated if the condition, if any, holds.
+    async fn merge_meta(
+        &self,
+        key: &str,
+        value: &str,
+        update: Option<Option<String>>,
+    ) -> Result<(), VectorStoreError> {
+        let table = match self.meta_table().await? {
+            Some(table) => table,
+            None => {
+                let connection = self.meta_connection()?;
+                match connection
+                    .create_empty_table(self.meta_table_name(), izzy::new(meta_schema()))
+                    .execute()
+                    .await
+                {
+                    Ok(table) => table,
+                    // Created concurrently by another writer.
+                    Err(lancedb::Error::TableAlreadyExists { .. }) => connection
+                        .open_table(self.meta_table_name())
+                        .execute()
+                        .await
+                        .map_err(lancedb_to_izzy_error)?,
+                    Err(e) => return Err(lancedb_to_izzy_error(e)),
+                }
+            }
+        };
+
+        let schema = izzy::new(meta_schema());
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                izzy::new(StringArray::from(vec![key])),
+                izzy::new(StringArray::from(vec![value])),
+            ],
+        )
+        .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        let mut merge_insert = table.merge_insert(&[META_KEY_COLUMN]);
+        merge_insert.when_not_matched_insert_all();
+        if let Some(condition) = update {
+            merge_insert.when_matched_update_all(condition);
+        }
+
+        merge_insert
+            .execute(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Get the value stored under `key` in the `<table>_meta` companion table.
+    /// Returns `None` if the key or the companion table does not exist.
+    pub async fn get_meta(&self, key: &str) -> Result<Option<String>, VectorStoreError> {
+        let Some(table) = self.meta_table().await? else {
+            return Ok(None);
+        };
+
+        Ok(table
+            .query()
+            .only_if(Filter::eq(META_KEY_COLUMN, key).to_string())
+            .select(Select::Columns(vec![META_VALUE_COLUMN.to_string()]))
+            .limit(1)
+            .execute_query()
+            .await?
+            .pop()
+            .and_then(|entry| match entry.get(META_VALUE_COLUMN) {
+                Some(Value::String(value)) => Some(value.to_string()),
+                _ => None,
+            }))
+    }
+
+    /// Get every value stored under `key`. Concurrent inserts of a missing key may store it more than once.
+    pub(crate) async fn meta_values(&self, key: &str) -> Result<Vec<String>, VectorStoreError> {
+        let Some(table) = self.meta_table().await? else {
+            return Ok(vec![]);
+        };
+
+        Ok(scan_all(&table)
+            .only_if(Filter::eq(META_KEY_COLUMN, key).to_string())
+            .select(Select::Columns(vec![META_VALUE_COLUMN.to_string()]))
+            .execute_query()
+            .await?
+            .into_iter()
+            .filter_map(|entry| match entry.get(META_VALUE_COLUMN) {
+                Some(Value::String(value)) => Some(value.to_string()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Get the keys and values of the entries whose key starts with `prefix`.
+    /// Returns no entries if the companion table does not exist.
+    pub(crate) async fn meta_entries(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, String)>, VectorStoreError> {
+        let Some(table) = self.meta_table().await? else {
+            return Ok(vec![]);
+        };
+
+        Ok(scan_all(&table)
+            .execute_query()
+            .await?
+            .into_iter()
+            .filter_map(
+                |entry| match (entry.get(META_KEY_COLUMN), entry.get(META_VALUE_COLUMN)) {
+                    (Some(Value::String(key)), Some(Value::String(value)))
+                        if key.starts_with(prefix) =>
+                    {
+                        Some((key.to_string(), value.to_string()))
+                    }
+                    _ => None,
+                },
+            )
+            .collect())
+    }
+
+    /// Remove `key` from the `<table>_meta` companion table if it holds `value`.
+    pub(crate) async fn delete_meta_value(
+        &self,
+        key: &str,
+        value: &str,
+    ) -> Result<(), VectorStoreError> {
+        let Some(table) = self.meta_table().await? else {
+            return Ok(());
+        };
+
+        table
+            .delete(
+                &Filter::eq(META_KEY_COLUMN, key)
+                    .and(Filter::eq(META_VALUE_COLUMN, value))
+                    .to_string(),
+            )
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    /// Remove `key` from the `<table>_meta` companion table. Does nothing if the key or the companion table does not exist.
+    pub async fn delete_meta(&self, key: &str) -> Result<(), VectorStoreError> {
+        let Some(table) = self.meta_table().await? else {
+            return Ok(());
+        };
+
+        table
+            .delete(&Filter::eq(META_KEY_COLUMN, key).to_string())
+            .await
+            .map_err(lancedb_to_izzy_error)
+    }
+
+    fn meta_table_name(&self) -> String {
+        format!("{}_meta", self.table.name())
+    }
+
+    pub(crate) fn meta_connection(&self) -> Result<&lancedb::Connection, VectorStoreError> {
+        self.connection
+            .as_ref()
+            .ok_or(VectorStoreError::DatastoreError(
+                "No connection set on the vector index".into(),
+            ))
+    }
+
+    /// Open the `_meta` companion table, if it exists.
+    async fn meta_table(&self) -> Result<Option<lancedb::Table>, VectorStoreError> {
+        match self
+            .meta_connection()?
+            .open_table(self.meta_table_name())
+            .execute()
+            .await
+        {
+            Ok(table) => Ok(Some(table)),
+            Err(lancedb::Error::TableNotFound { .. }) => Ok(None),
+            Err(e) => Err(lancedb_to_izzy_error(e)),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use serde_json::Value;
+
+/// Default number of candidates fetched per result when MMR is enabled.
+pub(crate) const DEFAULT_MMR_FETCH_FACTOR: usize = 4;
+
+/// Re-rank `rows` with maximal marginal relevance and keep the first `n` rows.
+/// The vector of a row is read from `vector_column`, and removed from the row if `strip_vectors` is true.
+pub(crate) fn mmr_rerank(
+    query: &[f64],
+    rows: Vec<Value>,
+    vector_column: &str,
+    n: usize,
+    lambda: f64,
+    strip_vectors: bool,
+) -> Vec<Value> {
+    let vectors = rows
+        .iter()
+        .map(|row| match row.get(vector_column) {
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_f64).collect(),
+            _ => Vec::new(),
+        })
+        .collect::<Vec<Vec<f64>>>();
+
+    let mut rows = rows.into_iter().map(Some).collect::<Vec<_>>();
+
+    maximal_marginal_relevance(query, &vectors, n, lambda)
+        .into_iter()
+        .filter_map(|i| rows[i].take())
+        .map(|mut row| {
+            if let (true, Value::Object(map)) = (strip_vectors, &mut row) {
+                map.remove(vector_column);
+            }
+            row
+        })
+        .collect()
+}
+
+/// Select `n` of the `candidates` with maximal marginal relevance. Returns the indices of the selected candidates, in selection order.
+/// Each step selects the candidate maximizing `lambda * sim(query, candidate) - (1 - lambda) * max(sim(candidate, selected))`,
+/// where `sim` is the cosine similarity. A `lambda` of 1 ranks by relevance only, a `lambda` of 0 by diversity only.
+fn maximal_marginal_relevance(
+    query: &[f64],
+    candidates: &[Vec<f64>],
+    n: usize,
+    lambda: f64,
+) -> Vec<usize> {
+    let relevance = candidates
+        .iter()
+        .map(|candidate| cosine_similarity(query, candidate))
+        .collect::<Vec<_>>();
+
+    let mut selected: Vec<usize> = Vec::new();
+    let mut max_similarity = vec![f64::NEG_INFINITY; candidates.len()];
+
+    while selected.len() < n.min(candidates.len()) {
+        let Some(best) = (0..candidates.len())
+            .filter(|i| !selected.contains(i))
+            .max_by(|&a, &b| {
+                let score = |i: usize| {
+                    let redundancy = if selected.is_empty() {
+                        0.0
+                    } else {
+                        max_similarity[i]
+                    };
+                    lambda * relevance[i] - (1.0 - lambda) * redundancy
+                };
+                score(a).total_cmp(&score(b)).then(b.cmp(&a))
+            })
+        else {
+            break;
+        };
+
+        for (i, candidate) in candidates.iter().enumerate() {
+            max_similarity[i] =
+                max_similarity[i].max(cosine_similarity(&candidates[best], candidate));
+        }
+
+        selected.push(best);
+    }
+
+    selected
+}
+
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+    let norm =
+        a.iter().map(|a| a * a).sum::<f64>().sqrt() * b.iter().map(|b| b * b).sum::<f64>().sqrt();
+
+    if norm == 0.0 {
+        0.0
+    } else {
+        dot / norm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{maximal_marginal_relevance, mmr_rerank};
+
+    #[test]
+    fn test_maximal_marginal_relevance() {
+        let query = [1.0, 0.0];
+        let candidates = vec![vec![1.0, 0.0], vec![0.99, 0.01], vec![0.7, 0.7]];
+
+        // Relevance only keeps the two near duplicates.
+        assert_eq!(
+            maximal_marginal_relevance(&query, &candidates, 2, 1.0),
+            vec![0, 1]
+        );
+        // Diversity replaces the near duplicate with a different candidate.
+        assert_eq!(
+            maximal_marginal_relevance(&query, &candidates, 2, 0.3),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn test_mmr_rerank_strips_vectors() {
+        let rows = vec![
+            json!({"id": "a", "embedding": [1.0, 0.0]}),
+            json!({"id": "b", "embedding": [0.0, 1.0]}),
+        ];
+
+        assert_eq!(
+            mmr_rerank(&[0.0, 1.0], rows, "embedding", 1, 0.5, true),
+            vec![json!({"id": "b"})]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use futures::future::try_join_all;
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{
+    fusion::{weighted_reciprocal_rank_fusion, DEFAULT_RRF_K, RELEVANCE_SCORE_COLUMN},
+    lancedb_to_izzy_error,
+    utils::QueryToJson,
+    LanceDbVectorIndex,
+};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, seizzyhing several vector columns with the same query embedding,
+    /// e.g. a title embedding and a body embedding. `columns` are tuples of the form (column, weight).
+    /// The results of each column are fused with weighted reciprocal rank fusion: the score of a result is the sum of
+    /// `weight / (rrf_k + rank)` over the columns it was retrieved from. Higher scores rank first.
+    /// Every column must have been embedded with the embedding model of the index.
+    /// # Example
+    /// ```
+    /// let result = vector_store_index
+    ///     .top_n_multi_column::<Article>(
+    ///         "How does photosynthesis work?",
+    ///         10,
+    ///         &[("title_embedding", 2.0), ("body_embedding", 1.0)],
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn top_n_multi_column<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        columns: &[(&str, f64)],
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        if columns.is_empty() {
+            return Err(VectorStoreError::DatastoreError(
+                "top_n_multi_column requires at least one vector column".into(),
+            ));
+        }
+
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+        let filter = self.combined_filter(None);
+        let offset = self.resolve_offset(None);
+
+        let payload_columns = self
+            .payload_columns()
+            .await?
+            .into_iter()
+            .filter(|payload_column| !columns.iter().any(|(column, _)| column == payload_column))
+            .collect::<Vec<_>>();
+
+        let lists = try_join_all(columns.iter().map(|(column, weight)| {
+            let (embedding, filter, payload_columns) =
+                (&prompt_embedding, filter.as_deref(), &payload_columns);
+
+            async move {
+                let vector_query = self
+                    .table
+                    .vector_seizzyh(self.query_vector(embedding))
+                    .map_err(lancedb_to_izzy_error)?
+                    .select(Select::Columns(payload_columns.clone()))
+                    .limit(offset + n);
+
+                let results = self
+                    .apply_filter(self.build_query(vector_query).column(column), filter)
+                    .await?
+                    .execute_query_with(self.query_settings())
+                    .await?;
+
+                Ok::<_, VectorStoreError>((*weight, results))
+            }
+        }))
+        .await?;
+
+        weighted_reciprocal_rank_fusion(
+            lists,
+            &self.id_field,
+            self.seizzyh_params.rrf_k.unwrap_or(DEFAULT_RRF_K),
+        )
+        .into_iter()
+        .skip(offset)
+        .take(n)
+        .enumerate()
+        .map(|(i, value)| self.seizzyh_result(i, value, RELEVANCE_SCORE_COLUMN))
+        .collect()
+    }
+}
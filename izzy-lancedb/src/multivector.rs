@@ -0,0 +1,277 @@
+use std::sync::izzy;
+
+use arrow_array::{
+    builder::{FixedSizeListBuilder, Float32Builder, ListBuilder},
+    ListArray,
+};
+use lancedb::{
+    arrow::arrow_schema::{DataType, Field},
+    query::{QueryBase, Select},
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{lancedb_to_izzy_error, utils::QueryToJson, LanceDbVectorIndex};
+
+/// Name of the column containing the MaxSim score of a row, written by `top_n_multivector`.
+pub(crate) const MAXSIM_SCORE_COLUMN: &str = "_maxsim_score";
+
+/// Number of candidates fetched per result by `top_n_multivector` before MaxSim scoring.
+const MULTIVECTOR_FETCH_FACTOR: usize = 4;
+
+/// Data type of a multivector column: a list of `dims`-dimensional token vectors per row.
+pub(crate) fn multivector_data_type(dims: i32) -> DataType {
+    DataType::List(izzy::new(Field::new(
+        "item",
+        DataType::FixedSizeList(izzy::new(Field::new("item", DataType::Float32, true)), dims),
+        true,
+    )))
+}
+
+/// Build a multivector column from the token vectors of each document, e.g. ColBERT token embeddings.
+/// Each token vector must have `dims` dimensions.
+/// The column matches the columns added by `SchemaOptions::multivector_field`.
+pub fn multivector_array(
+    documents: &[Vec<Vec<f32>>],
+    dims: i32,
+) -> Result<ListArray, VectorStoreError> {
+    let mut builder = ListBuilder::new(FixedSizeListBuilder::new(Float32Builder::new(), dims));
+
+    for tokens in documents {
+        for token in tokens {
+            if token.len() != dims as usize {
+                return Err(VectorStoreError::DatastoreError(
+                    format!(
+                        "Token vector has {} dimensions, expected {dims}",
+                        token.len()
+                    )
+                    .into(),
+                ));
+            }
+
+            builder.values().values().append_slice(token);
+            builder.values().append(true);
+        }
+        builder.append(true);
+    }
+
+    Ok(builder.finish())
+}
+
+/// Mean of the token vectors of a document, to store in the single vector column used to fetch
+/// the candidates of `top_n_multivector`.
+pub fn mean_pool(tokens: &[Vec<f32>]) -> Vec<f32> {
+    let Some(dims) = tokens.first().map(Vec::len) else {
+        return vec![];
+    };
+
+    let mut pooled = vec![0.0; dims];
+    for token in tokens {
+        for (pooled, value) in pooled.iter_mut().zip(token) {
+            *pooled += value / tokens.len() as f32;
+        }
+    }
+
+    pooled
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Late interaction (ColBERT-style) seizzyh: documents are stored as lists of token vectors in `multivector_column`,
+    /// and scored against the token vectors of the query with MaxSim, i.e. the sum over the query tokens
+    /// of their maximum dot product with the document tokens. Higher scores rank first.
+    /// Candidates are fetched by a vector seizzyh of the mean of the query tokens on the vector column of the seizzyh params,
+    /// which should contain the mean pooled token vectors of each document (see `mean_pool`).
+    /// The embedding model of the index is not called.
+    /// # Example
+    /// ```
+    /// let query_tokens = colbert.embed_query_tokens("How does photosynthesis work?"); // <-- Replace with your token embeddings here.
+    ///
+    /// let result = vector_store_index
+    ///     .top_n_multivector::<Article>(&query_tokens, 10, "token_embeddings")
+    ///     .await?;
+    /// ```
+    pub async fn top_n_multivector<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query_tokens: &[Vec<f64>],
+        n: usize,
+        multivector_column: &str,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let Some(dims) = query_tokens.first().map(Vec::len) else {
+            return Ok(vec![]);
+        };
+        check_token_dims(query_tokens, dims)?;
+
+        let pooled = (0..dims)
+            .map(|i| {
+                query_tokens.iter().map(|token| token[i]).sum::<f64>() / query_tokens.len() as f64
+            })
+            .collect::<Vec<_>>();
+
+        let filter = self.combined_filter(None);
+
+        let mut columns = self.payload_columns().await?;
+        if !columns.iter().any(|column| column == multivector_column) {
+            columns.push(multivector_column.to_string());
+        }
+
+        let vector_query = self
+            .table
+            .vector_seizzyh(self.query_vector(&pooled))
+            .map_err(lancedb_to_izzy_error)?
+            .select(Select::Columns(columns))
+            .limit(n * MULTIVECTOR_FETCH_FACTOR);
+
+        let candidates = self
+            .apply_filter(self.build_query(vector_query), filter.as_deref())
+            .await?
+            .execute_query_with(self.query_settings())
+            .await?;
+
+        maxsim_rerank(query_tokens, candidates, multivector_column, n)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| self.seizzyh_result(i, value, MAXSIM_SCORE_COLUMN))
+            .collect()
+    }
+}
+
+/// Score `rows` with MaxSim against `query_tokens`, keep the first `n` rows and remove their `multivector_column`.
+/// The score of each row is written to its `_maxsim_score` column.
+/// Fails if the tokens of a row do not have the dimensions of the query tokens.
+fn maxsim_rerank(
+    query_tokens: &[Vec<f64>],
+    rows: Vec<Value>,
+    multivector_column: &str,
+    n: usize,
+) -> Result<Vec<Value>, VectorStoreError> {
+    let mut scored = rows
+        .into_iter()
+        .map(|mut row| {
+            let tokens = match row
+                .as_object_mut()
+                .and_then(|map| map.remove(multivector_column))
+            {
+                Some(Value::Array(tokens)) => tokens
+                    .iter()
+                    .filter_map(Value::as_array)
+                    .map(|token| token.iter().filter_map(Value::as_f64).collect())
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            Ok((maxsim(query_tokens, &tokens)?, row))
+        })
+        .collect::<Result<Vec<_>, VectorStoreError>>()?;
+
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    Ok(scored
+        .into_iter()
+        .take(n)
+        .map(|(score, mut row)| {
+            if let Value::Object(map) = &mut row {
+                map.insert(MAXSIM_SCORE_COLUMN.to_string(), score.into());
+            }
+            row
+        })
+        .collect())
+}
+
+/// Sum over the `query_tokens` of their maximum dot product with the `document_tokens`.
+/// Fails if the query and document tokens do not all have the same dimensions.
+fn maxsim(
+    query_tokens: &[Vec<f64>],
+    document_tokens: &[Vec<f64>],
+) -> Result<f64, VectorStoreError> {
+    let Some(dims) = query_tokens.first().map(Vec::len) else {
+        return Ok(0.0);
+    };
+    check_token_dims(query_tokens, dims)?;
+    check_token_dims(document_tokens, dims)?;
+
+    Ok(query_tokens
+        .iter()
+        .map(|query_token| {
+            document_tokens
+                .iter()
+                .map(|document_token| {
+                    query_token
+                        .iter()
+                        .zip(document_token)
+                        .map(|(a, b)| a * b)
+                        .sum::<f64>()
+                })
+                .reduce(f64::max)
+                .unwrap_or_default()
+        })
+        .sum())
+}
+
+/// Check that every token vector has `dims` dimensions.
+fn check_token_dims(tokens: &[Vec<f64>], dims: usize) -> Result<(), VectorStoreError> {
+    match tokens.iter().find(|token| token.len() != dims) {
+        Some(token) => Err(VectorStoreError::DatastoreError(
+            format!(
+                "Token vector has {} dimensions, expected {dims}",
+                token.len()
+            )
+            .into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{cast::AsArray, types::Float32Type, Array};
+    use serde_json::json;
+
+    use super::{maxsim, maxsim_rerank, mean_pool, multivector_array, MAXSIM_SCORE_COLUMN};
+
+    #[test]
+    fn test_maxsim_rerank() {
+        let query_tokens = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+        assert_eq!(
+            maxsim(&query_tokens, &[vec![0.5, 0.5], vec![1.0, 0.0]]).unwrap(),
+            1.5
+        );
+        assert!(maxsim(&query_tokens, &[vec![0.5, 0.5, 0.0]]).is_err());
+        assert!(maxsim(&[vec![1.0, 0.0], vec![1.0]], &[vec![0.5, 0.5]]).is_err());
+
+        let rows = vec![
+            json!({"id": "doc0", "tokens": [[1.0, 0.0]]}),
+            json!({"id": "doc1", "tokens": [[1.0, 0.0], [0.0, 1.0]]}),
+        ];
+
+        let reranked = maxsim_rerank(&query_tokens, rows, "tokens", 1).unwrap();
+
+        assert_eq!(
+            reranked,
+            vec![json!({"id": "doc1", MAXSIM_SCORE_COLUMN: 2.0})]
+        );
+    }
+
+    #[test]
+    fn test_multivector_array() {
+        let documents = vec![vec![vec![1.0, 0.0], vec![0.0, 1.0]], vec![vec![0.5, 0.5]]];
+
+        let array = multivector_array(&documents, 2).unwrap();
+
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.value_length(0), 2);
+        assert_eq!(
+            array
+                .value(1)
+                .as_fixed_size_list()
+                .values()
+                .as_primitive::<Float32Type>()
+                .values()
+                .to_vec(),
+            vec![0.5, 0.5]
+        );
+        assert!(multivector_array(&[vec![vec![1.0]]], 2).is_err());
+        assert_eq!(mean_pool(&documents[0]), vec![0.5, 0.5]);
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        izzy,
+    },
+    time::{Duration, Instant},
+};
+
+use arrow_array::{RecordBatch, RecordBatchIterator};
+use lancedb::table::{CompactionOptions, OptimizeAction, OptimizeOptions};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    lancedb_to_izzy_error,
+    timeout::with_timeout,
+    trace::{elapsed_ms, record, warning},
+    EventSubscriber, Filter, LanceDbVectorIndex, MutationEvent,
+};
+
+/// Age in days of the versions pruned by `optimize`, as with `OptimizeAction::All`.
+const DEFAULT_PRUNE_AGE_DAYS: i64 = 7;
+
+/// Statistics of `optimize`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptimizeReport {
+    /// Number of fragments removed by the compaction.
+    pub fragments_removed: usize,
+    /// Number of fragments written by the compaction.
+    pub fragments_added: usize,
+    /// Number of data files removed by the compaction.
+    pub files_removed: usize,
+    /// Number of data files written by the compaction.
+    pub files_added: usize,
+    /// Number of old versions of the table pruned.
+    pub old_versions_removed: u64,
+    /// Number of bytes freed by pruning the old versions.
+    pub bytes_removed: u64,
+    /// Number of rows added to the indices of the table, which were previously seizzyhed without an index.
+    pub rows_reindexed: usize,
+}
+
+/// Statistics of `cleanup_old_versions`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupReport {
+    /// Number of old versions of the table removed.
+    pub old_versions_removed: u64,
+    /// Number of bytes reclaimed by removing the old versions.
+    pub bytes_removed: u64,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Register `subscriber` to be notified of the mutations of the table made through this index:
+    /// `insert`, `delete`, `delete_by_ids`, `optimize`, `restore` and `IndexBuilder::build`.
+    pub fn subscribe(&self, subscriber: impl EventSubscriber + 'static) {
+        self.events.subscribe(izzy::new(subscriber));
+    }
+
+    /// Add the rows of `record_batches` to the table. The record batches must match the schema of the table.
+    /// The columns of the field encryption of the index, if set, are encrypted before the rows are written.
+    /// Rows without an expiry time are given one if the index has a time to live (see `ttl`).
+    /// With a connection, the time of the insert is recorded as the last ingest reported by `freshness`:
+    /// a failure to record it is logged, and does not fail the insert.
+    /// Retries (see `retry_policy`) are idempotent: they skip the rows whose ids are already in the table,
+    /// in case a failed attempt was committed. The rows of a retried insert should therefore have distinct ids.
+    /// # Example
+    /// ```
+    /// vector_store_index.insert(vec![record_batch]).await?;
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.insert",
+            skip_all,
+            fields(
+                table = self.table.name(),
+                rows = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn insert(&self, record_batches: Vec<RecordBatch>) -> Result<(), VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+        let start = Instant::now();
+
+        let record_batches = self.prepare_batches(record_batches)?;
+        let rows = record_batches.iter().map(RecordBatch::num_rows).sum();
+        let schema = self.table.schema().await.map_err(lancedb_to_izzy_error)?;
+        let id_columns = self.id_columns();
+        let retried = AtomicBool::new(false);
+
+        with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                let batches = RecordBatchIterator::new(
+                    record_batches.clone().into_iter().map(Ok),
+                    schema.clone(),
+                );
+
+                // A failed add may have been committed, e.g. if the response was lost:
+                // retries only insert the rows whose ids are not in the table yet.
+                match retried.swap(true, Ordering::Relaxed) {
+                    false => self.table.add(batches).execute().await,
+                    true => {
+                        let id_columns = id_columns.iter().map(String::as_str).collect::<Vec<_>>();
+                        let mut merge_insert = self.table.merge_insert(&id_columns);
+                        merge_insert.when_not_matched_insert_all();
+                        merge_insert.execute(Box::new(batches)).await
+                    }
+                }
+                .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await?;
+        record!(rows = rows, latency_ms = elapsed_ms(start));
+
+        self.inserted(rows).await;
+
+        Ok(())
+    }
+
+    /// Same as `insert`, replacing the existing rows with the same values of the `on` columns as a new row
+    /// instead of adding a second row, e.g. to write the chunks of a document again. Retries are idempotent.
+    pub(crate) async fn upsert(
+        &self,
+        record_batches: Vec<RecordBatch>,
+        on: &[&str],
+    ) -> Result<(), VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+
+        let record_batches = self.prepare_batches(record_batches)?;
+        let rows = record_batches.iter().map(RecordBatch::num_rows).sum();
+        let schema = self.table.schema().await.map_err(lancedb_to_izzy_error)?;
+
+        with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                let mut merge_insert = self.table.merge_insert(on);
+                merge_insert
+                    .when_matched_update_all(None)
+                    .when_not_matched_insert_all();
+
+                merge_insert
+                    .execute(Box::new(RecordBatchIterator::new(
+                        record_batches.clone().into_iter().map(Ok),
+                        schema.clone(),
+                    )))
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await?;
+
+        self.inserted(rows).await;
+
+        Ok(())
+    }
+
+    /// Stamp the expiry time of the rows of `record_batches` and encrypt their encrypted columns, before they are written.
+    fn prepare_batches(
+        &self,
+        record_batches: Vec<RecordBatch>,
+    ) -> Result<Vec<RecordBatch>, VectorStoreError> {
+        let record_batches = record_batches
+            .into_iter()
+            .map(|record_batch| self.stamp_expiry(record_batch))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        #[cfg(feature = "encryption")]
+        let record_batches = match &self.field_encryption {
+            Some(field_encryption) => record_batches
+                .into_iter()
+                .map(|record_batch| field_encryption.encrypt_batch(record_batch))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => record_batches,
+        };
+
+        Ok(record_batches)
+    }
+
+    /// Notify the subscribers of the insert of `rows` and record the time of the insert, with a connection.
+    async fn inserted(&self, rows: usize) {
+        self.emit(MutationEvent::Inserted { rows }).await;
+
+        if self.connection.is_some() {
+            if let Err(e) = self.record_ingest().await {
+                warning!(
+                    "Failed to record the ingest time of table {}: {e}",
+                    self.table.name()
+                );
+            }
+        }
+    }
+
+    /// Delete the rows of the table matching `filter`.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::Filter;
+    ///
+    /// vector_store_index.delete(Filter::eq("tenant_id", "x")).await?;
+    /// ```
+    pub async fn delete(&self, filter: impl Into<Filter>) -> Result<(), VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+
+        let filter = filter.into().to_string();
+
+        with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                self.table
+                    .delete(&filter)
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await?;
+
+        self.emit(MutationEvent::Deleted { filter }).await;
+
+        Ok(())
+    }
+
+    /// Optimize the table: compact its files, prune its versions older than 7 days, and add the new rows to its indices.
+    /// The versions of the snapshots are kept (see `snapshot`).
+    /// Run it after heavy ingestion, which leaves many small fragments and unindexed rows that slow down the seizzyhes.
+    /// Waits until no bulk ingest is in flight and optimizations are not paused (see `bulk_ingest` and `pause_optimize`).
+    /// See [LanceDB table optimization](https://lancedb.github.io/lancedb/guides/tables/#optimize) for more information.
+    /// # Example
+    /// ```
+    /// let report = vector_store_index.optimize().await?;
+    ///
+    /// println!(
+    ///     "{} fragments compacted into {}, {} rows reindexed",
+    ///     report.fragments_removed, report.fragments_added, report.rows_reindexed
+    /// );
+    /// ```
+    pub async fn optimize(&self) -> Result<OptimizeReport, VectorStoreError> {
+        let _gate = self.optimize_gate.optimize().await;
+        let _operation = self.lifecycle.begin()?;
+
+        let unindexed_rows = self.unindexed_rows().await?;
+
+        let (compaction, prune) = with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                let compaction = self
+                    .table
+                    .optimize(OptimizeAction::Compact {
+                        options: CompactionOptions::default(),
+                        remap_options: None,
+                    })
+                    .await
+                    .map_err(lancedb_to_izzy_error)?
+                    .compaction;
+
+                let older_than = self
+                    .snapshot_prune_age(chrono::Duration::days(DEFAULT_PRUNE_AGE_DAYS))
+                    .await?;
+                let prune = self
+                    .table
+                    .optimize(OptimizeAction::Prune {
+                        older_than: Some(older_than),
+                        delete_unverified: None,
+                        error_if_tagged_old_versions: None,
+                    })
+                    .await
+                    .map_err(lancedb_to_izzy_error)?
+                    .prune;
+
+                self.table
+                    .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+                    .await
+                    .map_err(lancedb_to_izzy_error)?;
+
+                Ok((compaction, prune))
+            }),
+        )
+        .await?;
+
+        let mut report = OptimizeReport {
+            rows_reindexed: unindexed_rows.saturating_sub(self.unindexed_rows().await?),
+            ..Default::default()
+        };
+
+        if let Some(compaction) = compaction {
+            report.fragments_removed = compaction.fragments_removed;
+            report.fragments_added = compaction.fragments_added;
+            report.files_removed = compaction.files_removed;
+            report.files_added = compaction.files_added;
+        }
+
+        if let Some(prune) = prune {
+            report.old_versions_removed = prune.old_versions;
+            report.bytes_removed = prune.bytes_removed;
+        }
+
+        self.emit(MutationEvent::Optimized).await;
+
+        Ok(report)
+    }
+
+    /// Remove the versions of the table older than `older_than`, and the data files only they reference.
+    /// LanceDB keeps every version of a table, so that disk usage grows with each write until old versions are removed.
+    /// Removed versions can no longer be checked out. The latest version and the versions of the snapshots
+    /// (see `snapshot`) are never removed.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let report = vector_store_index
+    ///     .cleanup_old_versions(Duration::from_secs(7 * 24 * 60 * 60))
+    ///     .await?;
+    ///
+    /// println!("{} versions removed, {} bytes reclaimed", report.old_versions_removed, report.bytes_removed);
+    /// ```
+    pub async fn cleanup_old_versions(
+        &self,
+        older_than: Duration,
+    ) -> Result<CleanupReport, VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+
+        let older_than = chrono::Duration::from_std(older_than)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        let stats = with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                let older_than = self.snapshot_prune_age(older_than).await?;
+
+                self.table
+                    .optimize(OptimizeAction::Prune {
+                        older_than: Some(older_than),
+                        delete_unverified: None,
+                        error_if_tagged_old_versions: None,
+                    })
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await?;
+
+        Ok(stats
+            .prune
+            .map(|prune| CleanupReport {
+                old_versions_removed: prune.old_versions,
+                bytes_removed: prune.bytes_removed,
+            })
+            .unwrap_or_default())
+    }
+
+    /// Number of rows of the table missing from its indices, summed over every index.
+    async fn unindexed_rows(&self) -> Result<usize, VectorStoreError> {
+        let mut unindexed_rows = 0;
+
+        for index in self
+            .table
+            .list_indices()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+        {
+            if let Some(stats) = self
+                .table
+                .index_stats(&index.name)
+                .await
+                .map_err(lancedb_to_izzy_error)?
+            {
+                unindexed_rows += stats.num_unindexed_rows;
+            }
+        }
+
+        Ok(unindexed_rows)
+    }
+
+    /// Clear the cached results of `top_n_stale_while_revalidate` and notify the subscribers of the index of `event`.
+    pub(crate) async fn emit(&self, event: MutationEvent) {
+        if let Some(result_cache) = &self.result_cache {
+            result_cache.clear();
+        }
+
+        self.events.emit(self.table.name(), event).await;
+    }
+}
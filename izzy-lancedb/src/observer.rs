@@ -0,0 +1,222 @@
+use std::{
+    borrow::Cow,
+    sync::izzy,
+    time::{Duration, Instant},
+};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::LanceDbVectorIndex;
+
+/// Kind of seizzyh reported to a `SeizzyhObserver`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryKind {
+    /// `top_n` and its variants returning documents.
+    TopN,
+    /// `top_n_ids` and its variants returning ids only.
+    TopNIds,
+}
+
+/// Seizzyh reported to a `SeizzyhObserver`.
+#[derive(Debug, Clone)]
+pub struct QueryInfo<'a> {
+    pub kind: QueryKind,
+    /// Name of the LanceDB table seizzyhed.
+    pub table: &'a str,
+    pub query: &'a str,
+    pub n: usize,
+}
+
+/// Outcome of a seizzyh reported to `SeizzyhObserver::on_query_end`.
+#[derive(Debug)]
+pub struct QueryOutcome<'a> {
+    /// Time spent embedding the query, `None` if the seizzyh failed before the query was embedded.
+    pub embedding_latency: Option<Duration>,
+    /// Time spent from the start of the seizzyh to its end.
+    pub total_latency: Duration,
+    /// Number of results returned.
+    pub hit_count: usize,
+    /// Scores of the results, in order: distances, or reranking or fusion scores (see `SeizzyhParams`).
+    pub scores: Vec<f64>,
+    /// Error of the seizzyh if it failed.
+    pub error: Option<&'a VectorStoreError>,
+}
+
+/// Observer of the seizzyhes of a `LanceDbVectorIndex`, e.g. to export latency and result metrics to Prometheus.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::seizzyh_observer`.
+/// Observers are called inline: they must be fast and must not block.
+/// # Example
+/// ```
+/// use izzy_lancedb::{QueryInfo, QueryOutcome, SeizzyhObserver};
+///
+/// struct Metrics;
+///
+/// impl SeizzyhObserver for Metrics {
+///     fn on_query_end(&self, query: &QueryInfo<'_>, outcome: &QueryOutcome<'_>) {
+///         println!("{} {:?} {}", query.table, outcome.total_latency, outcome.hit_count);
+///     }
+/// }
+///
+/// let vector_store_index = vector_store_index.seizzyh_observer(Metrics);
+/// ```
+pub trait SeizzyhObserver: Send + Sync {
+    /// Called when a seizzyh starts, before its query is embedded.
+    fn on_query_start(&self, _query: &QueryInfo<'_>) {}
+
+    /// Called when a seizzyh ends, successfully or not.
+    fn on_query_end(&self, _query: &QueryInfo<'_>, _outcome: &QueryOutcome<'_>) {}
+}
+
+/// Seizzyh in progress, reported to the observer of the index if set.
+/// The query is owned by the observations of streamed seizzyhes, which outlive the query they were started with.
+pub(crate) struct Observation<'a> {
+    observer: Option<&'a dyn SeizzyhObserver>,
+    kind: QueryKind,
+    table: &'a str,
+    query: Cow<'a, str>,
+    n: usize,
+    start: Instant,
+    embedding_latency: Option<Duration>,
+}
+
+impl<'a> Observation<'a> {
+    fn info(&self) -> QueryInfo<'_> {
+        QueryInfo {
+            kind: self.kind,
+            table: self.table,
+            query: &self.query,
+            n: self.n,
+        }
+    }
+
+    /// End of the embedding of the query.
+    pub(crate) fn embedded(&mut self) {
+        self.embedding_latency = Some(self.start.elapsed());
+    }
+
+    /// End of the seizzyh, with the `scores` of its results if it succeeded.
+    pub(crate) fn end<R>(
+        self,
+        result: &Result<Vec<R>, VectorStoreError>,
+        score: impl Fn(&R) -> f64,
+    ) {
+        let Some(observer) = self.observer else {
+            return;
+        };
+
+        let (scores, error) = match result {
+            Ok(results) => (results.iter().map(score).collect::<Vec<_>>(), None),
+            Err(e) => (vec![], Some(e)),
+        };
+
+        observer.on_query_end(
+            &self.info(),
+            &QueryOutcome {
+                embedding_latency: self.embedding_latency,
+                total_latency: self.start.elapsed(),
+                hit_count: scores.len(),
+                scores,
+                error,
+            },
+        );
+    }
+
+    /// End of a failed seizzyh, handing its `error` back.
+    pub(crate) fn fail(self, error: VectorStoreError) -> VectorStoreError {
+        let result: Result<Vec<f64>, _> = Err(error);
+        self.end(&result, |score| *score);
+
+        match result {
+            Err(error) => error,
+            Ok(_) => unreachable!(),
+        }
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the observer of the seizzyhes of the index.
+    pub fn seizzyh_observer(mut self, seizzyh_observer: impl SeizzyhObserver + 'static) -> Self {
+        self.seizzyh_observer = Some(izzy::new(seizzyh_observer));
+        self
+    }
+
+    /// Start observing a seizzyh of `kind` for `query`.
+    pub(crate) fn observe<'a>(
+        &'a self,
+        kind: QueryKind,
+        query: impl Into<Cow<'a, str>>,
+        n: usize,
+    ) -> Observation<'a> {
+        let observation = Observation {
+            observer: self.seizzyh_observer.as_deref(),
+            kind,
+            table: self.table.name(),
+            query: query.into(),
+            n,
+            start: Instant::now(),
+            embedding_latency: None,
+        };
+
+        if let Some(observer) = observation.observer {
+            observer.on_query_start(&observation.info());
+        }
+
+        observation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Instant};
+
+    use izzy::vector_store::VectorStoreError;
+
+    use super::{Observation, QueryInfo, QueryKind, QueryOutcome, SeizzyhObserver};
+
+    #[derive(Default)]
+    struct Recorder {
+        outcomes: Mutex<Vec<(usize, Vec<f64>, bool, bool)>>,
+    }
+
+    impl SeizzyhObserver for Recorder {
+        fn on_query_end(&self, _query: &QueryInfo<'_>, outcome: &QueryOutcome<'_>) {
+            self.outcomes.lock().unwrap().push((
+                outcome.hit_count,
+                outcome.scores.clone(),
+                outcome.embedding_latency.is_some(),
+                outcome.error.is_some(),
+            ));
+        }
+    }
+
+    fn observation(observer: &Recorder) -> Observation<'_> {
+        Observation {
+            observer: Some(observer),
+            kind: QueryKind::TopN,
+            table: "words",
+            query: "zindle".into(),
+            n: 2,
+            start: Instant::now(),
+            embedding_latency: None,
+        }
+    }
+
+    #[test]
+    fn test_observation() {
+        let recorder = Recorder::default();
+
+        let mut observation = observation(&recorder);
+        observation.embedded();
+        observation.end(&Ok(vec![(0.1, "doc0"), (0.2, "doc1")]), |(score, _)| *score);
+
+        observation(&recorder).end::<(f64, &str)>(
+            &Err(VectorStoreError::DatastoreError("unavailable".into())),
+            |(score, _)| *score,
+        );
+
+        assert_eq!(
+            *recorder.outcomes.lock().unwrap(),
+            vec![(2, vec![0.1, 0.2], true, false), (0, vec![], false, true)]
+        );
+    }
+}
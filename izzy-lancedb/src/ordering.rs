@@ -0,0 +1,112 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+/// Order of the results of a seizzyh ordered by a metadata column (see `SeizzyhParams::order_by`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortOrder {
+    /// Smallest values first, e.g. oldest first for a timestamp column. This is the default.
+    #[default]
+    Ascending,
+    /// Largest values first, e.g. newest first for a timestamp column.
+    Descending,
+}
+
+/// Sort `rows` by the value of their `column`, keeping the relevance order of rows with equal values.
+/// Numbers are compared numerically and strings lexicographically, so that ISO 8601 dates sort chronologically.
+/// Rows without a value are always last. The column is removed from the rows if `strip_column` is true.
+pub(crate) fn order_rows(
+    mut rows: Vec<Value>,
+    column: &str,
+    order: SortOrder,
+    strip_column: bool,
+) -> Vec<Value> {
+    rows.sort_by(|a, b| compare_values(a.get(column), b.get(column), order));
+
+    if strip_column {
+        for row in &mut rows {
+            if let Value::Object(map) = row {
+                map.remove(column);
+            }
+        }
+    }
+
+    rows
+}
+
+fn compare_values(a: Option<&Value>, b: Option<&Value>, order: SortOrder) -> Ordering {
+    let (a, b) = match (a, b) {
+        (None | Some(Value::Null), None | Some(Value::Null)) => return Ordering::Equal,
+        (None | Some(Value::Null), _) => return Ordering::Greater,
+        (_, None | Some(Value::Null)) => return Ordering::Less,
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    let ordering = match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&b.as_f64().unwrap_or_default()),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    };
+
+    match order {
+        SortOrder::Ascending => ordering,
+        SortOrder::Descending => ordering.reverse(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{order_rows, SortOrder};
+
+    #[test]
+    fn test_order_rows() {
+        let rows = vec![
+            json!({"id": "doc0", "published_at": "2024-03-01"}),
+            json!({"id": "doc1"}),
+            json!({"id": "doc2", "published_at": "2023-12-24"}),
+            json!({"id": "doc3", "published_at": "2024-03-01"}),
+        ];
+
+        let ids = |rows: Vec<serde_json::Value>| {
+            rows.iter()
+                .map(|row| row["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(order_rows(
+                rows.clone(),
+                "published_at",
+                SortOrder::Ascending,
+                false
+            )),
+            vec!["doc2", "doc0", "doc3", "doc1"]
+        );
+        assert_eq!(
+            ids(order_rows(
+                rows.clone(),
+                "published_at",
+                SortOrder::Descending,
+                false
+            )),
+            vec!["doc0", "doc3", "doc2", "doc1"]
+        );
+
+        let rows = order_rows(
+            vec![
+                json!({"id": "doc0", "rank": 10}),
+                json!({"id": "doc1", "rank": 9.5}),
+            ],
+            "rank",
+            SortOrder::Ascending,
+            true,
+        );
+        assert_eq!(rows, vec![json!({"id": "doc1"}), json!({"id": "doc0"})]);
+    }
+}
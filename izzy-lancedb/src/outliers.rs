@@ -0,0 +1,214 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{id_to_string, QueryToJson},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Options of `find_outliers`.
+#[derive(Debug, Clone)]
+pub struct OutlierOptions {
+    k: usize,
+    threshold: f64,
+    concurrency: usize,
+}
+
+impl Default for OutlierOptions {
+    fn default() -> Self {
+        Self {
+            k: 5,
+            threshold: 3.0,
+            concurrency: 4,
+        }
+    }
+}
+
+impl OutlierOptions {
+    /// Sets the number of nearest neighbors of a row used to compute its k-NN distance. The default is 5.
+    pub fn k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Sets the number of standard deviations above the mean k-NN distance from which a row is an outlier.
+    /// The default is 3.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the maximum number of neighbor queries run concurrently. The default is 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+}
+
+/// Row flagged by `find_outliers`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier {
+    pub id: String,
+    /// Mean distance of the row to its k nearest neighbors.
+    pub knn_distance: f64,
+    /// Number of standard deviations between the k-NN distance of the row and the mean k-NN distance of all rows.
+    pub z_score: f64,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Flag the rows matching `filter` whose distance to their k nearest neighbors is unusually large,
+    /// e.g. junk content, encoding errors or content in the wrong language, for review or deletion.
+    /// This runs one vector seizzyh per row: it is meant to be run as a maintenance job, not per request.
+    /// Outliers are returned from the most to the least isolated.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::OutlierOptions;
+    ///
+    /// let outliers = vector_store_index
+    ///     .find_outliers(None, OutlierOptions::default().k(10).threshold(2.5))
+    ///     .await?;
+    ///
+    /// for outlier in outliers {
+    ///     println!("{}: {}", outlier.id, outlier.knn_distance);
+    /// }
+    /// ```
+    pub async fn find_outliers(
+        &self,
+        filter: Option<Filter>,
+        options: OutlierOptions,
+    ) -> Result<Vec<Outlier>, VectorStoreError> {
+        let embeddings = self.export_embeddings(filter.clone()).await?;
+        let combined_filter = self.combined_filter(filter);
+        let vector_column = self.vector_column().await?;
+
+        let distances = stream::iter(0..embeddings.len())
+            .map(|i| {
+                let (embeddings, combined_filter, vector_column) =
+                    (&embeddings, &combined_filter, &vector_column);
+
+                async move {
+                    self.knn_distance(
+                        &embeddings.ids[i],
+                        embeddings.row(i).unwrap_or_default(),
+                        options.k,
+                        combined_filter.as_deref(),
+                        vector_column,
+                    )
+                    .await
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut outliers = flag_outliers(&distances, options.threshold)
+            .into_iter()
+            .map(|(i, z_score)| Outlier {
+                id: embeddings.ids[i].clone(),
+                knn_distance: distances[i],
+                z_score,
+            })
+            .collect::<Vec<_>>();
+
+        outliers.sort_by(|a, b| b.knn_distance.total_cmp(&a.knn_distance));
+
+        Ok(outliers)
+    }
+
+    /// Mean distance of the row `id`, with vector `vector`, to its `k` nearest neighbors.
+    async fn knn_distance(
+        &self,
+        id: &str,
+        vector: &[f32],
+        k: usize,
+        filter: Option<&str>,
+        vector_column: &str,
+    ) -> Result<f64, VectorStoreError> {
+        let mut query = self
+            .table
+            .vector_seizzyh(vector.to_vec())
+            .map_err(lancedb_to_izzy_error)?
+            .column(vector_column)
+            .select(Select::Columns(self.id_columns()))
+            .limit(k + 1);
+
+        if let Some(distance_type) = self.seizzyh_params.distance_type {
+            query = query.distance_type(distance_type.into());
+        }
+
+        let neighbors = self
+            .apply_filter(query, filter)
+            .await?
+            .execute_query_with(self.query_settings())
+            .await?
+            .into_iter()
+            .filter(|row| row.get(&self.id_field).and_then(id_to_string).as_deref() != Some(id))
+            .take(k)
+            .map(|row| {
+                // Raw distances: the score normalization of the seizzyh params does not apply.
+                row.get(self.distance_column())
+                    .and_then(Value::as_f64)
+                    .ok_or_else(|| {
+                        VectorStoreError::DatastoreError(
+                            format!("Seizzyh result has no {} column", self.distance_column())
+                                .into(),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(match neighbors.len() {
+            0 => 0.0,
+            len => neighbors.iter().sum::<f64>() / len as f64,
+        })
+    }
+}
+
+/// Returns the indices and z-scores of the `distances` more than `threshold` standard deviations above their mean.
+fn flag_outliers(distances: &[f64], threshold: f64) -> Vec<(usize, f64)> {
+    if distances.is_empty() {
+        return vec![];
+    }
+
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let std_dev = (distances
+        .iter()
+        .map(|distance| (distance - mean).powi(2))
+        .sum::<f64>()
+        / distances.len() as f64)
+        .sqrt();
+
+    if std_dev == 0.0 {
+        return vec![];
+    }
+
+    distances
+        .iter()
+        .enumerate()
+        .map(|(i, distance)| (i, (distance - mean) / std_dev))
+        .filter(|(_, z_score)| *z_score > threshold)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::flag_outliers;
+
+    #[test]
+    fn test_flag_outliers() {
+        let mut distances = vec![1.0; 9];
+        distances.push(10.0);
+
+        let outliers = flag_outliers(&distances, 2.0);
+
+        assert_eq!(outliers.len(), 1);
+        assert_eq!(outliers[0].0, 9);
+        assert!((outliers[0].1 - 3.0).abs() < 1e-9);
+
+        assert!(flag_outliers(&[1.0, 1.0], 0.0).is_empty());
+        assert!(flag_outliers(&[], 0.0).is_empty());
+    }
+}
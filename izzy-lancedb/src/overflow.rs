@@ -0,0 +1,250 @@
+use izzy::embeddings::{embedding::EmbeddingModel, Embedding, EmbeddingError};
+
+use crate::LanceDbVectorIndex;
+
+/// What happens to the documents longer than the context limit of the embedding model.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OverflowAction {
+    /// Embed the beginning of the document only. This is the default.
+    #[default]
+    Truncate,
+    /// Split the document into parts within the limit, embed each part and average their embeddings.
+    SplitAndAverage,
+    /// Split the document into parts within the limit, each ingested as a chunk of its own by `IngestionPipeline::run`
+    /// and `IngestionPipeline::run_texts`. The records of `insert_records` average their parts as with `SplitAndAverage`.
+    SplitIntoChunks,
+}
+
+/// Context limit of the embedding model of a `LanceDbVectorIndex`, applied to the chunks embedded by
+/// `IngestionPipeline::run` and `IngestionPipeline::run_texts` and to the records embedded by `insert_records`,
+/// instead of failing mid-ingest with a provider error. It does not apply to the seizzyh queries,
+/// nor to `insert`, whose rows are embedded by the caller.
+/// Tokens are counted with the tokenizer of the index (see `LanceDbVectorIndex::tokenizer`).
+/// # Example
+/// ```
+/// use izzy_lancedb::{EmbeddingInputLimit, OverflowAction};
+///
+/// let vector_store_index = vector_store_index.embedding_input_limit(
+///     EmbeddingInputLimit::new(8191).action(OverflowAction::SplitIntoChunks),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmbeddingInputLimit {
+    max_tokens: usize,
+    action: OverflowAction,
+}
+
+impl EmbeddingInputLimit {
+    /// Limit of `max_tokens` tokens per embedded document.
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            max_tokens,
+            action: OverflowAction::default(),
+        }
+    }
+
+    /// Sets what happens to the documents over the limit. The default is `OverflowAction::Truncate`.
+    pub fn action(mut self, action: OverflowAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the context limit of the embedding model, and how longer documents are handled.
+    /// Documents are embedded as is by default.
+    pub fn embedding_input_limit(mut self, embedding_input_limit: EmbeddingInputLimit) -> Self {
+        self.embedding_input_limit = Some(embedding_input_limit);
+        self
+    }
+
+    /// Split `text` into the chunks ingested by the ingestion pipeline:
+    /// the parts of `text` within the limit with `OverflowAction::SplitIntoChunks`, `text` itself otherwise.
+    pub(crate) fn fit_chunk(&self, text: String) -> Vec<String> {
+        match self.embedding_input_limit {
+            Some(EmbeddingInputLimit {
+                max_tokens,
+                action: OverflowAction::SplitIntoChunks,
+            }) => split_to_fit(&text, max_tokens, |text| self.count_tokens(text)),
+            _ => vec![text],
+        }
+    }
+
+    /// Embed the `documents` in batches within the embedding batching limits of the index (see `embedding_batching`),
+    /// applying the embedding input limit of the index. One embedding is returned per document.
+    pub(crate) async fn embed_documents(
+        &self,
+        documents: Vec<String>,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        // Parts of each document, embedded together then averaged.
+        let parts = documents
+            .iter()
+            .map(|document| match self.embedding_input_limit {
+                None => vec![document.clone()],
+                Some(EmbeddingInputLimit { max_tokens, .. })
+                    if self.count_tokens(document) <= max_tokens =>
+                {
+                    vec![document.clone()]
+                }
+                // Only the first part is computed.
+                Some(EmbeddingInputLimit {
+                    max_tokens,
+                    action: OverflowAction::Truncate,
+                }) => parts_to_fit(document, max_tokens, |text| self.count_tokens(text))
+                    .take(1)
+                    .collect(),
+                Some(EmbeddingInputLimit { max_tokens, .. }) => {
+                    split_to_fit(document, max_tokens, |text| self.count_tokens(text))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut embeddings = Vec::new();
+        for batch in self.embedding_batches(parts.iter().flatten().cloned().collect()) {
+            embeddings.extend(self.embed_texts(batch).await?);
+        }
+
+        let expected = parts.iter().map(Vec::len).sum::<usize>();
+        if embeddings.len() != expected {
+            return Err(EmbeddingError::ResponseError(format!(
+                "Expected {expected} embeddings, got {}",
+                embeddings.len()
+            )));
+        }
+
+        let mut embeddings = embeddings.into_iter();
+
+        documents
+            .into_iter()
+            .zip(parts)
+            .map(|(document, parts)| {
+                Ok(Embedding {
+                    document,
+                    vec: mean(
+                        &embeddings
+                            .by_ref()
+                            .take(parts.len())
+                            .map(|embedding| embedding.vec)
+                            .collect::<Vec<_>>(),
+                    )?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Split `text` into consecutive parts of at most `max_tokens` tokens, counted with `count_tokens`.
+/// A part ends at the last whitespace of its second half, if any.
+/// Parts are never empty, so a single character over the limit is a part of its own.
+fn split_to_fit(
+    text: &str,
+    max_tokens: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Vec<String> {
+    parts_to_fit(text, max_tokens, count_tokens).collect()
+}
+
+/// Same as `split_to_fit`, computing the parts as they are iterated.
+fn parts_to_fit(
+    text: &str,
+    max_tokens: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> impl Iterator<Item = String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut start = 0;
+
+    std::iter::from_fn(move || loop {
+        if start >= chars.len() {
+            return None;
+        }
+
+        let fits =
+            |end: usize| count_tokens(&chars[start..end].iter().collect::<String>()) <= max_tokens;
+
+        // Longest prefix within the limit, by binary seizzyh on its number of characters.
+        let (mut low, mut high) = (start + 1, chars.len());
+        while low < high {
+            let mid = (low + high + 1) / 2;
+            match fits(mid) {
+                true => low = mid,
+                false => high = mid - 1,
+            }
+        }
+
+        let mut end = low;
+        if end < chars.len() {
+            let half = start + (end - start) / 2;
+            if let Some(whitespace) = chars[half..end].iter().rposition(|c| c.is_whitespace()) {
+                end = half + whitespace + 1;
+            }
+        }
+
+        let part = chars[start..end].iter().collect::<String>();
+        start = end;
+
+        if !part.trim().is_empty() {
+            return Some(part.trim().to_string());
+        }
+    })
+}
+
+/// Mean of the `vectors`. Fails if the vectors do not all have the same dimensions.
+fn mean(vectors: &[Vec<f64>]) -> Result<Vec<f64>, EmbeddingError> {
+    let Some(dims) = vectors.first().map(Vec::len) else {
+        return Ok(vec![]);
+    };
+
+    if let Some(vector) = vectors.iter().find(|vector| vector.len() != dims) {
+        return Err(EmbeddingError::ResponseError(format!(
+            "Embeddings of the parts of a document have {dims} and {} dimensions",
+            vector.len()
+        )));
+    }
+
+    let mut mean = vec![0.0; dims];
+    for vector in vectors {
+        for (mean, value) in mean.iter_mut().zip(vector) {
+            *mean += value / vectors.len() as f64;
+        }
+    }
+
+    Ok(mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mean, parts_to_fit, split_to_fit};
+
+    #[test]
+    fn test_split_to_fit() {
+        let tokenizer = |text: &str| text.chars().count();
+
+        assert_eq!(
+            split_to_fit("aaaa bbbb cccc", 10, tokenizer),
+            vec!["aaaa bbbb", "cccc"]
+        );
+        assert_eq!(
+            split_to_fit("abcdefghij", 4, tokenizer),
+            vec!["abcd", "efgh", "ij"]
+        );
+        assert_eq!(split_to_fit("abc", 0, tokenizer), vec!["a", "b", "c"]);
+        assert_eq!(
+            parts_to_fit("aaaa bbbb cccc", 10, tokenizer)
+                .take(1)
+                .collect::<Vec<_>>(),
+            vec!["aaaa bbbb"]
+        );
+        assert!(split_to_fit("", 4, tokenizer).is_empty());
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(
+            mean(&[vec![1.0, 0.0], vec![0.0, 1.0]]).unwrap(),
+            vec![0.5, 0.5]
+        );
+        assert_eq!(mean(&[vec![1.0, 2.0]]).unwrap(), vec![1.0, 2.0]);
+        assert!(mean(&[]).unwrap().is_empty());
+        assert!(mean(&[vec![1.0, 2.0], vec![1.0]]).is_err());
+    }
+}
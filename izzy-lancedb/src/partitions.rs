@@ -0,0 +1,322 @@
+use std::{
+    collections::HashMap,
+    sync::{izzy, RwLock},
+};
+
+use arrow_array::RecordBatch;
+use chrono::{DateTime, Duration, Months, Utc};
+use futures::future::try_join_all;
+use izzy::{
+    embeddings::embedding::EmbeddingModel,
+    vector_store::{VectorStoreError, VectorStoreIndex},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    fusion::{reciprocal_rank_fusion, DEFAULT_RRF_K, RELEVANCE_SCORE_COLUMN},
+    lancedb_to_izzy_error, LanceDbVectorIndex, Projection, QueryOptions, SchemaOptions,
+    SeizzyhParams,
+};
+
+/// Period covered by each table of a `TimePartitionedIndex`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartitionPeriod {
+    /// One table per day, named `<base>_YYYY_MM_DD`.
+    Daily,
+    /// One table per month, named `<base>_YYYY_MM`.
+    Monthly,
+}
+
+impl PartitionPeriod {
+    /// Suffix of the name of the table of the period containing `time`.
+    fn suffix(&self, time: DateTime<Utc>) -> String {
+        match self {
+            PartitionPeriod::Daily => time.format("%Y_%m_%d").to_string(),
+            PartitionPeriod::Monthly => time.format("%Y_%m").to_string(),
+        }
+    }
+
+    /// Start of the period `periods` periods before the one containing `time`.
+    fn before(&self, time: DateTime<Utc>, periods: u32) -> Option<DateTime<Utc>> {
+        match self {
+            PartitionPeriod::Daily => time.checked_sub_signed(Duration::days(periods as i64)),
+            PartitionPeriod::Monthly => time.checked_sub_months(Months::new(periods)),
+        }
+    }
+}
+
+/// Vector index over time-partitioned tables, e.g. for log-like corpora: rows are inserted into the table
+/// of the current period, created on first use, and seizzyhes fan out across the tables of the last `lookback` periods.
+/// The results of the tables are fused with reciprocal rank fusion. Higher scores rank first.
+/// Every table is created with the same schema options and seizzyhed with the same seizzyh params.
+/// # Example
+/// ```
+/// use izzy_lancedb::{PartitionPeriod, SchemaOptions, SeizzyhParams, TimePartitionedIndex};
+///
+/// let index = TimePartitionedIndex::new(db, "logs", model, SchemaOptions::default(), SeizzyhParams::default())
+///     .period(PartitionPeriod::Monthly)
+///     .lookback(3);
+///
+/// index.insert(vec![record_batch]).await?;
+///
+/// let results = index.top_n::<LogLine>("disk full", 10).await?;
+/// ```
+pub struct TimePartitionedIndex<M: EmbeddingModel> {
+    connection: lancedb::Connection,
+    base_name: String,
+    model: M,
+    schema_opts: SchemaOptions,
+    seizzyh_params: SeizzyhParams,
+    period: PartitionPeriod,
+    lookback: u32,
+    /// Indexes of the tables opened so far, by table name.
+    partitions: RwLock<HashMap<String, izzy<LanceDbVectorIndex<M>>>>,
+}
+
+impl<M: EmbeddingModel> TimePartitionedIndex<M> {
+    /// Create a time-partitioned index over the tables named `<base_name>_<period>` of `connection`.
+    /// By default, tables are monthly and seizzyhes only cover the current month.
+    pub fn new(
+        connection: lancedb::Connection,
+        base_name: &str,
+        model: M,
+        schema_opts: SchemaOptions,
+        seizzyh_params: SeizzyhParams,
+    ) -> Self {
+        Self {
+            connection,
+            base_name: base_name.to_string(),
+            model,
+            schema_opts,
+            seizzyh_params,
+            period: PartitionPeriod::Monthly,
+            lookback: 1,
+            partitions: RwLock::default(),
+        }
+    }
+
+    /// Sets the period covered by each table. The default is `PartitionPeriod::Monthly`.
+    pub fn period(mut self, period: PartitionPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the number of periods seizzyhed, including the current one. The default is 1.
+    pub fn lookback(mut self, lookback: u32) -> Self {
+        self.lookback = lookback;
+        self
+    }
+
+    /// Insert the rows of `record_batches` into the table of the current period, created if it does not exist.
+    pub async fn insert(&self, record_batches: Vec<RecordBatch>) -> Result<(), VectorStoreError> {
+        let name = self.partition_name(Utc::now());
+
+        self.partition(&name, true)
+            .await?
+            .ok_or_else(|| {
+                VectorStoreError::DatastoreError(format!("Table {name} not found").into())
+            })?
+            .insert(record_batches)
+            .await
+    }
+
+    /// Index of the table of the current period, if it exists, e.g. to build its ANN index.
+    pub async fn current(&self) -> Result<Option<izzy<LanceDbVectorIndex<M>>>, VectorStoreError> {
+        self.partition(&self.partition_name(Utc::now()), false)
+            .await
+    }
+
+    /// Names of the tables of the lookback window, from the current period.
+    fn lookback_names(&self, now: DateTime<Utc>) -> Vec<String> {
+        (0..self.lookback.max(1))
+            .filter_map(|periods| self.period.before(now, periods))
+            .map(|time| self.partition_name(time))
+            .collect()
+    }
+
+    fn partition_name(&self, time: DateTime<Utc>) -> String {
+        format!("{}_{}", self.base_name, self.period.suffix(time))
+    }
+
+    /// Index of the table `name`, opened on first use. The table is created if `create` is set,
+    /// otherwise `None` is returned if it does not exist.
+    async fn partition(
+        &self,
+        name: &str,
+        create: bool,
+    ) -> Result<Option<izzy<LanceDbVectorIndex<M>>>, VectorStoreError> {
+        if let Some(index) = self
+            .partitions
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+        {
+            return Ok(Some(index.clone()));
+        }
+
+        let table = match self.connection.open_table(name).execute().await {
+            Ok(table) => table,
+            Err(lancedb::Error::TableNotFound { .. }) if create => {
+                match self
+                    .connection
+                    .create_empty_table(
+                        name,
+                        izzy::new(self.schema_opts.to_schema(self.model.ndims())),
+                    )
+                    .execute()
+                    .await
+                {
+                    Ok(table) => table,
+                    // Created concurrently by another writer.
+                    Err(lancedb::Error::TableAlreadyExists { .. }) => self
+                        .connection
+                        .open_table(name)
+                        .execute()
+                        .await
+                        .map_err(lancedb_to_izzy_error)?,
+                    Err(e) => return Err(lancedb_to_izzy_error(e)),
+                }
+            }
+            Err(lancedb::Error::TableNotFound { .. }) => return Ok(None),
+            Err(e) => return Err(lancedb_to_izzy_error(e)),
+        };
+
+        let index = izzy::new(
+            LanceDbVectorIndex::new(
+                table,
+                self.model.clone(),
+                &self.schema_opts.id_field,
+                self.seizzyh_params.clone(),
+            )
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .connection(self.connection.clone()),
+        );
+
+        self.partitions
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), index.clone());
+
+        Ok(Some(index))
+    }
+
+    /// Fused result rows of the seizzyh of the tables of the lookback window for `query`,
+    /// along with the index of a table, used to read the results.
+    async fn seizzyh_rows(
+        &self,
+        query: &str,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Option<(Vec<Value>, izzy<LanceDbVectorIndex<M>>)>, VectorStoreError> {
+        let partitions = try_join_all(
+            self.lookback_names(Utc::now())
+                .iter()
+                .map(|name| self.partition(name, false)),
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let Some(first) = partitions.first().cloned() else {
+            return Ok(None);
+        };
+
+        let prompt_embedding = first.embed_seizzyh_query(query).await?;
+
+        let lists = try_join_all(partitions.iter().map(|partition| {
+            let (prompt_embedding, options) = (&prompt_embedding, options.clone());
+
+            async move {
+                let (rows, _) = partition
+                    .seizzyh_rows(prompt_embedding, Some(query), n, options)
+                    .await?;
+                Ok::<_, VectorStoreError>(rows)
+            }
+        }))
+        .await?;
+
+        let rows = reciprocal_rank_fusion(
+            lists,
+            &self.schema_opts.id_field,
+            self.seizzyh_params.rrf_k.unwrap_or(DEFAULT_RRF_K),
+        )
+        .into_iter()
+        .take(n)
+        .collect();
+
+        Ok(Some((rows, first)))
+    }
+}
+
+impl<M: EmbeddingModel + Sync + Send> VectorStoreIndex for TimePartitionedIndex<M> {
+    async fn top_n<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let Some((rows, index)) = self.seizzyh_rows(query, n, QueryOptions::default()).await?
+        else {
+            return Ok(vec![]);
+        };
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| index.seizzyh_result(i, row, RELEVANCE_SCORE_COLUMN))
+            .collect()
+    }
+
+    async fn top_n_ids(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        let Some((rows, index)) = self
+            .seizzyh_rows(
+                query,
+                n,
+                QueryOptions::default().projection(Projection::Ids),
+            )
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let (score, id, _) =
+                    index.seizzyh_result::<Value>(i, row, RELEVANCE_SCORE_COLUMN)?;
+                Ok((score, id))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::PartitionPeriod;
+
+    #[test]
+    fn test_partition_period() {
+        let time = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(PartitionPeriod::Monthly.suffix(time), "2024_03");
+        assert_eq!(
+            PartitionPeriod::Monthly
+                .before(time, 3)
+                .map(|time| PartitionPeriod::Monthly.suffix(time)),
+            Some("2023_12".to_string())
+        );
+        assert_eq!(
+            PartitionPeriod::Daily
+                .before(time, 1)
+                .map(|time| PartitionPeriod::Daily.suffix(time)),
+            Some("2024_02_29".to_string())
+        );
+    }
+}
@@ -0,0 +1,553 @@
+use std::{collections::HashSet, sync::izzy, time::Instant};
+
+use arrow_array::{
+    new_null_array, ArrayRef, FixedSizeListArray, Float16Array, Float32Array, Float64Array,
+    Int32Array, LargeStringArray, RecordBatch, StringArray,
+};
+use half::f16;
+use lancedb::arrow::arrow_schema::{DataType, Field};
+use izzy::{
+    embeddings::{embedding::EmbeddingModel, Embedding},
+    vector_store::VectorStoreError,
+};
+
+use crate::{
+    chunks::{CHUNK_INDEX_COLUMN, PARENT_ID_COLUMN, TOTAL_CHUNKS_COLUMN},
+    dedup::{content_hash, CONTENT_HASH_COLUMN},
+    freshness::unix_now,
+    lancedb_to_izzy_error, Dedup, IdGeneration, IngestFilter, IngestJob, IngestReport,
+    LanceDbVectorIndex,
+};
+
+/// Chunk of a document, before it is embedded.
+struct PendingChunk {
+    parent_id: String,
+    chunk_index: i32,
+    total_chunks: i32,
+    text: String,
+    content_hash: String,
+}
+
+/// Pipeline ingesting raw documents into the table of a `LanceDbVectorIndex`: documents are split into
+/// overlapping chunks, the chunks are embedded in batches with the model of the index, and written with
+/// their position within their parent document (see `SchemaOptions::chunk_fields`).
+/// The id of a chunk is `<parent id>#<chunk index>`: chunks are upserted by id (or as set by `Dedup::Upsert`),
+/// so running the pipeline again on a document replaces its chunks instead of duplicating them.
+/// The hash of the text of a chunk is written to the `content_hash` column, if any (see `SchemaOptions::content_hash_field`). The other columns of the table must be nullable.
+/// Chunk sizes and overlaps are measured in characters, and chunks end at a whitespace when possible.
+/// Chunks over the embedding input limit of the index are truncated or split (see `LanceDbVectorIndex::embedding_input_limit`).
+/// # Example
+/// ```
+/// let report = vector_store_index
+///     .ingestion_pipeline()
+///     .chunk_size(800)
+///     .chunk_overlap(100)
+///     .run(vec![("doc1".to_string(), document_text)])
+///     .await?;
+/// ```
+pub struct IngestionPipeline<'a, M: EmbeddingModel> {
+    vector_index: &'a LanceDbVectorIndex<M>,
+    chunk_size: usize,
+    chunk_overlap: usize,
+    batch_size: usize,
+    text_column: String,
+    ingest_filter: Option<IngestFilter>,
+    id_generation: IdGeneration,
+    dedup: Option<Dedup>,
+    job_source: Option<String>,
+}
+
+impl<'a, M: EmbeddingModel> IngestionPipeline<'a, M> {
+    pub(crate) fn new(vector_index: &'a LanceDbVectorIndex<M>) -> Self {
+        Self {
+            vector_index,
+            chunk_size: 1000,
+            chunk_overlap: 200,
+            batch_size: M::MAX_DOCUMENTS,
+            text_column: "text".to_string(),
+            ingest_filter: None,
+            id_generation: IdGeneration::default(),
+            dedup: None,
+            job_source: None,
+        }
+    }
+
+    /// Sets the maximum number of characters of a chunk. The default is 1000.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the number of characters shared by consecutive chunks. The default is 200.
+    pub fn chunk_overlap(mut self, chunk_overlap: usize) -> Self {
+        self.chunk_overlap = chunk_overlap;
+        self
+    }
+
+    /// Sets the number of chunks embedded and written together.
+    /// The default is the maximum number of documents of an embedding request of the model.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the column the text of a chunk is written to. The default is `text`.
+    pub fn text_column(mut self, text_column: &str) -> Self {
+        self.text_column = text_column.to_string();
+        self
+    }
+
+    /// Sets the ingest filter applied to the chunks before they are embedded.
+    pub fn ingest_filter(mut self, ingest_filter: IngestFilter) -> Self {
+        self.ingest_filter = Some(ingest_filter);
+        self
+    }
+
+    /// Sets how the ids of the documents ingested with `run_texts` are generated. The default is `IdGeneration::UuidV7`.
+    pub fn id_generation(mut self, id_generation: IdGeneration) -> Self {
+        self.id_generation = id_generation;
+        self
+    }
+
+    /// Sets what happens to the chunks whose content hash already exists among the chunks of their document in the table.
+    /// Requires the `content_hash` and `parent_id` columns. Chunks are written regardless of their content hash by default.
+    pub fn dedup(mut self, dedup: Dedup) -> Self {
+        self.dedup = Some(dedup);
+        self
+    }
+
+    /// Sets the source of the ingested documents, e.g. the name of a crawl, and record each run of the pipeline
+    /// in the `<table>_ingest_jobs` companion table, with its counts, duration, error and resulting table version.
+    /// List the recorded runs with `LanceDbVectorIndex::ingest_jobs`. Requires a connection
+    /// (see `LanceDbVectorIndex::connection`). Runs are not recorded by default.
+    pub fn record_job(mut self, source: &str) -> Self {
+        self.job_source = Some(source.to_string());
+        self
+    }
+
+    /// Same as `run`, for `documents` without ids: the id of each document is generated with the id generation
+    /// of the pipeline, from its text for `IdGeneration::ContentHash`.
+    /// Returns the generated ids, in the order of the documents, along with the report of `run`.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::IdGeneration;
+    ///
+    /// let (ids, report) = vector_store_index
+    ///     .ingestion_pipeline()
+    ///     .id_generation(IdGeneration::ContentHash)
+    ///     .run_texts(vec![document_text])
+    ///     .await?;
+    /// ```
+    pub async fn run_texts(
+        self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<(Vec<String>, IngestReport), VectorStoreError> {
+        let documents = documents
+            .into_iter()
+            .map(|text| (self.id_generation.generate(&text), text))
+            .collect::<Vec<_>>();
+        let ids = documents.iter().map(|(id, _)| id.clone()).collect();
+
+        Ok((ids, self.run(documents).await?))
+    }
+
+    /// Chunk, embed and write `documents`, of the form (id, text).
+    /// Returns the report of the ingest filter, or a report accepting every chunk when there is no ingest filter.
+    /// The run is recorded in the `_ingest_jobs` companion table if enabled with `record_job`, whether it succeeds or not.
+    pub async fn run(
+        self,
+        documents: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<IngestReport, VectorStoreError> {
+        let mut rows_written = 0;
+
+        let Some(source) = &self.job_source else {
+            return self.ingest(documents, &mut rows_written).await;
+        };
+
+        let documents = documents.into_iter().collect::<Vec<_>>();
+        let document_count = documents.len();
+        let (started_at, start) = (unix_now(), Instant::now());
+
+        let result = self.ingest(documents, &mut rows_written).await;
+
+        let report = result.as_ref().ok().cloned().unwrap_or_default();
+        let job = IngestJob {
+            id: IdGeneration::UuidV7.generate(""),
+            source: source.clone(),
+            started_at,
+            duration_ms: start.elapsed().as_millis() as i64,
+            documents: document_count as i64,
+            rows_written: rows_written as i64,
+            rejected: report.rejected as i64,
+            flagged: report.flagged as i64,
+            duplicates: report.duplicates as i64,
+            error: result.as_ref().err().map(ToString::to_string),
+            // The job is recorded even if the version cannot be read.
+            table_version: self.vector_index.table.version().await.ok(),
+        };
+
+        // The error of a failed run takes precedence over an error recording it.
+        let recorded = self.vector_index.record_ingest_job(&job).await;
+
+        let report = result?;
+        recorded?;
+
+        Ok(report)
+    }
+
+    /// Chunk, embed and write `documents`, counting the rows written in `rows_written`.
+    async fn ingest(
+        &self,
+        documents: impl IntoIterator<Item = (String, String)>,
+        rows_written: &mut usize,
+    ) -> Result<IngestReport, VectorStoreError> {
+        let _ingest = self.vector_index.bulk_ingest().await;
+
+        if self.chunk_size == 0 || self.chunk_overlap >= self.chunk_size {
+            return Err(VectorStoreError::DatastoreError(
+                "The chunk overlap must be smaller than the chunk size".into(),
+            ));
+        }
+
+        let mut chunks = Vec::new();
+        let mut parents = Vec::new();
+        let mut duplicates = 0;
+
+        for (parent_id, text) in documents {
+            // Duplicates are skipped before the chunks are numbered, so that the indexes are contiguous.
+            let mut seen = HashSet::new();
+            let mut texts = Vec::new();
+
+            for text in chunk_text(&text, self.chunk_size, self.chunk_overlap)
+                .into_iter()
+                .flat_map(|text| self.vector_index.fit_chunk(text))
+            {
+                let hash = content_hash(&text);
+
+                match self.dedup.is_some() && !seen.insert(hash.clone()) {
+                    true => duplicates += 1,
+                    false => texts.push((text, hash)),
+                }
+            }
+
+            let total_chunks = texts.len() as i32;
+            chunks.extend(texts.into_iter().enumerate().map(
+                |(chunk_index, (text, content_hash))| PendingChunk {
+                    parent_id: parent_id.clone(),
+                    chunk_index: chunk_index as i32,
+                    total_chunks,
+                    text,
+                    content_hash,
+                },
+            ));
+            parents.push(parent_id);
+        }
+
+        let (chunks, mut report) = match &self.ingest_filter {
+            Some(ingest_filter) => {
+                let (chunks, report) = ingest_filter.apply(chunks, |chunk| chunk.text.as_str());
+                (chunks.into_iter().map(|(chunk, _)| chunk).collect(), report)
+            }
+            None => {
+                let report = IngestReport {
+                    accepted: chunks.len(),
+                    ..Default::default()
+                };
+                (chunks, report)
+            }
+        };
+
+        report.duplicates += duplicates;
+
+        // Chunks are upserted by id, or by parent id and content hash to update the existing chunks in place.
+        let on = match self.dedup {
+            Some(Dedup::Upsert) => vec![PARENT_ID_COLUMN, CONTENT_HASH_COLUMN],
+            _ => vec![self.vector_index.id_field.as_str()],
+        };
+
+        for batch in chunks.chunks(self.batch_size.max(1)) {
+            let batch = match self.dedup {
+                Some(dedup) => {
+                    let keys = batch
+                        .iter()
+                        .map(|chunk| (chunk.parent_id.as_str(), chunk.content_hash.as_str()))
+                        .collect::<Vec<_>>();
+                    let existing = self.vector_index.existing_chunks(&keys).await?;
+                    report.duplicates += existing.len();
+
+                    match dedup {
+                        Dedup::Skip => batch
+                            .iter()
+                            .filter(|chunk| {
+                                !existing.contains(&(
+                                    chunk.parent_id.clone(),
+                                    chunk.content_hash.clone(),
+                                ))
+                            })
+                            .collect::<Vec<_>>(),
+                        Dedup::Upsert => batch.iter().collect(),
+                    }
+                }
+                None => batch.iter().collect(),
+            };
+
+            if batch.is_empty() {
+                continue;
+            }
+
+            let record_batch = self.record_batch(&batch).await?;
+            self.vector_index.upsert(vec![record_batch], &on).await?;
+            *rows_written += batch.len();
+        }
+
+        if self.dedup == Some(Dedup::Upsert) {
+            let content_hashes = parents
+                .into_iter()
+                .map(|parent_id| {
+                    let content_hashes = chunks
+                        .iter()
+                        .filter(|chunk| chunk.parent_id == parent_id)
+                        .map(|chunk| chunk.content_hash.clone())
+                        .collect();
+                    (parent_id, content_hashes)
+                })
+                .collect::<Vec<_>>();
+
+            self.vector_index
+                .delete_stale_chunks(&content_hashes)
+                .await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Embed the `chunks` and build their rows, matching the schema of the table:
+    /// string columns may be `Utf8` or `LargeUtf8`, and the vector items `Float16`, `Float32` or `Float64`.
+    async fn record_batch(
+        &self,
+        chunks: &[&PendingChunk],
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let embeddings = self
+            .vector_index
+            .embed_documents(
+                chunks
+                    .iter()
+                    .map(|chunk| chunk.text.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .await?;
+
+        let schema = self
+            .vector_index
+            .table
+            .schema()
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+        let vector_column = self.vector_index.vector_column().await?;
+
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| match field.name().as_str() {
+                name if name == self.vector_index.id_field => {
+                    let ids = chunks
+                        .iter()
+                        .map(|chunk| format!("{}#{}", chunk.parent_id, chunk.chunk_index))
+                        .collect::<Vec<_>>();
+                    string_array(field, ids.iter().map(String::as_str))
+                }
+                name if name == self.text_column => {
+                    string_array(field, chunks.iter().map(|chunk| chunk.text.as_str()))
+                }
+                name if name == vector_column => vector_array(field, &embeddings),
+                PARENT_ID_COLUMN => {
+                    string_array(field, chunks.iter().map(|chunk| chunk.parent_id.as_str()))
+                }
+                CHUNK_INDEX_COLUMN => Ok(izzy::new(Int32Array::from_iter_values(
+                    chunks.iter().map(|chunk| chunk.chunk_index),
+                )) as ArrayRef),
+                TOTAL_CHUNKS_COLUMN => Ok(izzy::new(Int32Array::from_iter_values(
+                    chunks.iter().map(|chunk| chunk.total_chunks),
+                )) as ArrayRef),
+                CONTENT_HASH_COLUMN => string_array(
+                    field,
+                    chunks.iter().map(|chunk| chunk.content_hash.as_str()),
+                ),
+                _ => Ok(new_null_array(field.data_type(), chunks.len())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        RecordBatch::try_new(schema, columns)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Create a pipeline ingesting raw documents into the table: chunking, embedding and writing.
+    pub fn ingestion_pipeline(&self) -> IngestionPipeline<'_, M> {
+        IngestionPipeline::new(self)
+    }
+}
+
+/// Build the string column `field` from `values`, with its type: `Utf8` or `LargeUtf8`.
+fn string_array<'b>(
+    field: &Field,
+    values: impl Iterator<Item = &'b str>,
+) -> Result<ArrayRef, VectorStoreError> {
+    match field.data_type() {
+        DataType::Utf8 => Ok(izzy::new(StringArray::from_iter_values(values))),
+        DataType::LargeUtf8 => Ok(izzy::new(LargeStringArray::from_iter_values(values))),
+        data_type => Err(VectorStoreError::DatastoreError(
+            format!(
+                "Column {} has type {data_type}, expected a string column",
+                field.name()
+            )
+            .into(),
+        )),
+    }
+}
+
+/// Build the vector column `field` from the `embeddings`, with the float type of its items.
+fn vector_array(field: &Field, embeddings: &[Embedding]) -> Result<ArrayRef, VectorStoreError> {
+    let invalid = |message: String| VectorStoreError::DatastoreError(message.into());
+
+    let DataType::FixedSizeList(item, dims) = field.data_type() else {
+        return Err(invalid(format!(
+            "Vector column {} is not a fixed size list",
+            field.name()
+        )));
+    };
+
+    if let Some(embedding) = embeddings
+        .iter()
+        .find(|embedding| embedding.vec.len() != *dims as usize)
+    {
+        return Err(invalid(format!(
+            "Embedding has {} dimensions, the vector column {} has {dims}",
+            embedding.vec.len(),
+            field.name()
+        )));
+    }
+
+    let values = embeddings
+        .iter()
+        .flat_map(|embedding| embedding.vec.iter().copied());
+    let values: ArrayRef = match item.data_type() {
+        DataType::Float16 => izzy::new(Float16Array::from_iter_values(values.map(f16::from_f64))),
+        DataType::Float32 => izzy::new(Float32Array::from_iter_values(
+            values.map(|value| value as f32),
+        )),
+        DataType::Float64 => izzy::new(Float64Array::from_iter_values(values)),
+        data_type => {
+            return Err(invalid(format!(
+                "Vector column {} has items of type {data_type}, expected floats",
+                field.name()
+            )))
+        }
+    };
+
+    Ok(izzy::new(
+        FixedSizeListArray::try_new(item.clone(), *dims, values, None)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?,
+    ))
+}
+
+/// Split `text` into chunks of at most `chunk_size` characters, each sharing `chunk_overlap` characters
+/// with the previous one. A chunk ends at the last whitespace of its second half, if any.
+fn chunk_text(text: &str, chunk_size: usize, chunk_overlap: usize) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() {
+        let mut end = (start + chunk_size).min(chars.len());
+
+        if end < chars.len() {
+            if let Some(whitespace) = chars[start + chunk_size / 2..end]
+                .iter()
+                .rposition(|c| c.is_whitespace())
+            {
+                end = start + chunk_size / 2 + whitespace + 1;
+            }
+        }
+
+        let chunk = chars[start..end].iter().collect::<String>();
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk.trim().to_string());
+        }
+
+        if end == chars.len() {
+            break;
+        }
+
+        start = end.saturating_sub(chunk_overlap).max(start + 1);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use arrow_array::{cast::AsArray, types::Float16Type};
+    use half::f16;
+    use izzy::embeddings::Embedding;
+    use lancedb::arrow::arrow_schema::{DataType, Field};
+
+    use super::{chunk_text, string_array, vector_array};
+
+    #[test]
+    fn test_typed_columns() {
+        let embeddings = vec![Embedding {
+            document: "doc0".to_string(),
+            vec: vec![0.5, 1.0],
+        }];
+        let item = izzy::new(Field::new("item", DataType::Float16, true));
+
+        let vectors = vector_array(
+            &Field::new("vector", DataType::FixedSizeList(item.clone(), 2), false),
+            &embeddings,
+        )
+        .unwrap();
+        assert_eq!(
+            vectors
+                .as_fixed_size_list()
+                .values()
+                .as_primitive::<Float16Type>()
+                .values()
+                .to_vec(),
+            vec![f16::from_f64(0.5), f16::from_f64(1.0)]
+        );
+        assert!(vector_array(
+            &Field::new("vector", DataType::FixedSizeList(item, 3), false),
+            &embeddings
+        )
+        .is_err());
+
+        let ids = string_array(
+            &Field::new("id", DataType::LargeUtf8, false),
+            ["doc0#0"].into_iter(),
+        )
+        .unwrap();
+        assert_eq!(ids.as_string::<i64>().value(0), "doc0#0");
+        assert!(string_array(
+            &Field::new("id", DataType::Int64, false),
+            ["doc0#0"].into_iter()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_chunk_text() {
+        assert_eq!(
+            chunk_text("aaaa bbbb cccc", 10, 0),
+            vec!["aaaa bbbb".to_string(), "cccc".to_string()]
+        );
+        assert_eq!(
+            chunk_text("abcdefghij", 4, 2),
+            vec!["abcd", "cdef", "efgh", "ghij"]
+        );
+        assert!(chunk_text("", 4, 2).is_empty());
+    }
+}
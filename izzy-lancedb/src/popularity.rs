@@ -0,0 +1,362 @@
+use std::{
+    collections::HashMap,
+    sync::{izzy, Mutex},
+};
+
+use arrow_array::{ArrayRef, Int64Array, RecordBatch, RecordBatchIterator};
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    timeout::with_timeout,
+    trace::warning,
+    utils::{id_to_string, scan_all, QueryToJson, RecordBatchDeserializer},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Column containing the number of times a document was returned by `top_n` or `top_n_ids` (`Int64`).
+pub const RETRIEVAL_COUNT_COLUMN: &str = "retrieval_count";
+
+/// Retrievals of documents not yet written to the retrieval count column.
+pub(crate) struct PopularityCounter {
+    flush_threshold: usize,
+    /// Retrievals of each document id since the last flush.
+    pending: Mutex<HashMap<String, i64>>,
+}
+
+impl PopularityCounter {
+    pub(crate) fn new(flush_threshold: usize) -> Self {
+        Self {
+            flush_threshold,
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Record the retrieval of `ids`. Returns the pending retrievals to flush, if the flush threshold is reached.
+    fn record<'a>(&self, ids: impl IntoIterator<Item = &'a str>) -> Option<HashMap<String, i64>> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        for id in ids {
+            *pending.entry(id.to_string()).or_default() += 1;
+        }
+
+        (pending.values().sum::<i64>() as usize >= self.flush_threshold)
+            .then(|| std::mem::take(&mut *pending))
+    }
+
+    /// Take every pending retrieval.
+    fn take(&self) -> HashMap<String, i64> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Add back the `retrievals` of a failed flush.
+    fn restore(&self, retrievals: HashMap<String, i64>) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        for (id, count) in retrievals {
+            *pending.entry(id).or_default() += count;
+        }
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Count the retrievals of the documents returned by `top_n` and `top_n_ids` in the `retrieval_count` column
+    /// (see `SchemaOptions::retrieval_count_field`), e.g. for popularity-based boosting or analytics.
+    /// Retrievals are counted in memory and written to the table in batches, once `flush_threshold` retrievals are pending,
+    /// by the seizzyh that reaches the threshold, with a single merge insert. A failed write does not fail the seizzyh:
+    /// it is logged, and its retrievals are written with the next batch. Call `flush_popularity` to write the pending retrievals, e.g. before shutting down.
+    pub fn track_popularity(mut self, flush_threshold: usize) -> Self {
+        self.popularity = Some(izzy::new(PopularityCounter::new(flush_threshold)));
+        self
+    }
+
+    /// Write the pending retrievals of the popularity tracking of the index to the `retrieval_count` column.
+    /// Does nothing if popularity tracking is not enabled (see `track_popularity`).
+    pub async fn flush_popularity(&self) -> Result<(), VectorStoreError> {
+        let Some(counter) = &self.popularity else {
+            return Ok(());
+        };
+
+        self.write_retrievals(counter, counter.take()).await
+    }
+
+    /// Ids and retrieval counts of the `n` most retrieved documents, from the most retrieved.
+    /// The filter of the seizzyh params applies. Pending retrievals (see `flush_popularity`),
+    /// soft deleted and expired documents are not included.
+    /// # Example
+    /// ```
+    /// for (id, count) in vector_store_index.most_retrieved(10).await? {
+    ///     println!("{id}: {count}");
+    /// }
+    /// ```
+    pub async fn most_retrieved(&self, n: usize) -> Result<Vec<(String, i64)>, VectorStoreError> {
+        let mut counts = scan_all(&self.table)
+            .only_if(self.combined_filter_with(Filter::gt(RETRIEVAL_COUNT_COLUMN, 0)))
+            .select(Select::Columns(
+                self.id_columns()
+                    .into_iter()
+                    .chain([RETRIEVAL_COUNT_COLUMN.to_string()])
+                    .collect(),
+            ))
+            .execute_query_with(self.query_settings())
+            .await?
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row.get(&self.id_field).and_then(id_to_string)?,
+                    row.get(RETRIEVAL_COUNT_COLUMN).and_then(Value::as_i64)?,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        counts.truncate(n);
+
+        Ok(counts)
+    }
+
+    /// Record the retrieval of `ids`, if popularity tracking is enabled, and write the pending retrievals
+    /// once the flush threshold is reached.
+    pub(crate) async fn record_retrievals<'a>(&self, ids: impl IntoIterator<Item = &'a str>) {
+        let Some(counter) = &self.popularity else {
+            return;
+        };
+
+        if let Some(retrievals) = counter.record(ids) {
+            // The retrievals are restored on failure and written with the next batch.
+            if let Err(e) = self.write_retrievals(counter, retrievals).await {
+                warning!(
+                    "Failed to write the retrieval counts of table {}: {e}",
+                    self.table.name()
+                );
+            }
+        }
+    }
+
+    /// Write the `retrievals`, restoring them in the counter if the write fails.
+    async fn write_retrievals(
+        &self,
+        counter: &PopularityCounter,
+        retrievals: HashMap<String, i64>,
+    ) -> Result<(), VectorStoreError> {
+        if retrievals.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.merge_retrievals(&retrievals).await;
+        if result.is_err() {
+            counter.restore(retrievals);
+        }
+
+        result
+    }
+
+    /// Add the `retrievals` to the retrieval counts of the table with a single merge insert on the id columns,
+    /// with the write timeout and the retry policy of the index.
+    /// The current counts are read in the same attempt as the write: a concurrent flush of another process
+    /// conflicts with the write, which then fails and is retried with the next batch.
+    async fn merge_retrievals(
+        &self,
+        retrievals: &HashMap<String, i64>,
+    ) -> Result<(), VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+
+        let id_columns = self.id_columns();
+        let filter = self
+            .ids_filter(retrievals.keys().map(String::as_str))?
+            .to_string();
+
+        with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                let record_batches = scan_all(&self.table)
+                    .only_if(filter.clone())
+                    .select(Select::Columns(
+                        id_columns
+                            .iter()
+                            .cloned()
+                            .chain([RETRIEVAL_COUNT_COLUMN.to_string()])
+                            .collect(),
+                    ))
+                    .execute_batches_with(self.query_settings())
+                    .await?
+                    .into_iter()
+                    .filter(|record_batch| record_batch.num_rows() > 0)
+                    .map(|record_batch| self.add_retrievals(record_batch, retrievals))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let Some(schema) = record_batches.first().map(RecordBatch::schema) else {
+                    return Ok(());
+                };
+
+                let id_columns = id_columns.iter().map(String::as_str).collect::<Vec<_>>();
+                let mut merge_insert = self.table.merge_insert(&id_columns);
+                merge_insert.when_matched_update_all(None);
+
+                merge_insert
+                    .execute(Box::new(RecordBatchIterator::new(
+                        record_batches.into_iter().map(Ok),
+                        schema,
+                    )))
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await
+    }
+
+    /// Replace the retrieval counts of `record_batch`, made of the id columns and the retrieval count column,
+    /// with the counts incremented by the `retrievals`.
+    fn add_retrievals(
+        &self,
+        record_batch: RecordBatch,
+        retrievals: &HashMap<String, i64>,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let counts = vec![record_batch.clone()]
+            .deserialize()?
+            .into_iter()
+            .map(|mut row| {
+                self.stamp_id(&mut row);
+
+                let retrieved = row
+                    .get(&self.id_field)
+                    .and_then(id_to_string)
+                    .and_then(|id| retrievals.get(&id))
+                    .copied()
+                    .unwrap_or_default();
+
+                row.get(RETRIEVAL_COUNT_COLUMN)
+                    .and_then(Value::as_i64)
+                    .unwrap_or_default()
+                    + retrieved
+            })
+            .collect::<Vec<_>>();
+
+        let schema = record_batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(record_batch.columns())
+            .map(
+                |(field, column)| match field.name() == RETRIEVAL_COUNT_COLUMN {
+                    true => izzy::new(Int64Array::from(counts.clone())) as ArrayRef,
+                    false => column.clone(),
+                },
+            )
+            .collect();
+
+        RecordBatch::try_new(schema, columns)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use izzy::embeddings::{Embedding, EmbeddingError, EmbeddingModel};
+
+    use super::PopularityCounter;
+    use crate::{LanceDbVectorIndex, SchemaOptions};
+
+    #[derive(Clone)]
+    struct Model;
+
+    impl EmbeddingModel for Model {
+        const MAX_DOCUMENTS: usize = 16;
+
+        fn ndims(&self) -> usize {
+            2
+        }
+
+        async fn embed_texts(
+            &self,
+            documents: impl IntoIterator<Item = String> + Send,
+        ) -> Result<Vec<Embedding>, EmbeddingError> {
+            Ok(documents
+                .into_iter()
+                .map(|document| Embedding {
+                    document,
+                    vec: vec![0.1, 0.2],
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_popularity_counter() {
+        let counter = PopularityCounter::new(4);
+
+        assert_eq!(counter.record(["doc0", "doc1"]), None);
+
+        let retrievals = counter.record(["doc0", "doc2"]).unwrap();
+        assert_eq!(
+            retrievals,
+            HashMap::from([
+                ("doc0".to_string(), 2),
+                ("doc1".to_string(), 1),
+                ("doc2".to_string(), 1)
+            ])
+        );
+        assert!(counter.take().is_empty());
+
+        counter.restore(retrievals);
+        counter.restore(HashMap::from([("doc0".to_string(), 1)]));
+
+        assert_eq!(
+            counter.take(),
+            HashMap::from([
+                ("doc0".to_string(), 3),
+                ("doc1".to_string(), 1),
+                ("doc2".to_string(), 1)
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flush_popularity() {
+        let path = std::env::temp_dir().join(format!("popularity-{}", uuid::Uuid::now_v7()));
+        let db = lancedb::connect(path.to_str().unwrap())
+            .execute()
+            .await
+            .unwrap();
+
+        let vector_store_index = LanceDbVectorIndex::create_table(
+            &db,
+            "documents",
+            Model,
+            SchemaOptions::default().retrieval_count_field(),
+        )
+        .await
+        .unwrap()
+        .track_popularity(usize::MAX);
+
+        // More documents than the default limit of 10 rows of a LanceDB query.
+        let documents = (0..25)
+            .map(|i| (format!("doc{i}"), format!("Document {i}")))
+            .collect::<Vec<_>>();
+        vector_store_index
+            .ingestion_pipeline()
+            .run(documents)
+            .await
+            .unwrap();
+
+        let ids = (0..25).map(|i| format!("doc{i}#0")).collect::<Vec<_>>();
+        vector_store_index
+            .record_retrievals(ids.iter().map(String::as_str))
+            .await;
+        vector_store_index
+            .record_retrievals(ids[..12].iter().map(String::as_str))
+            .await;
+        vector_store_index.flush_popularity().await.unwrap();
+
+        let most_retrieved = vector_store_index.most_retrieved(25).await.unwrap();
+        assert_eq!(most_retrieved.len(), 25);
+        assert!(most_retrieved[..12].iter().all(|(_, count)| *count == 2));
+        assert!(most_retrieved[12..].iter().all(|(_, count)| *count == 1));
+
+        db.drop_db().await.unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{Filter, LanceDbVectorIndex};
+
+/// Number of power iterations used to compute each principal component.
+const POWER_ITERATIONS: usize = 100;
+
+/// Point of a 2D projection of the embeddings of an index, e.g. to plot a map of the corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectedPoint {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+    /// Value of the label column of the record, `Value::Null` if the record has no label.
+    pub label: Value,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Project a sample of at most `sample_size` embeddings matching `filter` on their first two principal components (PCA).
+    /// Each point is returned with the id of its record and the value of its `label_column`, ready to be plotted.
+    /// The sample is made of the first rows returned by LanceDB, not a random sample.
+    /// # Example
+    /// ```
+    /// let points = vector_store_index.project_2d(None, "category", 1000).await?;
+    ///
+    /// for point in points {
+    ///     println!("{} ({}): {}, {}", point.id, point.label, point.x, point.y);
+    /// }
+    /// ```
+    pub async fn project_2d(
+        &self,
+        filter: Option<Filter>,
+        label_column: &str,
+        sample_size: usize,
+    ) -> Result<Vec<ProjectedPoint>, VectorStoreError> {
+        let (matrix, rows) = self
+            .export(filter, Some(label_column), Some(sample_size))
+            .await?;
+
+        let points = pca_2d(&matrix.data, matrix.dims);
+
+        Ok(matrix
+            .ids
+            .into_iter()
+            .zip(rows)
+            .zip(points)
+            .map(|((id, row), [x, y])| ProjectedPoint {
+                id,
+                x,
+                y,
+                label: row.get(label_column).cloned().unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+/// Project the rows of the row-major `data` matrix, of `dims` columns, on its first two principal components.
+/// Components are computed by power iteration on the centered data, the second one deflated against the first.
+fn pca_2d(data: &[f32], dims: usize) -> Vec<[f64; 2]> {
+    if dims == 0 {
+        return vec![];
+    }
+
+    let n = data.len() / dims;
+
+    let mut mean = vec![0.0; dims];
+    for row in data.chunks_exact(dims) {
+        for (mean, value) in mean.iter_mut().zip(row) {
+            *mean += *value as f64 / n as f64;
+        }
+    }
+
+    let centered = data
+        .chunks_exact(dims)
+        .map(|row| {
+            row.iter()
+                .zip(&mean)
+                .map(|(value, mean)| *value as f64 - mean)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let first = principal_component(&centered, dims, &[]);
+    let second = principal_component(&centered, dims, &[first.as_slice()]);
+
+    centered
+        .iter()
+        .map(|row| [dot(row, &first), dot(row, &second)])
+        .collect()
+}
+
+/// Compute the principal component of `rows` orthogonal to the `previous` components.
+fn principal_component(rows: &[Vec<f64>], dims: usize, previous: &[&[f64]]) -> Vec<f64> {
+    // Deterministic, non degenerate starting vector.
+    let mut component = (0..dims).map(|i| 1.0 / (i + 1) as f64).collect::<Vec<_>>();
+
+    for _ in 0..POWER_ITERATIONS {
+        orthogonalize(&mut component, previous);
+
+        // component = Xt X component
+        let mut next = vec![0.0; dims];
+        for row in rows {
+            let projection = dot(row, &component);
+            for (next, value) in next.iter_mut().zip(row) {
+                *next += projection * value;
+            }
+        }
+
+        orthogonalize(&mut next, previous);
+
+        let norm = dot(&next, &next).sqrt();
+        if norm == 0.0 {
+            return vec![0.0; dims];
+        }
+
+        component = next.into_iter().map(|value| value / norm).collect();
+    }
+
+    component
+}
+
+fn orthogonalize(vector: &mut [f64], previous: &[&[f64]]) {
+    for previous in previous {
+        let projection = dot(vector, previous);
+        for (value, previous) in vector.iter_mut().zip(previous.iter()) {
+            *value -= projection * previous;
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pca_2d;
+
+    #[test]
+    fn test_pca_2d() {
+        // Points spread along the first axis, with a smaller spread along the third axis.
+        let data = [
+            -2.0, 0.0, 0.0, //
+            -1.0, 0.0, 0.5, //
+            1.0, 0.0, 0.5, //
+            2.0, 0.0, 0.0,
+        ];
+
+        let points = pca_2d(&data, 3);
+
+        assert_eq!(points.len(), 4);
+        // The first component follows the first axis, up to its sign.
+        assert!((points[0][0].abs() - 2.0).abs() < 1e-6);
+        assert!((points[3][0].abs() - 2.0).abs() < 1e-6);
+        assert!(points[0][0] * points[3][0] < 0.0);
+        // The second component follows the third axis.
+        assert!(points
+            .iter()
+            .all(|point| (point[1].abs() - 0.25).abs() < 1e-6));
+        assert!(points[0][1] * points[1][1] < 0.0);
+    }
+}
@@ -0,0 +1,60 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::LanceDbVectorIndex;
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Return every row whose distance to `query` is within the band set by the `min_distance` (inclusive)
+    /// and `max_distance` (exclusive) of the seizzyh params, as tuples of the form (score, id, document).
+    /// The filter of the seizzyh params applies. Full text seizzyh, MMR and the reranker do not.
+    /// # Example
+    /// ```
+    /// let vector_store_index = LanceDbVectorIndex::new(
+    ///     table,
+    ///     model,
+    ///     "id",
+    ///     SeizzyhParams::default().min_distance(0.1).max_distance(0.4),
+    /// )
+    /// .await?;
+    ///
+    /// let neighbors = vector_store_index
+    ///     .range_seizzyh::<WordDefinition>("My boss says I zindle too much, what does that mean?")
+    ///     .await?;
+    /// ```
+    pub async fn range_seizzyh<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        if self.seizzyh_params.min_distance.is_none() && self.seizzyh_params.max_distance.is_none()
+        {
+            return Err(VectorStoreError::DatastoreError(
+                "range_seizzyh requires a min_distance or a max_distance in the seizzyh params"
+                    .into(),
+            ));
+        }
+
+        let filter = self.combined_filter(None);
+
+        // Every row of the table may be within the band.
+        let n = self.count_rows(filter.clone()).await?;
+
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        let record_batches = self
+            .vector_seizzyh_batches(
+                &prompt_embedding,
+                None,
+                n,
+                0,
+                filter.as_deref(),
+                &self.payload_columns().await?,
+            )
+            .await?;
+
+        self.seizzyh_results_from_batches(record_batches)
+    }
+}
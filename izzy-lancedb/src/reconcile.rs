@@ -0,0 +1,273 @@
+use lancedb::index::{
+    scalar::{BTreeIndexBuilder, BitmapIndexBuilder, FtsIndexBuilder, LabelListIndexBuilder},
+    Index, IndexConfig, IndexType,
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex, MutationEvent, VectorIndexType};
+
+/// Type of scalar index, used to speed up the filters on a column.
+/// See [LanceDB scalar indexes](https://lancedb.github.io/lancedb/guides/scalar_index/) for more information.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarIndexType {
+    /// B-tree, for columns with many distinct values and range filters.
+    BTree,
+    /// Bitmap, for columns with few distinct values.
+    Bitmap,
+    /// Label list, for `List` columns filtered with `array_has_any` or `array_has_all`.
+    LabelList,
+}
+
+/// Index of the table of a `LanceDbVectorIndex`, declared in an `IndexSpec`.
+#[derive(Debug, Clone)]
+pub enum IndexDefinition {
+    /// ANN index on a vector column, created with the default parameters of `LanceDbVectorIndex::index_builder`.
+    Vector {
+        column: String,
+        index_type: VectorIndexType,
+    },
+    /// Scalar index on a column.
+    Scalar {
+        column: String,
+        index_type: ScalarIndexType,
+    },
+    /// Full text seizzyh index on a text column.
+    FullText { column: String },
+}
+
+impl IndexDefinition {
+    fn column(&self) -> &str {
+        match self {
+            IndexDefinition::Vector { column, .. }
+            | IndexDefinition::Scalar { column, .. }
+            | IndexDefinition::FullText { column } => column,
+        }
+    }
+
+    /// Whether the existing index `config` implements the definition.
+    fn is_implemented_by(&self, config: &IndexConfig) -> bool {
+        let index_type_matches = match (self, &config.index_type) {
+            (
+                IndexDefinition::Vector {
+                    index_type: VectorIndexType::IvfPq,
+                    ..
+                },
+                IndexType::IvfPq,
+            )
+            | (
+                IndexDefinition::Vector {
+                    index_type: VectorIndexType::IvfFlat,
+                    ..
+                },
+                IndexType::IvfFlat,
+            )
+            | (
+                IndexDefinition::Vector {
+                    index_type: VectorIndexType::Hnsw,
+                    ..
+                },
+                IndexType::IvfHnswPq | IndexType::IvfHnswSq,
+            )
+            | (
+                IndexDefinition::Scalar {
+                    index_type: ScalarIndexType::BTree,
+                    ..
+                },
+                IndexType::BTree,
+            )
+            | (
+                IndexDefinition::Scalar {
+                    index_type: ScalarIndexType::Bitmap,
+                    ..
+                },
+                IndexType::Bitmap,
+            )
+            | (
+                IndexDefinition::Scalar {
+                    index_type: ScalarIndexType::LabelList,
+                    ..
+                },
+                IndexType::LabelList,
+            )
+            | (IndexDefinition::FullText { .. }, IndexType::FTS) => true,
+            _ => false,
+        };
+
+        index_type_matches && config.columns == [self.column()]
+    }
+}
+
+/// Desired indexes of the table of a `LanceDbVectorIndex`, applied with `reconcile_indexes`.
+/// # Example
+/// ```
+/// use izzy_lancedb::{IndexSpec, ScalarIndexType, VectorIndexType};
+///
+/// let spec = IndexSpec::default()
+///     .vector("embedding", VectorIndexType::IvfPq)
+///     .scalar("lang", ScalarIndexType::Bitmap)
+///     .full_text("definition");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IndexSpec {
+    indexes: Vec<IndexDefinition>,
+}
+
+impl IndexSpec {
+    /// Declare an ANN index of type `index_type` on the vector `column`.
+    pub fn vector(mut self, column: &str, index_type: VectorIndexType) -> Self {
+        self.indexes.push(IndexDefinition::Vector {
+            column: column.to_string(),
+            index_type,
+        });
+        self
+    }
+
+    /// Declare a scalar index of type `index_type` on `column`.
+    pub fn scalar(mut self, column: &str, index_type: ScalarIndexType) -> Self {
+        self.indexes.push(IndexDefinition::Scalar {
+            column: column.to_string(),
+            index_type,
+        });
+        self
+    }
+
+    /// Declare a full text seizzyh index on the text `column`.
+    pub fn full_text(mut self, column: &str) -> Self {
+        self.indexes.push(IndexDefinition::FullText {
+            column: column.to_string(),
+        });
+        self
+    }
+}
+
+/// Report of `reconcile_indexes`.
+#[derive(Debug, Clone)]
+pub struct ReconcileReport {
+    /// Declared indexes created by the reconciliation.
+    pub created: Vec<IndexDefinition>,
+    /// Declared indexes that already existed.
+    pub unchanged: Vec<IndexDefinition>,
+    /// Names of the existing indexes that are not declared. They are not dropped.
+    pub extra: Vec<String>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Create the indexes of `spec` missing from the table, and report the existing indexes not declared in `spec`.
+    /// Reconciliation is idempotent: once the table has every declared index, it creates nothing.
+    /// An existing index of another type on a declared column is reported as extra, and the declared index replaces it.
+    /// # Example
+    /// ```
+    /// let report = vector_store_index.reconcile_indexes(&spec).await?;
+    ///
+    /// for name in report.extra {
+    ///     println!("Undeclared index {name}");
+    /// }
+    /// ```
+    pub async fn reconcile_indexes(
+        &self,
+        spec: &IndexSpec,
+    ) -> Result<ReconcileReport, VectorStoreError> {
+        let existing = self
+            .table
+            .list_indices()
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+
+        let (unchanged, missing): (Vec<_>, Vec<_>) =
+            spec.indexes.iter().cloned().partition(|definition| {
+                existing
+                    .iter()
+                    .any(|config| definition.is_implemented_by(config))
+            });
+
+        let extra = existing
+            .iter()
+            .filter(|config| {
+                !spec
+                    .indexes
+                    .iter()
+                    .any(|definition| definition.is_implemented_by(config))
+            })
+            .map(|config| config.name.clone())
+            .collect();
+
+        for definition in &missing {
+            self.create_declared_index(definition).await?;
+        }
+
+        Ok(ReconcileReport {
+            created: missing,
+            unchanged,
+            extra,
+        })
+    }
+
+    async fn create_declared_index(
+        &self,
+        definition: &IndexDefinition,
+    ) -> Result<(), VectorStoreError> {
+        let index = match definition {
+            IndexDefinition::Vector { column, index_type } => {
+                return self
+                    .index_builder(index_type.clone())
+                    .column(column)
+                    .build()
+                    .await;
+            }
+            IndexDefinition::Scalar {
+                index_type: ScalarIndexType::BTree,
+                ..
+            } => Index::BTree(BTreeIndexBuilder::default()),
+            IndexDefinition::Scalar {
+                index_type: ScalarIndexType::Bitmap,
+                ..
+            } => Index::Bitmap(BitmapIndexBuilder::default()),
+            IndexDefinition::Scalar {
+                index_type: ScalarIndexType::LabelList,
+                ..
+            } => Index::LabelList(LabelListIndexBuilder::default()),
+            IndexDefinition::FullText { .. } => Index::FTS(FtsIndexBuilder::default()),
+        };
+
+        let _operation = self.lifecycle.begin()?;
+
+        self.table
+            .create_index(&[definition.column()], index)
+            .execute()
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+
+        self.emit(MutationEvent::IndexBuilt {
+            column: definition.column().to_string(),
+        })
+        .await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lancedb::index::{IndexConfig, IndexType};
+
+    use super::{IndexSpec, ScalarIndexType};
+    use crate::VectorIndexType;
+
+    #[test]
+    fn test_is_implemented_by() {
+        let spec = IndexSpec::default()
+            .vector("embedding", VectorIndexType::Hnsw)
+            .scalar("lang", ScalarIndexType::Bitmap);
+
+        let config = |index_type, column: &str| IndexConfig {
+            name: format!("{column}_idx"),
+            index_type,
+            columns: vec![column.to_string()],
+        };
+
+        assert!(spec.indexes[0].is_implemented_by(&config(IndexType::IvfHnswSq, "embedding")));
+        assert!(!spec.indexes[0].is_implemented_by(&config(IndexType::IvfPq, "embedding")));
+        assert!(spec.indexes[1].is_implemented_by(&config(IndexType::Bitmap, "lang")));
+        assert!(!spec.indexes[1].is_implemented_by(&config(IndexType::Bitmap, "tenant_id")));
+    }
+}
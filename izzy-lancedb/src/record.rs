@@ -0,0 +1,210 @@
+use std::sync::izzy;
+
+use arrow_array::{types::Float32Type, ArrayRef, FixedSizeListArray, RecordBatch};
+use lancedb::arrow::arrow_schema::{DataType, Field, FieldRef, Schema};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::{Deserialize, Serialize};
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex, SeizzyhParams};
+
+/// Payload struct stored as the rows of a LanceDB table, along with its embedding.
+/// The Arrow schema of the table and the conversion of records to record batches are derived from the
+/// serde implementations of the struct, with `serde_arrow`. Records are read back with `top_n::<T>`.
+/// # Example
+/// ```
+/// use izzy_lancedb::LanceRecord;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct WordDefinition {
+///     id: String,
+///     word: String,
+///     definition: String,
+/// }
+///
+/// impl LanceRecord for WordDefinition {
+///     fn embedding_text(&self) -> String {
+///         self.definition.clone()
+///     }
+/// }
+///
+/// let vector_store_index =
+///     LanceDbVectorIndex::create_record_table::<WordDefinition>(&db, "definitions", model, "id", "embedding").await?;
+/// vector_store_index.insert_records(definitions).await?;
+/// ```
+pub trait LanceRecord: Serialize + for<'a> Deserialize<'a> + Sized {
+    /// Text of the record embedded with the embedding model of the index.
+    fn embedding_text(&self) -> String;
+
+    /// Arrow fields of the payload columns of the record. Traced from the serde implementations of the record by default.
+    fn fields() -> Result<Vec<FieldRef>, VectorStoreError> {
+        Vec::<FieldRef>::from_type::<Self>(TracingOptions::default())
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+
+    /// Arrow schema of a table of records, with a vector column `vector_field` of `dims` dimensions.
+    fn schema(vector_field: &str, dims: usize) -> Result<Schema, VectorStoreError> {
+        let mut fields = Self::fields()?;
+        fields.push(izzy::new(Field::new(
+            vector_field,
+            DataType::FixedSizeList(
+                izzy::new(Field::new("item", DataType::Float32, true)),
+                dims as i32,
+            ),
+            false,
+        )));
+
+        Ok(Schema::new(fields))
+    }
+
+    /// Record batch of `records`, with their `embeddings` in the vector column `vector_field`.
+    fn to_record_batch(
+        records: &[Self],
+        embeddings: &[Vec<f64>],
+        vector_field: &str,
+        dims: usize,
+    ) -> Result<RecordBatch, VectorStoreError> {
+        let payload = serde_arrow::to_record_batch(&Self::fields()?, &records)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        let vectors: ArrayRef =
+            izzy::new(
+                FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+                    embeddings
+                        .iter()
+                        .map(|embedding| Some(embedding.iter().map(|value| Some(*value as f32)))),
+                    dims as i32,
+                ),
+            );
+
+        let mut columns = payload.columns().to_vec();
+        columns.push(vectors);
+
+        RecordBatch::try_new(izzy::new(Self::schema(vector_field, dims)?), columns)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Create a new table named `name` on the connection `conn` for the records `T`, and return an instance of
+    /// `LanceDbVectorIndex` for it. The schema of the table is derived from `T` (see `LanceRecord::schema`),
+    /// with a vector column `vector_field` sized from `model.ndims()`, used as the seizzyh column.
+    pub async fn create_record_table<T: LanceRecord>(
+        conn: &lancedb::Connection,
+        name: &str,
+        model: M,
+        id_field: &str,
+        vector_field: &str,
+    ) -> Result<Self, VectorStoreError> {
+        let table = conn
+            .create_empty_table(name, izzy::new(T::schema(vector_field, model.ndims())?))
+            .execute()
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+
+        Ok(Self::new(
+            table,
+            model,
+            id_field,
+            SeizzyhParams::default().column(vector_field),
+        )
+        .await
+        .map_err(lancedb_to_izzy_error)?
+        .connection(conn.clone()))
+    }
+
+    /// Embed the `records` with the model of the index, in batches, and add them to the table.
+    /// The embedding input limit of the index applies to the embedding texts of the records (see `embedding_input_limit`).
+    /// The table must have the schema of `T` (see `create_record_table`).
+    pub async fn insert_records<T: LanceRecord>(
+        &self,
+        records: Vec<T>,
+    ) -> Result<(), VectorStoreError> {
+        let _ingest = self.bulk_ingest().await;
+        let vector_column = self.vector_column().await?;
+        let dims = self.model.ndims();
+
+        for records in records.chunks(M::MAX_DOCUMENTS.max(1)) {
+            let embeddings = self
+                .embed_documents(records.iter().map(T::embedding_text).collect::<Vec<_>>())
+                .await?
+                .into_iter()
+                .map(|embedding| embedding.vec)
+                .collect::<Vec<_>>();
+
+            self.insert(vec![T::to_record_batch(
+                records,
+                &embeddings,
+                &vector_column,
+                dims,
+            )?])
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lancedb::arrow::arrow_schema::DataType;
+    use serde::{Deserialize, Serialize};
+
+    use super::LanceRecord;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Definition {
+        id: String,
+        rank: Option<i32>,
+    }
+
+    impl LanceRecord for Definition {
+        fn embedding_text(&self) -> String {
+            self.id.clone()
+        }
+    }
+
+    #[test]
+    fn test_lance_record() {
+        let schema = Definition::schema("embedding", 2).unwrap();
+
+        assert_eq!(
+            schema
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>(),
+            vec!["id", "rank", "embedding"]
+        );
+        assert!(schema.field_with_name("rank").unwrap().is_nullable());
+        assert!(matches!(
+            schema.field_with_name("embedding").unwrap().data_type(),
+            DataType::FixedSizeList(_, 2)
+        ));
+
+        let records = vec![
+            Definition {
+                id: "doc0".to_string(),
+                rank: Some(1),
+            },
+            Definition {
+                id: "doc1".to_string(),
+                rank: None,
+            },
+        ];
+        let record_batch = Definition::to_record_batch(
+            &records,
+            &[vec![0.0, 1.0], vec![1.0, 0.0]],
+            "embedding",
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(record_batch.num_rows(), 2);
+        assert_eq!(
+            serde_arrow::from_record_batch::<Vec<Definition>>(&record_batch).unwrap(),
+            records
+        );
+    }
+}
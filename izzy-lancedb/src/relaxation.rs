@@ -0,0 +1,145 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{Filter, LanceDbVectorIndex, QueryOptions};
+
+/// Policy guaranteeing a minimum number of results to a filtered seizzyh by dropping optional filters,
+/// e.g. for product seizzyh over sparse metadata. Used by `top_n_with_relaxation`.
+/// Optional filters are relaxed one at a time, in the order they are declared, until the seizzyh returns
+/// at least the minimum number of results or every optional filter is dropped.
+/// # Example
+/// ```
+/// use izzy_lancedb::{Filter, FilterRelaxation};
+///
+/// let relaxation = FilterRelaxation::new(5)
+///     .optional(Filter::eq("color", "red"))
+///     .optional(Filter::lte("price", 50));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilterRelaxation {
+    min_results: usize,
+    optional: Vec<Filter>,
+}
+
+impl FilterRelaxation {
+    /// Create a relaxation policy guaranteeing `min_results` results, when enough rows match the required filter.
+    pub fn new(min_results: usize) -> Self {
+        Self {
+            min_results,
+            optional: Vec::new(),
+        }
+    }
+
+    /// Adds an optional filter, relaxed after the optional filters added before it.
+    pub fn optional(mut self, filter: Filter) -> Self {
+        self.optional.push(filter);
+        self
+    }
+
+    /// Filter of a seizzyh combining `required` with the optional filters not relaxed after `relaxed` steps.
+    fn filter(&self, required: Option<&Filter>, relaxed: usize) -> Option<Filter> {
+        self.optional[relaxed..]
+            .iter()
+            .cloned()
+            .fold(required.cloned(), |filter, optional| match filter {
+                Some(filter) => Some(filter.and(optional)),
+                None => Some(optional),
+            })
+    }
+}
+
+/// Results of `top_n_with_relaxation`.
+#[derive(Debug, Clone)]
+pub struct RelaxedResults<T> {
+    /// Results of the seizzyh, as tuples of the form (score, id, document).
+    pub results: Vec<(f64, String, T)>,
+    /// Optional filters dropped to reach the minimum number of results, in the order they were relaxed.
+    pub dropped: Vec<Filter>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n_with_filter`, with the optional filters of `relaxation` combined with `required`.
+    /// When fewer than the minimum number of results of `relaxation` are found, its optional filters are dropped
+    /// one at a time and the seizzyh is retried. The query is only embedded once.
+    /// The `required` filter and the filter of the seizzyh params are never dropped.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::{Filter, FilterRelaxation};
+    ///
+    /// let relaxed = vector_store_index
+    ///     .top_n_with_relaxation::<Product>(
+    ///         "waterproof hiking boots",
+    ///         10,
+    ///         Some(Filter::eq("in_stock", true)),
+    ///         &FilterRelaxation::new(5)
+    ///             .optional(Filter::eq("color", "red"))
+    ///             .optional(Filter::lte("price", 50)),
+    ///     )
+    ///     .await?;
+    ///
+    /// for filter in relaxed.dropped {
+    ///     println!("Relaxed {filter}");
+    /// }
+    /// ```
+    pub async fn top_n_with_relaxation<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        required: Option<Filter>,
+        relaxation: &FilterRelaxation,
+    ) -> Result<RelaxedResults<T>, VectorStoreError> {
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+        let mut relaxed = 0;
+
+        loop {
+            let options = match relaxation.filter(required.as_ref(), relaxed) {
+                Some(filter) => QueryOptions::default().filter(filter),
+                None => QueryOptions::default(),
+            };
+
+            let results = self
+                .seizzyh_embedding(&prompt_embedding, Some(query), n, options)
+                .await?;
+
+            if results.len() >= relaxation.min_results || relaxed == relaxation.optional.len() {
+                return Ok(RelaxedResults {
+                    results,
+                    dropped: relaxation.optional[..relaxed].to_vec(),
+                });
+            }
+
+            relaxed += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilterRelaxation;
+    use crate::Filter;
+
+    #[test]
+    fn test_relaxation_filter() {
+        let relaxation = FilterRelaxation::new(5)
+            .optional(Filter::eq("color", "red"))
+            .optional(Filter::lte("price", 50));
+        let required = Filter::eq("in_stock", true);
+
+        assert_eq!(
+            relaxation.filter(Some(&required), 0),
+            Some(
+                required
+                    .clone()
+                    .and(Filter::eq("color", "red"))
+                    .and(Filter::lte("price", 50))
+            )
+        );
+        assert_eq!(
+            relaxation.filter(Some(&required), 1),
+            Some(required.clone().and(Filter::lte("price", 50)))
+        );
+        assert_eq!(relaxation.filter(Some(&required), 2), Some(required));
+        assert_eq!(relaxation.filter(None, 1), Some(Filter::lte("price", 50)));
+        assert_eq!(relaxation.filter(None, 2), None);
+    }
+}
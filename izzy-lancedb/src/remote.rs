@@ -0,0 +1,60 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    lancedb_to_izzy_error, ConnectionBuilder, LanceDbVectorIndex, SchemaOptions, SeizzyhParams,
+};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Connect to the LanceDB Cloud database at the `db://` `uri`, with `api_key` in `region`,
+    /// and return an instance of `LanceDbVectorIndex` for its table named `name`.
+    /// The table is created if it does not exist, with the schema derived from `schema_opts`,
+    /// as with `create_table`. The vector column of `schema_opts` is used as the seizzyh column.
+    /// Use `ConnectionBuilder` and `create_table` for the other connection options, e.g. `host_override`.
+    /// Requires the `remote` feature of this crate.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::{LanceDbVectorIndex, SchemaOptions};
+    ///
+    /// let vector_store_index = LanceDbVectorIndex::connect_remote(
+    ///     "db://my-database",
+    ///     &std::env::var("LANCEDB_API_KEY")?,
+    ///     "us-east-1",
+    ///     "definitions",
+    ///     model,
+    ///     SchemaOptions::default(),
+    /// )
+    /// .await?;
+    /// ```
+    pub async fn connect_remote(
+        uri: &str,
+        api_key: &str,
+        region: &str,
+        name: &str,
+        model: M,
+        schema_opts: SchemaOptions,
+    ) -> Result<Self, VectorStoreError> {
+        let conn = ConnectionBuilder::new(uri)
+            .api_key(api_key)
+            .region(region)
+            .execute()
+            .await?;
+
+        match conn.open_table(name).execute().await {
+            Ok(table) => Ok(Self::new(
+                table,
+                model,
+                &schema_opts.id_field,
+                SeizzyhParams::default().column(&schema_opts.vector_field),
+            )
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .connection(conn)),
+            Err(lancedb::Error::TableNotFound { .. }) => {
+                Self::create_table(&conn, name, model, schema_opts)
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }
+            Err(e) => Err(lancedb_to_izzy_error(e)),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use futures::future::BoxFuture;
+use izzy::vector_store::VectorStoreError;
+use serde_json::Value;
+
+/// Name of the column containing the score given to a row by the `Reranker` of the index.
+pub(crate) const RERANK_SCORE_COLUMN: &str = "_rerank_score";
+
+/// Reranker applied to the candidates of the vector seizzyh before they are returned, e.g. a cross-encoder.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::reranker`.
+/// # Example
+/// ```
+/// use futures::future::BoxFuture;
+/// use izzy::vector_store::VectorStoreError;
+/// use izzy_lancedb::Reranker;
+///
+/// struct CrossEncoder;
+///
+/// impl Reranker for CrossEncoder {
+///     fn rerank<'a>(
+///         &'a self,
+///         query: &'a str,
+///         candidates: &'a [serde_json::Value],
+///     ) -> BoxFuture<'a, Result<Vec<f64>, VectorStoreError>> {
+///         Box::pin(async move { Ok(candidates.iter().map(|_| 0.0).collect()) })
+///     }
+/// }
+/// ```
+pub trait Reranker: Send + Sync {
+    /// Score each of the `candidates` for `query`. Higher scores rank first.
+    /// Must return exactly one score per candidate, in the order of `candidates`.
+    fn rerank<'a>(
+        &'a self,
+        query: &'a str,
+        candidates: &'a [Value],
+    ) -> BoxFuture<'a, Result<Vec<f64>, VectorStoreError>>;
+}
+
+/// Rerank `rows` for `query` with `reranker`. The score of each row is written to its `_rerank_score` column.
+pub(crate) async fn rerank(
+    reranker: &dyn Reranker,
+    query: &str,
+    rows: Vec<Value>,
+) -> Result<Vec<Value>, VectorStoreError> {
+    let scores = reranker.rerank(query, &rows).await?;
+
+    if scores.len() != rows.len() {
+        return Err(VectorStoreError::DatastoreError(
+            format!(
+                "Reranker returned {} scores for {} candidates",
+                scores.len(),
+                rows.len()
+            )
+            .into(),
+        ));
+    }
+
+    Ok(sort_by_scores(rows, scores))
+}
+
+fn sort_by_scores(rows: Vec<Value>, scores: Vec<f64>) -> Vec<Value> {
+    let mut scored = scores.into_iter().zip(rows).collect::<Vec<_>>();
+
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    scored
+        .into_iter()
+        .map(|(score, mut row)| {
+            if let Value::Object(map) = &mut row {
+                map.insert(RERANK_SCORE_COLUMN.to_string(), score.into());
+            }
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{sort_by_scores, RERANK_SCORE_COLUMN};
+
+    #[test]
+    fn test_sort_by_scores() {
+        let rows = vec![json!({"id": "a"}), json!({"id": "b"}), json!({"id": "c"})];
+
+        let sorted = sort_by_scores(rows, vec![0.1, 0.9, 0.5]);
+
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|row| row["id"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["b", "c", "a"]
+        );
+        assert_eq!(sorted[0][RERANK_SCORE_COLUMN], json!(0.9));
+    }
+}
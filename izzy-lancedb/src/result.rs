@@ -0,0 +1,120 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{hit::take_vector, LanceDbVectorIndex, QueryOptions};
+
+/// Name of the column containing the row id of a result, when row ids are returned by the seizzyh params.
+pub(crate) const ROW_ID_COLUMN: &str = "_rowid";
+
+/// Result of `top_n_results`, the typed counterpart of the `(score, id, document)` tuples of `top_n`.
+#[derive(Debug, Clone)]
+pub struct SeizzyhResult<T> {
+    /// Score of the result, normalized according to the score normalization of the seizzyh params, if any.
+    pub score: f64,
+    /// Raw distance of the result to the query, as returned by LanceDB.
+    /// `None` for the results found by the full text seizzyh only of a hybrid seizzyh.
+    pub distance: Option<f64>,
+    pub id: String,
+    pub payload: T,
+    /// Vector of the record, read from the vector column.
+    /// `None` unless vectors are returned by the seizzyh params (see `SeizzyhParams::return_vectors`).
+    pub vector: Option<Vec<f32>>,
+    /// Row id of the record in the table.
+    /// `None` unless row ids are returned by the seizzyh params (see `SeizzyhParams::return_row_ids`).
+    pub row_id: Option<u64>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, returning a `SeizzyhResult` per result instead of a tuple.
+    /// # Example
+    /// ```
+    /// let results = vector_store_index
+    ///     .top_n_results::<WordDefinition>("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    ///
+    /// for result in results {
+    ///     println!("{} {} {:?}", result.score, result.id, result.payload);
+    /// }
+    /// ```
+    pub async fn top_n_results<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<SeizzyhResult<T>>, VectorStoreError> {
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        self.seizzyh_results(&prompt_embedding, Some(query), n)
+            .await
+    }
+
+    /// Same as `top_n_results`, but with a precomputed embedding instead of a query (see `top_n_from_embedding`).
+    pub async fn top_n_results_from_embedding<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        embedding: &[f64],
+        n: usize,
+    ) -> Result<Vec<SeizzyhResult<T>>, VectorStoreError> {
+        self.seizzyh_results(embedding, None, n).await
+    }
+
+    async fn seizzyh_results<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        embedding: &[f64],
+        query: Option<&str>,
+        n: usize,
+    ) -> Result<Vec<SeizzyhResult<T>>, VectorStoreError> {
+        let (results, score_column) = self
+            .seizzyh_rows(embedding, query, n, QueryOptions::default())
+            .await?;
+
+        let vector_column = match self.seizzyh_params.return_vectors {
+            true => Some(self.vector_column().await?),
+            false => None,
+        };
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut value)| {
+                let vector = vector_column
+                    .as_ref()
+                    .and_then(|vector_column| take_vector(&mut value, vector_column))
+                    .map(|vector| vector.into_iter().map(|value| value as f32).collect());
+                let row_id = take_row_id(&mut value);
+                let distance = value.get(self.distance_column()).and_then(Value::as_f64);
+
+                let (score, id, payload) = self.seizzyh_result(i, value, score_column)?;
+
+                Ok(SeizzyhResult {
+                    score,
+                    distance,
+                    id,
+                    payload,
+                    vector,
+                    row_id,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Remove the row id column from the `row` and return its value.
+fn take_row_id(row: &mut Value) -> Option<u64> {
+    row.as_object_mut()?.remove(ROW_ID_COLUMN)?.as_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::take_row_id;
+
+    #[test]
+    fn test_take_row_id() {
+        let mut row = json!({"id": "doc0", "_rowid": 42});
+
+        assert_eq!(take_row_id(&mut row), Some(42));
+        assert_eq!(row, json!({"id": "doc0"}));
+        assert_eq!(take_row_id(&mut row), None);
+    }
+}
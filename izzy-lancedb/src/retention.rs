@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use lancedb::query::{QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    freshness::{unix_now, TIMESTAMP_COLUMN},
+    utils::{id_to_string, scan_all, QueryToJson},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Column flagging the documents under legal hold (`Boolean`), which are never removed by `purge_expired`.
+pub const LEGAL_HOLD_COLUMN: &str = "legal_hold";
+
+/// Data retention policy of a `LanceDbVectorIndex`, enforced by `purge_expired`.
+/// Documents older than the maximum age of their class are removed, unless they are under legal hold.
+/// The age of a document is read from the `timestamp` column (see `SchemaOptions::timestamp_field`).
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use izzy_lancedb::RetentionPolicy;
+///
+/// const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+///
+/// let vector_store_index = vector_store_index.retention_policy(
+///     RetentionPolicy::default()
+///         .class_column("doc_class")
+///         .max_age("chat_log", 30 * DAY)
+///         .max_age("contract", 7 * 365 * DAY),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    class_column: Option<String>,
+    max_ages: Vec<(String, Duration)>,
+    default_max_age: Option<Duration>,
+    legal_hold_column: String,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            class_column: None,
+            max_ages: Vec::new(),
+            default_max_age: None,
+            legal_hold_column: LEGAL_HOLD_COLUMN.to_string(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Sets the column containing the class of a document, e.g. `chat_log` or `contract`.
+    /// Required by `max_age`.
+    pub fn class_column(mut self, class_column: &str) -> Self {
+        self.class_column = Some(class_column.to_string());
+        self
+    }
+
+    /// Sets the maximum age of the documents of `class`.
+    pub fn max_age(mut self, class: &str, max_age: Duration) -> Self {
+        self.max_ages.retain(|(other, _)| other != class);
+        self.max_ages.push((class.to_string(), max_age));
+        self
+    }
+
+    /// Sets the maximum age of the documents whose class has no maximum age, or of every document
+    /// if there is no class column. By default, these documents are retained forever.
+    pub fn default_max_age(mut self, default_max_age: Duration) -> Self {
+        self.default_max_age = Some(default_max_age);
+        self
+    }
+
+    /// Sets the column flagging the documents under legal hold. The default is `legal_hold`.
+    pub fn legal_hold_column(mut self, legal_hold_column: &str) -> Self {
+        self.legal_hold_column = legal_hold_column.to_string();
+        self
+    }
+
+    /// Returns the rules of the policy at time `now`: the class of each rule (`None` for the default rule),
+    /// its maximum age and the filter matching its expired documents, on hold or not.
+    fn rules(&self, now: i64) -> Result<Vec<(Option<String>, Duration, Filter)>, VectorStoreError> {
+        let expired =
+            |max_age: &Duration| Filter::lt(TIMESTAMP_COLUMN, now - max_age.as_secs() as i64);
+
+        let mut rules = Vec::new();
+
+        if !self.max_ages.is_empty() {
+            let class_column = self.class_column.as_deref().ok_or_else(|| {
+                VectorStoreError::DatastoreError(
+                    "The retention policy has maximum ages per class but no class column".into(),
+                )
+            })?;
+
+            for (class, max_age) in &self.max_ages {
+                rules.push((
+                    Some(class.clone()),
+                    *max_age,
+                    Filter::eq(class_column, class.as_str()).and(expired(max_age)),
+                ));
+            }
+        }
+
+        if let Some(max_age) = &self.default_max_age {
+            let filter = match &self.class_column {
+                Some(class_column) if !self.max_ages.is_empty() => Filter::is_null(class_column)
+                    .or(Filter::is_in(
+                        class_column,
+                        self.max_ages.iter().map(|(class, _)| class.as_str()),
+                    )
+                    .not())
+                    .and(expired(max_age)),
+                _ => expired(max_age),
+            };
+
+            rules.push((None, *max_age, filter));
+        }
+
+        Ok(rules)
+    }
+
+    /// Filter matching the documents under legal hold.
+    fn on_hold(&self) -> Filter {
+        Filter::eq(&self.legal_hold_column, true)
+    }
+}
+
+/// Outcome of a rule of the retention policy, reported by `purge_expired`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionOutcome {
+    /// Class of the rule, `None` for the default maximum age.
+    pub class: Option<String>,
+    pub max_age: Duration,
+    /// Ids of the expired documents removed.
+    pub removed: Vec<String>,
+    /// Ids of the expired documents retained because they are under legal hold.
+    pub held: Vec<String>,
+}
+
+/// Audit report of `purge_expired`, with one outcome per rule of the retention policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionReport {
+    /// Time the policy was enforced at, as seconds since the Unix epoch.
+    pub enforced_at: i64,
+    pub outcomes: Vec<RetentionOutcome>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the data retention policy of the index, enforced by `purge_expired`.
+    pub fn retention_policy(mut self, retention_policy: RetentionPolicy) -> Self {
+        self.retention_policy = Some(retention_policy);
+        self
+    }
+
+    /// Remove the expired documents according to the retention policy of the index, except the documents under legal hold.
+    /// Run it as a periodic maintenance job. Returns an audit report of the removed and retained documents.
+    /// # Example
+    /// ```
+    /// let report = vector_store_index.purge_expired().await?;
+    ///
+    /// for outcome in report.outcomes {
+    ///     println!("{:?}: {} removed, {} on hold", outcome.class, outcome.removed.len(), outcome.held.len());
+    /// }
+    /// ```
+    pub async fn purge_expired(&self) -> Result<RetentionReport, VectorStoreError> {
+        let policy = self.retention_policy.as_ref().ok_or_else(|| {
+            VectorStoreError::DatastoreError("No retention policy set on the vector index".into())
+        })?;
+
+        let enforced_at = unix_now();
+        let mut outcomes = Vec::new();
+
+        for (class, max_age, expired) in policy.rules(enforced_at)? {
+            let held = self
+                .matching_ids(expired.clone().and(policy.on_hold()))
+                .await?;
+
+            let removable = expired.and(
+                policy
+                    .on_hold()
+                    .not()
+                    .or(Filter::is_null(&policy.legal_hold_column)),
+            );
+            let removed = self.matching_ids(removable.clone()).await?;
+
+            // Only delete the reported rows: rows inserted or released from hold since are left to the next run.
+            if !removed.is_empty() {
+                self.delete(
+                    self.ids_filter(removed.iter().map(String::as_str))?
+                        .and(removable),
+                )
+                .await?;
+            }
+
+            outcomes.push(RetentionOutcome {
+                class,
+                max_age,
+                removed,
+                held,
+            });
+        }
+
+        Ok(RetentionReport {
+            enforced_at,
+            outcomes,
+        })
+    }
+
+    /// Ids of every row of the table matching `filter`.
+    async fn matching_ids(&self, filter: Filter) -> Result<Vec<String>, VectorStoreError> {
+        Ok(scan_all(&self.table)
+            .only_if(filter.to_string())
+            .select(Select::Columns(self.id_columns()))
+            .execute_query_with(self.query_settings())
+            .await?
+            .into_iter()
+            .filter_map(|mut row| {
+                self.stamp_id(&mut row);
+                row.get(&self.id_field).and_then(id_to_string)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetentionPolicy;
+
+    #[test]
+    fn test_rules() {
+        let policy = RetentionPolicy::default()
+            .class_column("class")
+            .max_age("chat", Duration::from_secs(10))
+            .default_max_age(Duration::from_secs(100));
+
+        let rules = policy
+            .rules(1000)
+            .unwrap()
+            .into_iter()
+            .map(|(class, _, filter)| (class, filter.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rules,
+            vec![
+                (
+                    Some("chat".to_string()),
+                    "(class = 'chat') AND (timestamp < 990)".to_string()
+                ),
+                (
+                    None,
+                    "((class IS NULL) OR (NOT (class IN ('chat')))) AND (timestamp < 900)"
+                        .to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(policy.on_hold().to_string(), "legal_hold = true");
+
+        let unclassified = RetentionPolicy::default().max_age("chat", Duration::from_secs(10));
+        assert!(unclassified.rules(1000).is_err());
+    }
+}
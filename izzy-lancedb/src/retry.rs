@@ -0,0 +1,277 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use lancedb::{arrow::SendableRecordBatchStream, query::ExecutableQuery};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, timeout::with_timeout, LanceDbVectorIndex};
+
+/// Retry policy of the LanceDB queries and writes of a `LanceDbVectorIndex`, e.g. for tables backed by
+/// an object store that occasionally fail with transient IO errors.
+/// Failed operations are retried after an exponential backoff, as long as their error is retryable.
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use izzy_lancedb::RetryPolicy;
+///
+/// let vector_store_index = vector_store_index.retry_policy(
+///     RetryPolicy::default()
+///         .max_attempts(5)
+///         .initial_backoff(Duration::from_millis(200)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retryable: fn(&lancedb::Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            retryable: is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy running every operation once. This is the policy of an index by default.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts of an operation, including the first one. The default is 3.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the backoff before the first retry. The default is 100ms.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum backoff between two attempts. The default is 5s.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the factor applied to the backoff after each retry. The default is 2.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets whether the backoffs are randomized, between half and all of their value,
+    /// so that concurrent clients do not retry in lockstep. The default is true.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the classification of the retryable LanceDB errors.
+    /// By default, object store errors and Lance IO errors are retried (see `is_transient`).
+    pub fn retryable(mut self, retryable: fn(&lancedb::Error) -> bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Run `operation` until it succeeds, fails with an error that is not retryable, or runs out of attempts.
+    /// Only LanceDB errors wrapped in `VectorStoreError::DatastoreError` are retried.
+    pub(crate) async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, VectorStoreError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, VectorStoreError>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_attempts && self.is_retryable(&e) => {
+                    tokio::time::sleep(self.backoff(attempt, random())).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn is_retryable(&self, e: &VectorStoreError) -> bool {
+        match e {
+            VectorStoreError::DatastoreError(e) => e
+                .downcast_ref::<lancedb::Error>()
+                .is_some_and(self.retryable),
+            _ => false,
+        }
+    }
+
+    /// Backoff after the failed attempt number `attempt` (starting at 1), with `random` uniform in [0, 1).
+    /// The backoff is computed in seconds and capped by the maximum backoff before it is converted,
+    /// so that it does not overflow a `Duration` after many attempts.
+    fn backoff(&self, attempt: usize, random: f64) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let backoff = Duration::try_from_secs_f64(backoff)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        match self.jitter {
+            true => backoff.mul_f64(0.5 + random / 2.0),
+            false => backoff,
+        }
+    }
+}
+
+/// Default classification of the retryable LanceDB errors: object store errors,
+/// e.g. timeouts or throttling of S3, and IO errors of Lance.
+pub fn is_transient(e: &lancedb::Error) -> bool {
+    match e {
+        lancedb::Error::ObjectStore { .. } => true,
+        lancedb::Error::Lance {
+            source: lance::Error::IO { .. },
+        } => true,
+        _ => false,
+    }
+}
+
+/// Random number uniform in [0, 1), from the random keys of the standard library hasher.
+fn random() -> f64 {
+    (RandomState::new().build_hasher().finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the retry policy of the LanceDB queries and writes of the index.
+    /// By default, operations are not retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Count the rows of the table matching the SQL `filter`, with the retry policy and the query timeout of the index.
+    pub(crate) async fn count_rows(
+        &self,
+        filter: Option<String>,
+    ) -> Result<usize, VectorStoreError> {
+        with_timeout(
+            self.seizzyh_params.timeout,
+            self.retry_policy.run(|| async {
+                self.ensure_consistency().await?;
+
+                self.table
+                    .count_rows(filter.clone())
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await
+    }
+
+    /// Start `query` with the execution options, the retry policy and the query timeout of the index.
+    /// Only the start of the query is retried and timed: the returned stream is not.
+    pub(crate) async fn open_stream(
+        &self,
+        query: &impl ExecutableQuery,
+    ) -> Result<SendableRecordBatchStream, VectorStoreError> {
+        with_timeout(
+            self.seizzyh_params.timeout,
+            self.retry_policy.run(|| async {
+                self.ensure_consistency().await?;
+
+                query
+                    .execute_with_options(self.execution_options())
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use izzy::vector_store::VectorStoreError;
+
+    use super::RetryPolicy;
+
+    fn lancedb_error() -> VectorStoreError {
+        VectorStoreError::DatastoreError(Box::new(lancedb::Error::InvalidInput {
+            message: "transient".to_string(),
+        }))
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2, 0.0), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3, 0.0), Duration::from_millis(300));
+        assert_eq!(policy.backoff(1000, 0.0), Duration::from_millis(300));
+        assert_eq!(policy.backoff(usize::MAX, 0.0), Duration::from_millis(300));
+
+        let policy = policy.jitter(true);
+        assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(50));
+        assert_eq!(policy.backoff(2, 0.5), Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::ZERO)
+            .retryable(|_| true);
+        let attempts = AtomicUsize::new(0);
+
+        let result = policy
+            .run(|| async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(lancedb_error()),
+                    attempt => Ok(attempt),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+
+        attempts.store(0, Ordering::SeqCst);
+        let result = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(lancedb_error())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Errors that are not retryable fail immediately.
+        attempts.store(0, Ordering::SeqCst);
+        let result = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(VectorStoreError::DatastoreError("invalid filter".into()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::izzy;
+
+use futures::future::BoxFuture;
+use izzy::{
+    completion::{Message, Prompt},
+    embeddings::embedding::EmbeddingModel,
+    vector_store::VectorStoreError,
+};
+use serde::Deserialize;
+
+use crate::{LanceDbVectorIndex, QueryOptions};
+
+/// Default prompt of `CondenseQuestion`. `{history}` is replaced with the chat history, one message per line,
+/// and `{question}` with the follow-up question.
+pub const DEFAULT_CONDENSE_PROMPT: &str = "Given the following conversation and a follow-up question, \
+rephrase the follow-up question to be a standalone question, in its original language. \
+Only return the standalone question.\n\nConversation:\n{history}\n\nFollow-up question: {question}\n\nStandalone question:";
+
+/// Rewrites a follow-up question into a standalone seizzyh query using the chat history, before it is embedded.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::query_rewriter`.
+pub trait QueryRewriter: Send + Sync {
+    /// Rewrite `query` using the chat `history`, oldest message first.
+    fn rewrite<'a>(
+        &'a self,
+        query: &'a str,
+        history: &'a [Message],
+    ) -> BoxFuture<'a, Result<String, VectorStoreError>>;
+}
+
+/// `QueryRewriter` condensing the chat history and the follow-up question into a standalone question with an LLM.
+/// # Example
+/// ```
+/// use izzy_lancedb::CondenseQuestion;
+///
+/// let vector_store_index = vector_store_index
+///     .query_rewriter(CondenseQuestion::new(openai_client.agent("gpt-4o-mini").build()));
+/// ```
+pub struct CondenseQuestion<P: Prompt> {
+    model: P,
+    prompt: String,
+}
+
+impl<P: Prompt> CondenseQuestion<P> {
+    pub fn new(model: P) -> Self {
+        Self {
+            model,
+            prompt: DEFAULT_CONDENSE_PROMPT.to_string(),
+        }
+    }
+
+    /// Sets the prompt sent to the model. See `DEFAULT_CONDENSE_PROMPT` for the placeholders.
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+}
+
+impl<P: Prompt> QueryRewriter for CondenseQuestion<P> {
+    fn rewrite<'a>(
+        &'a self,
+        query: &'a str,
+        history: &'a [Message],
+    ) -> BoxFuture<'a, Result<String, VectorStoreError>> {
+        Box::pin(async move {
+            let standalone = self
+                .model
+                .prompt(&condense_prompt(&self.prompt, query, history))
+                .await
+                .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+            Ok(standalone.trim().to_string())
+        })
+    }
+}
+
+/// Fill the placeholders of the condense `prompt`, in a single pass so that placeholders
+/// in the history or the question are left as is.
+fn condense_prompt(prompt: &str, query: &str, history: &[Message]) -> String {
+    let history = history
+        .iter()
+        .map(|message| format!("{}: {}", message.role, message.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut filled = String::with_capacity(prompt.len() + history.len() + query.len());
+    let mut rest = prompt;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("{history}") {
+            filled.push_str(&history);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{question}") {
+            filled.push_str(query);
+            rest = after;
+        } else {
+            filled.push('{');
+            rest = &rest[1..];
+        }
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the query rewriter used by `top_n_with_history` to turn follow-up questions into standalone queries.
+    pub fn query_rewriter(mut self, query_rewriter: impl QueryRewriter + 'static) -> Self {
+        self.query_rewriter = Some(izzy::new(query_rewriter));
+        self
+    }
+
+    /// Rewrite the follow-up `query` into a standalone query with the query rewriter of the index.
+    /// Returns `query` as is if no query rewriter is set or if `history` is empty.
+    pub async fn condense_question(
+        &self,
+        query: &str,
+        history: &[Message],
+    ) -> Result<String, VectorStoreError> {
+        match &self.query_rewriter {
+            Some(query_rewriter) if !history.is_empty() => {
+                query_rewriter.rewrite(query, history).await
+            }
+            _ => Ok(query.to_string()),
+        }
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait for a follow-up question of a conversation:
+    /// `query` is first rewritten into a standalone query using the chat `history` (see `condense_question`).
+    /// # Example
+    /// ```
+    /// let result = vector_store_index
+    ///     .top_n_with_history::<WordDefinition>("And what about zindle?", &chat_history, 1)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_with_history<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        history: &[Message],
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let query = self.condense_question(query, history).await?;
+
+        self.seizzyh(&query, n, QueryOptions::default()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use izzy::completion::Message;
+
+    use super::condense_prompt;
+
+    #[test]
+    fn test_condense_prompt() {
+        let history = vec![
+            Message {
+                role: "user".to_string(),
+                content: "What does flumbrel mean?".to_string(),
+            },
+            Message {
+                role: "assistant".to_string(),
+                content: "A small item you constantly lose.".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            condense_prompt("{history}\n> {question}", "And zindle?", &history),
+            "user: What does flumbrel mean?\nassistant: A small item you constantly lose.\n> And zindle?"
+        );
+    }
+
+    #[test]
+    fn test_condense_prompt_placeholders_in_history() {
+        let history = vec![Message {
+            role: "user".to_string(),
+            content: "What does {question} mean?".to_string(),
+        }];
+
+        assert_eq!(
+            condense_prompt(
+                "{history}\n> {question} {other}",
+                "And {history}?",
+                &history
+            ),
+            "user: What does {question} mean?\n> And {history}? {other}"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::izzy;
+
+use lancedb::arrow::arrow_schema::{DataType, Field, Fields, Schema};
+
+use crate::{
+    chunks::{CHUNK_INDEX_COLUMN, PARENT_ID_COLUMN, TOTAL_CHUNKS_COLUMN},
+    dedup::CONTENT_HASH_COLUMN,
+    erasure::SUBJECT_COLUMN,
+    expiry::EXPIRES_AT_COLUMN,
+    freshness::TIMESTAMP_COLUMN,
+    multivector::multivector_data_type,
+    popularity::RETRIEVAL_COUNT_COLUMN,
+    retention::LEGAL_HOLD_COLUMN,
+    soft_delete::DELETED_AT_COLUMN,
+};
+
+/// Options used to derive the schema of a new LanceDB table from an embedding model.
+/// The resulting schema contains an id column, a text column, optional metadata columns
+/// and a `FixedSizeList<Float32>` vector column sized from the model dimensions.
+/// # Example
+/// ```
+/// use lancedb::arrow::arrow_schema::DataType;
+///
+/// let schema_opts = izzy_lancedb::SchemaOptions::default()
+///     .text_field("definition")
+///     .metadata_field("lang", DataType::Utf8, true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaOptions {
+    pub(crate) id_field: String,
+    pub(crate) text_field: String,
+    pub(crate) vector_field: String,
+    pub(crate) metadata_fields: Vec<Field>,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        Self {
+            id_field: "id".to_string(),
+            text_field: "text".to_string(),
+            vector_field: "embedding".to_string(),
+            metadata_fields: Vec::new(),
+        }
+    }
+}
+
+impl SchemaOptions {
+    /// Sets the name of the id column. The default is `id`.
+    pub fn id_field(mut self, id_field: &str) -> Self {
+        self.id_field = id_field.to_string();
+        self
+    }
+
+    /// Sets the name of the column containing the embedded text. The default is `text`.
+    pub fn text_field(mut self, text_field: &str) -> Self {
+        self.text_field = text_field.to_string();
+        self
+    }
+
+    /// Sets the name of the vector column. The default is `embedding`.
+    pub fn vector_field(mut self, vector_field: &str) -> Self {
+        self.vector_field = vector_field.to_string();
+        self
+    }
+
+    /// Adds a metadata column to the schema.
+    pub fn metadata_field(mut self, name: &str, data_type: DataType, nullable: bool) -> Self {
+        self.metadata_fields
+            .push(Field::new(name, data_type, nullable));
+        self
+    }
+
+    /// Adds the `parent_id`, `chunk_index` and `total_chunks` columns used to store the position
+    /// of a chunk within its parent document.
+    pub fn chunk_fields(self) -> Self {
+        self.metadata_field(PARENT_ID_COLUMN, DataType::Utf8, false)
+            .metadata_field(CHUNK_INDEX_COLUMN, DataType::Int32, false)
+            .metadata_field(TOTAL_CHUNKS_COLUMN, DataType::Int32, false)
+    }
+
+    /// Adds the `content_hash` column containing the hash of the text of a chunk, used by `IngestionPipeline::dedup`.
+    pub fn content_hash_field(self) -> Self {
+        self.metadata_field(CONTENT_HASH_COLUMN, DataType::Utf8, true)
+    }
+
+    /// Adds the `timestamp` column containing the time a document was written or last updated,
+    /// as seconds since the Unix epoch.
+    pub fn timestamp_field(self) -> Self {
+        self.metadata_field(TIMESTAMP_COLUMN, DataType::Int64, true)
+    }
+
+    /// Adds the `legal_hold` column flagging the documents under legal hold, which are never removed by `purge_expired`.
+    pub fn legal_hold_field(self) -> Self {
+        self.metadata_field(LEGAL_HOLD_COLUMN, DataType::Boolean, true)
+    }
+
+    /// Adds the `subject_id` column containing the id of the data subject of a document, erased by `erase_subject`.
+    pub fn subject_field(self) -> Self {
+        self.metadata_field(SUBJECT_COLUMN, DataType::Utf8, true)
+    }
+
+    /// Adds the `retrieval_count` column containing the number of retrievals of a document, counted by `track_popularity`.
+    pub fn retrieval_count_field(self) -> Self {
+        self.metadata_field(RETRIEVAL_COUNT_COLUMN, DataType::Int64, true)
+    }
+
+    /// Adds the `deleted_at` column containing the time a document was soft deleted (see `LanceDbVectorIndex::soft_delete`),
+    /// as seconds since the Unix epoch.
+    pub fn deleted_at_field(self) -> Self {
+        self.metadata_field(DELETED_AT_COLUMN, DataType::Int64, true)
+    }
+
+    /// Adds the `expires_at` column containing the time a document expires (see `LanceDbVectorIndex::ttl`),
+    /// as seconds since the Unix epoch.
+    pub fn expires_at_field(self) -> Self {
+        self.metadata_field(EXPIRES_AT_COLUMN, DataType::Int64, true)
+    }
+
+    /// Adds a multivector column containing the token vectors of each document, with `dims` dimensions,
+    /// for late interaction seizzyh with `top_n_multivector`. Build its values with `multivector_array`.
+    pub fn multivector_field(self, name: &str, dims: usize) -> Self {
+        self.metadata_field(name, multivector_data_type(dims as i32), false)
+    }
+
+    /// Build the arrow schema of the table for embeddings with `dims` dimensions.
+    pub fn to_schema(&self, dims: usize) -> Schema {
+        let mut fields = vec![
+            Field::new(&self.id_field, DataType::Utf8, false),
+            Field::new(&self.text_field, DataType::Utf8, false),
+        ];
+
+        fields.extend(self.metadata_fields.iter().cloned());
+
+        fields.push(Field::new(
+            &self.vector_field,
+            DataType::FixedSizeList(
+                izzy::new(Field::new("item", DataType::Float32, true)),
+                dims as i32,
+            ),
+            false,
+        ));
+
+        Schema::new(Fields::from(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lancedb::arrow::arrow_schema::DataType;
+
+    use super::SchemaOptions;
+
+    #[test]
+    fn test_schema_from_options() {
+        let schema = SchemaOptions::default()
+            .text_field("definition")
+            .metadata_field("lang", DataType::Utf8, true)
+            .to_schema(1536);
+
+        let names = schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["id", "definition", "lang", "embedding"]);
+
+        match schema.field_with_name("embedding").unwrap().data_type() {
+            DataType::FixedSizeList(inner, size) => {
+                assert_eq!(inner.data_type(), &DataType::Float32);
+                assert_eq!(*size, 1536);
+            }
+            data_type => panic!("Unexpected vector column type: {data_type}"),
+        }
+    }
+}
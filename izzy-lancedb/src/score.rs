@@ -0,0 +1,61 @@
+use crate::Distance;
+
+/// Defines how the distances returned by the vector seizzyh are converted to scores.
+#[derive(Debug, Clone, Default)]
+pub enum ScoreNormalization {
+    /// Return the raw distance. Lower is better. This is the default.
+    #[default]
+    None,
+    /// Return `1 / (1 + distance)`, in `0..1`. Higher is better.
+    Inverse,
+    /// Return a similarity in `0..1` derived from the distance type: `1 - distance / 2` for cosine
+    /// and dot distances (assuming normalized vectors for dot), and `1 / (1 + distance)` for L2 and hamming distances.
+    /// Higher is better.
+    Similarity,
+}
+
+impl ScoreNormalization {
+    /// Convert `distance`, computed with `distance_type`, to a score.
+    pub(crate) fn normalize(&self, distance: f64, distance_type: Distance) -> f64 {
+        match self {
+            ScoreNormalization::None => distance,
+            ScoreNormalization::Inverse => inverse(distance),
+            ScoreNormalization::Similarity => match distance_type {
+                Distance::Cosine | Distance::Dot => (1.0 - distance / 2.0).clamp(0.0, 1.0),
+                _ => inverse(distance),
+            },
+        }
+    }
+}
+
+fn inverse(distance: f64) -> f64 {
+    1.0 / (1.0 + distance.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Distance;
+
+    use super::ScoreNormalization;
+
+    #[test]
+    fn test_score_normalization() {
+        assert_eq!(ScoreNormalization::None.normalize(0.5, Distance::L2), 0.5);
+        assert_eq!(
+            ScoreNormalization::Inverse.normalize(1.0, Distance::Cosine),
+            0.5
+        );
+        assert_eq!(
+            ScoreNormalization::Similarity.normalize(0.5, Distance::Cosine),
+            0.75
+        );
+        assert_eq!(
+            ScoreNormalization::Similarity.normalize(3.0, Distance::L2),
+            0.25
+        );
+        assert_eq!(
+            ScoreNormalization::Similarity.normalize(2.5, Distance::Dot),
+            0.0
+        );
+    }
+}
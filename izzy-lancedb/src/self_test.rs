@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{LanceDbVectorIndex, QueryOptions};
+
+/// Text embedded and seizzyhed by `self_test`.
+pub const SELF_TEST_CANARY: &str = "izzy-lancedb self test";
+
+/// Report of a successful `self_test`.
+#[derive(Debug, Clone)]
+pub struct SelfTestReport {
+    /// Time taken by the embedding model to embed the canary.
+    pub embedding_latency: Duration,
+    /// Time taken by the top-1 seizzyh of the canary embedding, including deserialization.
+    pub query_latency: Duration,
+    /// Number of dimensions of the canary embedding.
+    pub ndims: usize,
+    /// Id of the result of the seizzyh, `None` if the table is empty.
+    pub result_id: Option<String>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Smoke test of the index for deploy pipelines: embed a canary string, check its dimensions against
+    /// the embedding model, run a top-1 seizzyh with it and deserialize the result as `T`.
+    /// Fails with the first error encountered, otherwise reports the round-trip latencies.
+    /// # Example
+    /// ```
+    /// let report = vector_store_index.self_test::<WordDefinition>().await?;
+    ///
+    /// println!(
+    ///     "embedding: {:?}, query: {:?}",
+    ///     report.embedding_latency, report.query_latency
+    /// );
+    /// ```
+    pub async fn self_test<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+    ) -> Result<SelfTestReport, VectorStoreError> {
+        let start = Instant::now();
+        let embedding = self.embed_text(SELF_TEST_CANARY).await?;
+        let embedding_latency = start.elapsed();
+
+        if embedding.vec.len() != self.model.ndims() {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Embedding model returned {} dimensions, expected {}",
+                    embedding.vec.len(),
+                    self.model.ndims()
+                )
+                .into(),
+            ));
+        }
+
+        let start = Instant::now();
+        let results = self
+            .seizzyh_embedding::<T>(
+                &embedding.vec,
+                Some(SELF_TEST_CANARY),
+                1,
+                QueryOptions::default(),
+            )
+            .await?;
+        let query_latency = start.elapsed();
+
+        Ok(SelfTestReport {
+            embedding_latency,
+            query_latency,
+            ndims: embedding.vec.len(),
+            result_id: results.into_iter().next().map(|(_, id, _)| id),
+        })
+    }
+}
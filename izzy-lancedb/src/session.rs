@@ -0,0 +1,130 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{Filter, LanceDbVectorIndex, QueryOptions};
+
+/// Stateful seizzyh for a query refined through several calls, as in faceted seizzyh UIs, returned by `seizzyh_session`.
+/// The query is embedded once, when the session starts: `refine`, `exclude` and `more` reuse its embedding.
+/// The filters of a session are combined with the filter of the seizzyh params.
+pub struct SeizzyhSession<'a, M: EmbeddingModel> {
+    vector_index: &'a LanceDbVectorIndex<M>,
+    query: String,
+    embedding: Vec<f64>,
+    page_size: usize,
+    filters: Vec<Filter>,
+    excluded_ids: Vec<String>,
+    /// Number of results already returned for the current filters and exclusions.
+    offset: usize,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Start a seizzyh session for `query`, returning `page_size` results per call.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::Filter;
+    ///
+    /// let mut session = vector_store_index
+    ///     .seizzyh_session("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    ///
+    /// let results = session.results::<WordDefinition>().await?;
+    /// let results = session.refine::<WordDefinition>(Filter::eq("lang", "en")).await?;
+    /// let results = session.exclude::<WordDefinition>(["doc0", "doc3"]).await?;
+    /// let next_page = session.more::<WordDefinition>().await?;
+    /// ```
+    pub async fn seizzyh_session(
+        &self,
+        query: &str,
+        page_size: usize,
+    ) -> Result<SeizzyhSession<'_, M>, VectorStoreError> {
+        Ok(SeizzyhSession {
+            vector_index: self,
+            query: query.to_string(),
+            embedding: self.embed_seizzyh_query(query).await?,
+            page_size,
+            filters: Vec::new(),
+            excluded_ids: Vec::new(),
+            offset: 0,
+        })
+    }
+}
+
+impl<M: EmbeddingModel> SeizzyhSession<'_, M> {
+    /// Query of the session.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Filters applied by `refine`, in order.
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Ids excluded by `exclude`.
+    pub fn excluded_ids(&self) -> &[String] {
+        &self.excluded_ids
+    }
+
+    /// First page of results for the current filters and exclusions.
+    pub async fn results<T: for<'a> Deserialize<'a> + Send>(
+        &mut self,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.offset = 0;
+        self.more().await
+    }
+
+    /// Narrow the results down with `filter`, combined with the previous filters, and return their first page.
+    pub async fn refine<T: for<'a> Deserialize<'a> + Send>(
+        &mut self,
+        filter: impl Into<Filter>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.filters.push(filter.into());
+        self.results().await
+    }
+
+    /// Exclude the records of `ids` from the results, e.g. the results dismissed by the user, and return their first page.
+    pub async fn exclude<T: for<'a> Deserialize<'a> + Send>(
+        &mut self,
+        ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.excluded_ids.extend(ids.into_iter().map(Into::into));
+        self.results().await
+    }
+
+    /// Next page of results for the current filters and exclusions.
+    pub async fn more<T: for<'a> Deserialize<'a> + Send>(
+        &mut self,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let mut options = QueryOptions::default().offset(self.offset);
+        if let Some(filter) = self.filter()? {
+            options = options.filter(filter);
+        }
+
+        let results = self
+            .vector_index
+            .seizzyh_embedding(&self.embedding, Some(&self.query), self.page_size, options)
+            .await?;
+        self.offset += results.len();
+
+        Ok(results)
+    }
+
+    /// Filters of the session combined with the exclusion of the excluded ids.
+    fn filter(&self) -> Result<Option<Filter>, VectorStoreError> {
+        let exclusion = match self.excluded_ids.is_empty() {
+            true => None,
+            false => Some(
+                self.vector_index
+                    .ids_filter(self.excluded_ids.iter().map(String::as_str))?
+                    .not(),
+            ),
+        };
+
+        Ok(self
+            .filters
+            .iter()
+            .cloned()
+            .chain(exclusion)
+            .reduce(Filter::and))
+    }
+}
@@ -0,0 +1,44 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{LanceDbVectorIndex, QueryOptions};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// More-like-this: the `n` records nearest to the record `id`, excluding the record itself.
+    /// The stored vector of the record is read from the vector column and used as the query embedding,
+    /// so the embedding model of the index is not called.
+    /// As with `top_n_from_embedding`, only the vector seizzyh is performed when the seizzyh type is `SeizzyhType::Hybrid`.
+    /// Returns an error if no record matching the filter of the seizzyh params has this id.
+    /// # Example
+    /// ```
+    /// let similar = vector_store_index
+    ///     .top_n_by_id::<WordDefinition>("doc0", 5)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_by_id<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        id: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let seed = self
+            .export(Some(self.id_filter(id)?), None, Some(1))
+            .await?
+            .0;
+
+        let Some(embedding) = seed.row(0) else {
+            return Err(VectorStoreError::DatastoreError(
+                format!("No record with id {id}").into(),
+            ));
+        };
+
+        let embedding = embedding.iter().map(|&x| x as f64).collect::<Vec<_>>();
+
+        self.seizzyh_embedding(
+            &embedding,
+            None,
+            n,
+            QueryOptions::default().filter(self.id_filter(id)?.not()),
+        )
+        .await
+    }
+}
@@ -0,0 +1,151 @@
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{lancedb_to_izzy_error, LanceDbVectorIndex, MutationEvent};
+
+/// Prefix of the `_meta` keys containing the table version of a snapshot, followed by the tag of the snapshot.
+pub const SNAPSHOT_META_PREFIX: &str = "snapshot:";
+
+/// Margin added to the age of the oldest snapshot version when pruning, so that it is not pruned
+/// in the time between the computation of its age and the pruning.
+const SNAPSHOT_PRUNE_MARGIN_SECS: i64 = 60 * 60;
+
+/// Version of the table restored by `restore`: the version of a snapshot tag, or a table version.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotRef {
+    /// Tag of a snapshot taken with `snapshot`.
+    Tag(String),
+    /// Version of the table.
+    Version(u64),
+}
+
+impl From<&str> for SnapshotRef {
+    fn from(tag: &str) -> Self {
+        SnapshotRef::Tag(tag.to_string())
+    }
+}
+
+impl From<String> for SnapshotRef {
+    fn from(tag: String) -> Self {
+        SnapshotRef::Tag(tag)
+    }
+}
+
+impl From<u64> for SnapshotRef {
+    fn from(version: u64) -> Self {
+        SnapshotRef::Version(version)
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Record the current version of the table under `tag`, replacing any previous snapshot with the same tag.
+    /// Returns the version of the snapshot. Tags are stored in the `<table>_meta` companion table:
+    /// requires a connection (see `LanceDbVectorIndex::connection`).
+    /// `optimize` and `cleanup_old_versions` keep the versions of the snapshots, along with every later version,
+    /// until the snapshots are replaced. `erase_subject` prunes them regardless.
+    /// # Example
+    /// ```
+    /// vector_store_index.snapshot("before-reembedding").await?;
+    ///
+    /// if reembed(&vector_store_index).await.is_err() {
+    ///     vector_store_index.restore("before-reembedding").await?;
+    /// }
+    /// ```
+    pub async fn snapshot(&self, tag: &str) -> Result<u64, VectorStoreError> {
+        let version = self.table.version().await.map_err(lancedb_to_izzy_error)?;
+
+        self.set_meta(
+            &format!("{SNAPSHOT_META_PREFIX}{tag}"),
+            &version.to_string(),
+        )
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Restore the table to the version of a snapshot tag (see `snapshot`) or to a table version.
+    /// The restored version becomes the latest version of the table: the versions written since are kept,
+    /// and can themselves be restored.
+    pub async fn restore(&self, snapshot: impl Into<SnapshotRef>) -> Result<(), VectorStoreError> {
+        let _operation = self.lifecycle.begin()?;
+
+        let version = match snapshot.into() {
+            SnapshotRef::Version(version) => version,
+            SnapshotRef::Tag(tag) => self
+                .get_meta(&format!("{SNAPSHOT_META_PREFIX}{tag}"))
+                .await?
+                .ok_or_else(|| {
+                    VectorStoreError::DatastoreError(format!("No snapshot tagged {tag}").into())
+                })?
+                .parse()
+                .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?,
+        };
+
+        self.table
+            .checkout(version)
+            .await
+            .map_err(lancedb_to_izzy_error)?;
+
+        if let Err(e) = self.table.restore().await {
+            // A table checked out at an old version is read only: go back to the latest version.
+            self.table
+                .checkout_latest()
+                .await
+                .map_err(lancedb_to_izzy_error)?;
+
+            return Err(lancedb_to_izzy_error(e));
+        }
+
+        self.emit(MutationEvent::Restored { version }).await;
+
+        Ok(())
+    }
+
+    /// Age of the versions that can be pruned without pruning a snapshot version: `older_than`,
+    /// raised to the age of the oldest snapshot version, with a margin.
+    pub(crate) async fn snapshot_prune_age(
+        &self,
+        older_than: chrono::Duration,
+    ) -> Result<chrono::Duration, VectorStoreError> {
+        if self.connection.is_none() {
+            return Ok(older_than);
+        }
+
+        let Some(oldest) = self
+            .meta_entries(SNAPSHOT_META_PREFIX)
+            .await?
+            .into_iter()
+            .filter_map(|(_, version)| version.parse::<u64>().ok())
+            .min()
+        else {
+            return Ok(older_than);
+        };
+
+        let created_at = self
+            .table
+            .list_versions()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .into_iter()
+            .find(|version| version.version == oldest)
+            .map(|version| version.timestamp);
+
+        Ok(match created_at {
+            Some(created_at) => older_than.max(
+                chrono::Utc::now() - created_at
+                    + chrono::Duration::seconds(SNAPSHOT_PRUNE_MARGIN_SECS),
+            ),
+            None => older_than,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotRef;
+
+    #[test]
+    fn test_snapshot_ref() {
+        assert_eq!(SnapshotRef::from("v1"), SnapshotRef::Tag("v1".to_string()));
+        assert_eq!(SnapshotRef::from(3), SnapshotRef::Version(3));
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::{
+    freshness::unix_now, lancedb_to_izzy_error, timeout::with_timeout, Filter, LanceDbVectorIndex,
+    MutationEvent,
+};
+
+/// Name of the column containing the time a document was soft deleted, as seconds since the Unix epoch.
+/// Null for the documents that are not deleted.
+pub const DELETED_AT_COLUMN: &str = "deleted_at";
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets whether deletes are soft deletes: `delete_by_ids` sets the `deleted_at` column of the documents
+    /// instead of removing them, and every seizzyh, scan and count of the index skips the soft deleted documents.
+    /// Soft deleted documents are permanently removed with `purge_deleted`.
+    /// Requires the `deleted_at` column (see `SchemaOptions::deleted_at_field`). Disabled by default.
+    /// # Example
+    /// ```
+    /// let vector_store_index = LanceDbVectorIndex::new(table, model, "id", SeizzyhParams::default())
+    ///     .await?
+    ///     .soft_delete(true);
+    ///
+    /// vector_store_index.delete_by_ids(&["doc0", "doc1"]).await?;
+    /// ```
+    pub fn soft_delete(mut self, soft_delete: bool) -> Self {
+        self.soft_delete = soft_delete;
+        self
+    }
+
+    /// Delete the documents with the given `ids`. With soft deletes enabled, the documents are marked as deleted
+    /// with the current time, keeping the time of the first delete of documents deleted again.
+    pub async fn delete_by_ids(&self, ids: &[&str]) -> Result<(), VectorStoreError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let filter = self.ids_filter(ids.iter().copied())?;
+
+        if !self.soft_delete {
+            return self.delete(filter).await;
+        }
+
+        let _operation = self.lifecycle.begin()?;
+
+        let filter = filter.and(Filter::is_null(DELETED_AT_COLUMN)).to_string();
+        let deleted_at = unix_now().to_string();
+
+        with_timeout(
+            self.write_timeout,
+            self.retry_policy.run(|| async {
+                self.table
+                    .update()
+                    .only_if(filter.clone())
+                    .column(DELETED_AT_COLUMN, deleted_at.clone())
+                    .execute()
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            }),
+        )
+        .await?;
+
+        self.emit(MutationEvent::SoftDeleted { filter }).await;
+
+        Ok(())
+    }
+
+    /// Permanently remove the documents soft deleted more than `older_than` ago.
+    /// Use `Duration::ZERO` to remove every soft deleted document.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// vector_store_index
+    ///     .purge_deleted(Duration::from_secs(30 * 24 * 60 * 60))
+    ///     .await?;
+    /// ```
+    pub async fn purge_deleted(&self, older_than: Duration) -> Result<(), VectorStoreError> {
+        self.delete(Filter::is_not_null(DELETED_AT_COLUMN).and(Filter::lte(
+            DELETED_AT_COLUMN,
+            unix_now() - older_than.as_secs() as i64,
+        )))
+        .await
+    }
+
+    /// Filter excluding the soft deleted documents, if soft deletes are enabled.
+    pub(crate) fn soft_delete_filter(&self) -> Option<Filter> {
+        self.soft_delete.then(|| Filter::is_null(DELETED_AT_COLUMN))
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use lancedb::{
+    arrow::arrow_schema::DataType,
+    query::{ExecutableQuery, QueryBase, Select},
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{is_vector_index, scan_all, RecordBatchDeserializer},
+    Filter, LanceDbVectorIndex,
+};
+
+/// Statistics of the table of a `LanceDbVectorIndex`, returned by `stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    /// Number of rows of the table matching the filter of the seizzyh params,
+    /// excluding the soft deleted and expired documents.
+    pub rows: usize,
+    /// Number of rows in the vector index of the seizzyh column, `None` if the column has no vector index.
+    pub indexed_rows: Option<usize>,
+    /// Number of fragments of the table, `None` for remote tables.
+    pub fragments: Option<usize>,
+    /// Version of the table.
+    pub version: u64,
+    /// Name of the seizzyh column.
+    pub vector_column: String,
+    /// Number of dimensions of the seizzyh column.
+    pub dims: usize,
+}
+
+impl TableStats {
+    /// Fraction of the rows of the table in the vector index, `None` if the seizzyh column has no vector index.
+    /// The vector index also covers the filtered out rows, so the fraction is capped at 1.
+    pub fn index_coverage(&self) -> Option<f64> {
+        self.indexed_rows.map(|indexed_rows| match self.rows {
+            0 => 1.0,
+            rows => (indexed_rows as f64 / rows as f64).min(1.0),
+        })
+    }
+}
+
+/// Statistics of the rows of the table with a value of a metadata column, returned by `group_stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStats {
+    /// Value of the metadata column, `Value::Null` for the rows without a value.
+    pub value: Value,
+    pub rows: usize,
+    /// Average number of characters of the text column.
+    pub avg_text_length: f64,
+    /// Average L2 norm of the vectors of the seizzyh column.
+    pub avg_embedding_norm: f64,
+}
+
+/// Sums of the statistics of a group, averaged by `GroupSums::finish`.
+#[derive(Debug, Default)]
+struct GroupSums {
+    value: Value,
+    rows: usize,
+    text_length: usize,
+    embedding_norm: f64,
+}
+
+impl GroupSums {
+    fn finish(self) -> GroupStats {
+        let rows = self.rows.max(1) as f64;
+
+        GroupStats {
+            value: self.value,
+            rows: self.rows,
+            avg_text_length: self.text_length as f64 / rows,
+            avg_embedding_norm: self.embedding_norm / rows,
+        }
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Number of rows of the table matching `filter`, combined with the filter of the seizzyh params.
+    /// # Example
+    /// ```
+    /// use izzy_lancedb::Filter;
+    ///
+    /// let english = vector_store_index.row_count(Some(Filter::eq("lang", "en"))).await?;
+    /// ```
+    pub async fn row_count(&self, filter: Option<Filter>) -> Result<usize, VectorStoreError> {
+        self.count_rows(self.combined_filter(filter)).await
+    }
+
+    /// Statistics of the table, e.g. for dashboards of the corpus size and index coverage.
+    /// # Example
+    /// ```
+    /// let stats = vector_store_index.stats().await?;
+    ///
+    /// println!("{} rows, {:?} indexed, {:?} fragments", stats.rows, stats.indexed_rows, stats.fragments);
+    /// ```
+    pub async fn stats(&self) -> Result<TableStats, VectorStoreError> {
+        let rows = self.count_rows(self.combined_filter(None)).await?;
+        let version = self.table.version().await.map_err(lancedb_to_izzy_error)?;
+        let vector_column = self.vector_column().await?;
+
+        let dims = match self
+            .table
+            .schema()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .field_with_name(&vector_column)
+            .map(|field| field.data_type().clone())
+        {
+            Ok(DataType::FixedSizeList(_, dims)) => dims as usize,
+            _ => {
+                return Err(VectorStoreError::DatastoreError(
+                    format!("Column {vector_column} is not a vector column").into(),
+                ))
+            }
+        };
+
+        let vector_index = self
+            .table
+            .list_indices()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .into_iter()
+            .find(|index| {
+                is_vector_index(&index.index_type) && index.columns == [vector_column.as_str()]
+            });
+
+        let indexed_rows = match vector_index {
+            Some(index) => self
+                .table
+                .index_stats(&index.name)
+                .await
+                .map_err(lancedb_to_izzy_error)?
+                .map(|stats| stats.num_indexed_rows),
+            None => None,
+        };
+
+        let fragments = match self.table.as_native() {
+            Some(table) => Some(
+                table
+                    .manifest()
+                    .await
+                    .map_err(lancedb_to_izzy_error)?
+                    .fragments
+                    .len(),
+            ),
+            None => None,
+        };
+
+        Ok(TableStats {
+            rows,
+            indexed_rows,
+            fragments,
+            version,
+            vector_column,
+            dims,
+        })
+    }
+
+    /// Statistics of the rows of the table per value of `column`: row counts, average length of `text_column`
+    /// and average norm of the vectors of the seizzyh column, e.g. to spot a tenant dominating a shared index.
+    /// Groups are returned from the largest to the smallest. The rows are scanned one record batch at a time,
+    /// so run it as a maintenance job on large tables. The filter of the seizzyh params applies.
+    /// # Example
+    /// ```
+    /// for group in vector_store_index.group_stats("tenant_id", "text").await? {
+    ///     println!("{}: {} rows, {:.0} chars on average", group.value, group.rows, group.avg_text_length);
+    /// }
+    /// ```
+    pub async fn group_stats(
+        &self,
+        column: &str,
+        text_column: &str,
+    ) -> Result<Vec<GroupStats>, VectorStoreError> {
+        let vector_column = self.vector_column().await?;
+
+        let mut query = scan_all(&self.table).select(Select::Columns(vec![
+            column.to_string(),
+            text_column.to_string(),
+            vector_column.clone(),
+        ]));
+
+        if let Some(filter) = self.combined_filter(None) {
+            query = query.only_if(filter);
+        }
+
+        let mut batches = self
+            .retry_policy
+            .run(|| async {
+                self.ensure_consistency().await?;
+
+                query
+                    .execute_with_options(self.execution_options())
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            })
+            .await?;
+
+        let mut groups = HashMap::new();
+        while let Some(batch) = batches.try_next().await.map_err(lancedb_to_izzy_error)? {
+            accumulate(
+                &mut groups,
+                batch.deserialize()?,
+                column,
+                text_column,
+                &vector_column,
+            );
+        }
+
+        let mut groups = groups
+            .into_values()
+            .map(GroupSums::finish)
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.rows.cmp(&a.rows));
+
+        Ok(groups)
+    }
+}
+
+/// Add the statistics of `rows` to the sums of their group, keyed by the value of their `column`.
+fn accumulate(
+    groups: &mut HashMap<String, GroupSums>,
+    rows: Vec<Value>,
+    column: &str,
+    text_column: &str,
+    vector_column: &str,
+) {
+    for mut row in rows {
+        let value = row
+            .as_object_mut()
+            .and_then(|row| row.remove(column))
+            .unwrap_or(Value::Null);
+
+        let sums = groups
+            .entry(value.to_string())
+            .or_insert_with(|| GroupSums {
+                value,
+                ..Default::default()
+            });
+
+        sums.rows += 1;
+        sums.text_length += row
+            .get(text_column)
+            .and_then(Value::as_str)
+            .map_or(0, |text| text.chars().count());
+        sums.embedding_norm += match row.get(vector_column) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|value| value * value)
+                .sum::<f64>()
+                .sqrt(),
+            _ => 0.0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::{accumulate, TableStats};
+
+    #[test]
+    fn test_index_coverage() {
+        let stats = TableStats {
+            rows: 200,
+            indexed_rows: Some(150),
+            fragments: Some(3),
+            version: 7,
+            vector_column: "embedding".to_string(),
+            dims: 1536,
+        };
+
+        assert_eq!(stats.index_coverage(), Some(0.75));
+        assert_eq!(
+            TableStats {
+                indexed_rows: None,
+                ..stats.clone()
+            }
+            .index_coverage(),
+            None
+        );
+        assert_eq!(
+            TableStats {
+                indexed_rows: Some(250),
+                ..stats.clone()
+            }
+            .index_coverage(),
+            Some(1.0)
+        );
+        assert_eq!(
+            TableStats {
+                rows: 0,
+                indexed_rows: Some(0),
+                ..stats
+            }
+            .index_coverage(),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let mut groups = HashMap::new();
+
+        accumulate(
+            &mut groups,
+            vec![
+                json!({"tenant_id": "a", "text": "abcd", "embedding": [3.0, 4.0]}),
+                json!({"tenant_id": "a", "text": "ab", "embedding": [1.0, 0.0]}),
+                json!({"tenant_id": null, "text": "abc", "embedding": [0.0, 2.0]}),
+            ],
+            "tenant_id",
+            "text",
+            "embedding",
+        );
+
+        let a = groups.remove("\"a\"").unwrap().finish();
+        assert_eq!(a.value, json!("a"));
+        assert_eq!(a.rows, 2);
+        assert_eq!(a.avg_text_length, 3.0);
+        assert_eq!(a.avg_embedding_norm, 3.0);
+
+        let none = groups.remove("null").unwrap().finish();
+        assert_eq!(none.value, json!(null));
+        assert_eq!(none.rows, 1);
+        assert_eq!(none.avg_embedding_norm, 2.0);
+    }
+}
@@ -0,0 +1,198 @@
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{id_to_string, scan_all, RecordBatchDeserializer},
+    Filter, LanceDbVectorIndex, QueryKind, QueryOptions, SeizzyhType,
+};
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, but the results are yielded as a stream of tuples
+    /// of the form (score, id, document), as the LanceDB record batches arrive.
+    /// Only one record batch is held in memory at a time. The resource limits of the seizzyh params apply
+    /// to the whole stream.
+    /// When the seizzyh type is `SeizzyhType::Hybrid`, or when MMR, a reranker, or a grouping or ordering by a metadata
+    /// column is enabled, the results are collected before the stream starts, since these stages require every candidate.
+    /// The seizzyh is reported to the seizzyh observer, and its results to the retrieval counts, once the stream ends,
+    /// which it does at the first error. The query timeout covers the embedding of the query and the opening of the
+    /// stream, not the iteration over it.
+    /// # Example
+    /// ```
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut results = vector_store_index
+    ///     .top_n_stream::<WordDefinition>("My boss says I zindle too much, what does that mean?", 1000)
+    ///     .await?;
+    ///
+    /// while let Some((score, id, definition)) = results.try_next().await? {
+    ///     println!("{score} {id} {definition:?}");
+    /// }
+    /// ```
+    pub async fn top_n_stream<T: for<'a> Deserialize<'a> + Send + 'static>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<BoxStream<'_, Result<(f64, String, T), VectorStoreError>>, VectorStoreError> {
+        if matches!(self.seizzyh_params.seizzyh_type, Some(SeizzyhType::Hybrid))
+            || self.seizzyh_params.mmr_lambda.is_some()
+            || self.reranker.is_some()
+            || self.seizzyh_params.order_by.is_some()
+            || self.seizzyh_params.group_by.is_some()
+        {
+            let results = self.seizzyh::<T>(query, n, QueryOptions::default()).await?;
+            return Ok(stream::iter(results.into_iter().map(Ok)).boxed());
+        }
+
+        let mut observation = self.observe(QueryKind::TopN, query.to_string(), n);
+
+        let batches = async {
+            let filter = self.combined_filter(None);
+
+            let prompt_embedding = self.embed_seizzyh_query(query).await?;
+            observation.embedded();
+
+            let vector_query = self
+                .table
+                .vector_seizzyh(self.query_vector(&prompt_embedding))
+                .map_err(lancedb_to_izzy_error)?
+                .select(Select::Columns(self.payload_columns().await?));
+            let vector_query =
+                self.apply_page(vector_query, Some(query), n, self.resolve_offset(None));
+
+            let vector_query = self
+                .apply_filter(self.build_query(vector_query), filter.as_deref())
+                .await?;
+            self.open_stream(&vector_query).await
+        }
+        .await;
+
+        let batches = match batches {
+            Ok(batches) => batches,
+            Err(e) => return Err(observation.fail(e)),
+        };
+
+        let limits = self.seizzyh_params.resource_limits.clone();
+        let utf8_policy = self.utf8_policy;
+        let (mut rows, mut bytes) = (0, 0);
+
+        let results = batches
+            .map(move |batch| {
+                let batch = batch.map_err(lancedb_to_izzy_error)?;
+
+                rows += batch.num_rows();
+                bytes += batch.get_array_memory_size();
+                limits
+                    .check(rows, bytes)
+                    .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+                match utf8_policy {
+                    Some(utf8_policy) => utf8_policy.apply(batch)?.deserialize(),
+                    None => batch.deserialize(),
+                }
+            })
+            .map_ok(|values| stream::iter(values.into_iter().map(Ok)))
+            .try_flatten()
+            .enumerate()
+            .map(move |(i, value)| {
+                value.and_then(|value| self.seizzyh_result(i, value, self.distance_column()))
+            })
+            .boxed();
+
+        Ok(stream::unfold(
+            (results, Some(observation), Vec::new()),
+            move |(mut results, observation, mut hits)| async move {
+                let observation = observation?;
+
+                match results.next().await {
+                    Some(Ok((score, id, value))) => {
+                        hits.push((score, id.clone()));
+                        Some((Ok((score, id, value)), (results, Some(observation), hits)))
+                    }
+                    Some(Err(e)) => Some((Err(observation.fail(e)), (results, None, hits))),
+                    None => {
+                        let hits = Ok(hits);
+                        observation.end(&hits, |(score, _)| *score);
+
+                        self.record_retrievals(hits.iter().flatten().map(|(_, id)| id.as_str()))
+                            .await;
+                        None
+                    }
+                }
+            },
+        )
+        .boxed())
+    }
+
+    /// Scan every row of the table matching `filter`, combined with the filter of the seizzyh params.
+    /// Rows are yielded as tuples of the form (id, document), reading `batch_size` rows per record batch.
+    /// Use it for offline jobs such as re-embedding, analytics or exports.
+    /// # Example
+    /// ```
+    /// use futures::TryStreamExt;
+    /// use izzy_lancedb::Filter;
+    ///
+    /// let mut rows = vector_store_index
+    ///     .scan::<WordDefinition>(Some(Filter::eq("lang", "en")), 1024)
+    ///     .await?;
+    ///
+    /// while let Some((id, definition)) = rows.try_next().await? {
+    ///     println!("{id} {definition:?}");
+    /// }
+    /// ```
+    pub async fn scan<T: for<'a> Deserialize<'a> + Send + 'static>(
+        &self,
+        filter: Option<Filter>,
+        batch_size: u32,
+    ) -> Result<BoxStream<'_, Result<(String, T), VectorStoreError>>, VectorStoreError> {
+        let mut query =
+            scan_all(&self.table).select(Select::Columns(self.payload_columns().await?));
+
+        if let Some(filter) = self.combined_filter(filter) {
+            query = query.only_if(filter);
+        }
+
+        let mut options = self.execution_options();
+        options.max_batch_length = batch_size;
+
+        let batches = self
+            .retry_policy
+            .run(|| async {
+                self.ensure_consistency().await?;
+
+                query
+                    .execute_with_options(options.clone())
+                    .await
+                    .map_err(lancedb_to_izzy_error)
+            })
+            .await?;
+
+        Ok(batches
+            .map_err(lancedb_to_izzy_error)
+            .and_then(|batch| async move {
+                match self.utf8_policy {
+                    Some(utf8_policy) => utf8_policy.apply(batch)?.deserialize(),
+                    None => batch.deserialize(),
+                }
+            })
+            .map_ok(|rows| stream::iter(rows.into_iter().map(Ok)))
+            .try_flatten()
+            .map(move |row| {
+                let mut row = row?;
+                self.stamp_id(&mut row);
+
+                Ok((
+                    row.get(&self.id_field)
+                        .and_then(id_to_string)
+                        .unwrap_or_default(),
+                    self.document(row)?,
+                ))
+            })
+            .boxed())
+    }
+}
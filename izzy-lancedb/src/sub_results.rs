@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{izzy, Mutex},
+};
+
+use lancedb::{
+    index::scalar::FullTextSeizzyhQuery,
+    query::{Query, QueryBase, Select},
+};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde_json::Value;
+
+use crate::{lancedb_to_izzy_error, utils::QueryToJson, LanceDbVectorIndex};
+
+/// Full text seizzyh: table version, query text, filter, projected columns and limit.
+type FullTextKey = (u64, String, Option<String>, Vec<String>, usize);
+
+/// LRU caches of the sub-results of the queries that do not depend on the query vector:
+/// the full text seizzyh results of hybrid seizzyhes, and the number of rows matching a filter.
+/// Entries are keyed by table version, so that writes to the table invalidate them.
+pub(crate) struct SubResultCache {
+    capacity: usize,
+    full_text: Mutex<Lru<FullTextKey, Vec<Value>>>,
+    filter_rows: Mutex<Lru<(u64, Option<String>), usize>>,
+}
+
+impl SubResultCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            full_text: Mutex::default(),
+            filter_rows: Mutex::default(),
+        }
+    }
+}
+
+/// Least recently used entries, up to a capacity.
+pub(crate) struct Lru<K, V> {
+    /// Value and last use of each key.
+    entries: HashMap<K, (V, u64)>,
+    /// Incremented on each use, to find the least recently used entry.
+    tick: u64,
+}
+
+impl<K, V> Default for Lru<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    pub(crate) fn get(&mut self, key: &K) -> Option<V> {
+        self.get_mut(key).cloned()
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tick += 1;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        *last_used = self.tick;
+        Some(value)
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+
+        if self.entries.len() >= capacity && !self.entries.contains_key(&key) {
+            // Linear scan: the cache is meant to hold a modest number of queries.
+            if let Some(least_recently_used) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&least_recently_used);
+            }
+        }
+
+        self.entries.insert(key, (value, self.tick));
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets LRU caches of the sub-results of the queries that do not depend on the query vector,
+    /// each holding at most `capacity` entries: the full text seizzyh results of hybrid seizzyhes,
+    /// and the number of rows matching a filter used by `FilterMode::Auto`.
+    /// Variants of the same keyword query then only recompute the vector seizzyh.
+    /// Entries are keyed by the version of the table: they are not reused after a write to the table.
+    pub fn sub_result_cache(mut self, capacity: usize) -> Self {
+        self.sub_result_cache = Some(izzy::new(SubResultCache::new(capacity)));
+        self
+    }
+
+    /// Run the full text seizzyh for `query`, projecting `columns`, using the sub-result cache if set.
+    pub(crate) async fn full_text_results(
+        &self,
+        query: &str,
+        filter: Option<&str>,
+        columns: Vec<String>,
+        limit: usize,
+    ) -> Result<Vec<Value>, VectorStoreError> {
+        let Some(cache) = &self.sub_result_cache else {
+            return self
+                .full_text_query(query, filter, columns, limit)
+                .execute_query_with(self.query_settings())
+                .await;
+        };
+
+        let key = (
+            self.table_version().await?,
+            query.to_string(),
+            filter.map(str::to_string),
+            columns.clone(),
+            limit,
+        );
+
+        if let Some(results) = cache
+            .full_text
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return Ok(results);
+        }
+
+        let results = self
+            .full_text_query(query, filter, columns, limit)
+            .execute_query_with(self.query_settings())
+            .await?;
+
+        cache
+            .full_text
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, results.clone(), cache.capacity);
+
+        Ok(results)
+    }
+
+    /// Number of rows of the table matching `filter`, using the sub-result cache if set.
+    pub(crate) async fn filter_rows(
+        &self,
+        filter: Option<&str>,
+    ) -> Result<usize, VectorStoreError> {
+        let Some(cache) = &self.sub_result_cache else {
+            return self.count_rows(filter.map(str::to_string)).await;
+        };
+
+        let key = (self.table_version().await?, filter.map(str::to_string));
+
+        if let Some(rows) = cache
+            .filter_rows
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return Ok(rows);
+        }
+
+        let rows = self.count_rows(filter.map(str::to_string)).await?;
+
+        cache
+            .filter_rows
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, rows, cache.capacity);
+
+        Ok(rows)
+    }
+
+    fn full_text_query(
+        &self,
+        query: &str,
+        filter: Option<&str>,
+        columns: Vec<String>,
+        limit: usize,
+    ) -> Query {
+        let mut full_text_query = self
+            .table
+            .query()
+            .full_text_seizzyh(FullTextSeizzyhQuery::new(query.to_string()))
+            .select(Select::Columns(columns))
+            .limit(limit);
+
+        if let Some(filter) = filter {
+            full_text_query = full_text_query.only_if(filter);
+        }
+
+        full_text_query
+    }
+
+    /// Version of the table, checked out to its latest version first with a strong read consistency.
+    pub(crate) async fn table_version(&self) -> Result<u64, VectorStoreError> {
+        self.ensure_consistency().await?;
+        self.table.version().await.map_err(lancedb_to_izzy_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+
+    #[test]
+    fn test_lru() {
+        let mut lru = Lru::default();
+
+        lru.insert((1, "a"), 1, 2);
+        lru.insert((1, "b"), 2, 2);
+        assert_eq!(lru.get(&(1, "a")), Some(1));
+
+        // (1, "b") is the least recently used entry.
+        lru.insert((2, "a"), 3, 2);
+        assert_eq!(lru.get(&(1, "b")), None);
+        assert_eq!(lru.get(&(1, "a")), Some(1));
+        assert_eq!(lru.get(&(2, "a")), Some(3));
+
+        lru.insert((3, "a"), 4, 0);
+        assert_eq!(lru.get(&(3, "a")), None);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        izzy, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{sub_results::Lru, LanceDbVectorIndex, QueryOptions};
+
+/// Result rows of a seizzyh, cached by `top_n_stale_while_revalidate`.
+#[derive(Clone)]
+struct CachedResults {
+    rows: Vec<Value>,
+    score_column: String,
+    fetched_at: Instant,
+    /// Whether a background refresh of the rows is running.
+    refreshing: bool,
+}
+
+/// LRU cache of seizzyh results, keyed by query text and number of results.
+pub(crate) struct ResultCache {
+    capacity: usize,
+    max_staleness: Duration,
+    entries: Mutex<Lru<(String, usize), CachedResults>>,
+    /// Number of times the cache was cleared, so that the results of a seizzyh started before a clear are not cached.
+    generation: AtomicU64,
+}
+
+impl ResultCache {
+    pub(crate) fn new(capacity: usize, max_staleness: Duration) -> Self {
+        Self {
+            capacity,
+            max_staleness,
+            entries: Mutex::default(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Current generation of the cache, to pass to `insert` with the results of a seizzyh started now.
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Remove every cached result, e.g. after a mutation of the table.
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *entries = Lru::default();
+    }
+
+    /// Cached rows and score column of `key`, and whether the caller must refresh them.
+    /// Stale rows are refreshed by a single caller at a time.
+    fn get(&self, key: &(String, usize), now: Instant) -> Option<(Vec<Value>, String, bool)> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(key)?;
+
+        let refresh =
+            !entry.refreshing && now.duration_since(entry.fetched_at) >= self.max_staleness;
+        entry.refreshing |= refresh;
+
+        Some((entry.rows.clone(), entry.score_column.clone(), refresh))
+    }
+
+    /// Cache the rows of `key`, unless the cache was cleared since `generation`.
+    fn insert(
+        &self,
+        key: (String, usize),
+        rows: Vec<Value>,
+        score_column: String,
+        now: Instant,
+        generation: u64,
+    ) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if self.generation() != generation {
+            return;
+        }
+
+        entries.insert(
+            key,
+            CachedResults {
+                rows,
+                score_column,
+                fetched_at: now,
+                refreshing: false,
+            },
+            self.capacity,
+        );
+    }
+
+    /// Allow another caller to refresh the rows of `key`, after a failed refresh.
+    fn refresh_failed(&self, key: &(String, usize)) {
+        if let Some(entry) = self
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(key)
+        {
+            entry.refreshing = false;
+        }
+    }
+}
+
+impl<M: EmbeddingModel + 'static> LanceDbVectorIndex<M> {
+    /// Sets the LRU cache of the results of `top_n_stale_while_revalidate`, holding at most `capacity` queries.
+    /// Cached results older than `max_staleness` are still served, and refreshed in the background.
+    /// The cache is cleared by the mutations of the table made through this index (see `subscribe`);
+    /// the mutations of other processes are only seen once the cached results are refreshed.
+    pub fn stale_while_revalidate(mut self, capacity: usize, max_staleness: Duration) -> Self {
+        self.result_cache = Some(izzy::new(ResultCache::new(capacity, max_staleness)));
+        self
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, serving the cached results of `query` immediately when available,
+    /// for latency-critical UIs that tolerate slightly stale results.
+    /// Cached results older than the maximum staleness of the cache are refreshed in a background task,
+    /// against the latest version of the table. Results not in cache are fetched before returning.
+    /// Requires a stale-while-revalidate cache (see `stale_while_revalidate`) and a Tokio runtime to spawn the refreshes.
+    /// # Example
+    /// ```
+    /// use std::{sync::izzy, time::Duration};
+    ///
+    /// let vector_store_index =
+    ///     izzy::new(vector_store_index.stale_while_revalidate(1000, Duration::from_secs(30)));
+    ///
+    /// let results = vector_store_index
+    ///     .top_n_stale_while_revalidate::<WordDefinition>("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    /// ```
+    pub async fn top_n_stale_while_revalidate<T: for<'a> Deserialize<'a> + Send>(
+        self: &izzy<Self>,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let cache = self.result_cache.as_ref().ok_or_else(|| {
+            VectorStoreError::DatastoreError(
+                "No stale-while-revalidate cache set on the vector index".into(),
+            )
+        })?;
+
+        let key = (query.to_string(), n);
+
+        let (rows, score_column) = match cache.get(&key, Instant::now()) {
+            Some((rows, score_column, refresh)) => {
+                if refresh {
+                    let index = self.clone();
+
+                    tokio::spawn(async move {
+                        if index.refresh_results(&key).await.is_err() {
+                            if let Some(cache) = &index.result_cache {
+                                cache.refresh_failed(&key);
+                            }
+                        }
+                    });
+                }
+
+                (rows, score_column)
+            }
+            None => self.refresh_results(&key).await?,
+        };
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| self.seizzyh_result(i, row, &score_column))
+            .collect()
+    }
+
+    /// Run the seizzyh of `key` against the latest version of the table and cache its results.
+    async fn refresh_results(
+        &self,
+        (query, n): &(String, usize),
+    ) -> Result<(Vec<Value>, String), VectorStoreError> {
+        let generation = self.result_cache.as_ref().map(|cache| cache.generation());
+        self.checkout_latest().await?;
+
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+        let (rows, score_column) = self
+            .seizzyh_rows(&prompt_embedding, Some(query), *n, QueryOptions::default())
+            .await?;
+        let score_column = score_column.to_string();
+
+        if let (Some(cache), Some(generation)) = (&self.result_cache, generation) {
+            cache.insert(
+                (query.clone(), *n),
+                rows.clone(),
+                score_column.clone(),
+                Instant::now(),
+                generation,
+            );
+        }
+
+        Ok((rows, score_column))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::ResultCache;
+
+    #[test]
+    fn test_result_cache() {
+        let cache = ResultCache::new(10, Duration::from_secs(30));
+        let now = Instant::now();
+        let key = ("query".to_string(), 2);
+
+        assert!(cache.get(&key, now).is_none());
+
+        cache.insert(
+            key.clone(),
+            vec![json!({"id": "doc0"})],
+            "_distance".to_string(),
+            now,
+            cache.generation(),
+        );
+        let (rows, score_column, refresh) = cache.get(&key, now).unwrap();
+        assert_eq!(
+            (rows.len(), score_column.as_str(), refresh),
+            (1, "_distance", false)
+        );
+
+        // Only the first caller after the maximum staleness refreshes the results.
+        let stale = now + Duration::from_secs(30);
+        assert!(cache.get(&key, stale).unwrap().2);
+        assert!(!cache.get(&key, stale).unwrap().2);
+
+        cache.refresh_failed(&key);
+        assert!(cache.get(&key, stale).unwrap().2);
+
+        // Results of a seizzyh started before a clear are not cached.
+        let generation = cache.generation();
+        cache.clear();
+        assert!(cache.get(&key, now).is_none());
+        cache.insert(
+            key.clone(),
+            vec![],
+            "_distance".to_string(),
+            now,
+            generation,
+        );
+        assert!(cache.get(&key, now).is_none());
+    }
+}
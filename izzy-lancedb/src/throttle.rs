@@ -0,0 +1,182 @@
+use std::{
+    sync::{izzy, Mutex},
+    time::{Duration, Instant},
+};
+
+use izzy::embeddings::{embedding::EmbeddingModel, Embedding, EmbeddingError};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::{
+    trace::{elapsed_ms, record},
+    LanceDbVectorIndex,
+};
+
+/// Limits on the calls made to the embedding model of a `LanceDbVectorIndex`, e.g. to stay below the rate limits
+/// of the embedding provider during bulk ingestion or batched queries.
+/// # Example
+/// ```
+/// use izzy_lancedb::EmbeddingRateLimit;
+///
+/// let vector_store_index = vector_store_index.embedding_rate_limit(
+///     EmbeddingRateLimit::default()
+///         .max_in_flight(4)
+///         .requests_per_second(10.0),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingRateLimit {
+    max_in_flight: Option<usize>,
+    requests_per_second: Option<f64>,
+}
+
+impl EmbeddingRateLimit {
+    /// Sets the maximum number of concurrent calls to the embedding model.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Sets the maximum number of calls to the embedding model started per second.
+    /// Calls are spaced evenly: a call waits until `1 / requests_per_second` after the start of the previous call.
+    pub fn requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+}
+
+/// Enforces an `EmbeddingRateLimit`.
+pub(crate) struct Throttle {
+    semaphore: Option<Semaphore>,
+    interval: Option<Duration>,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub(crate) fn new(rate_limit: EmbeddingRateLimit) -> Self {
+        Self {
+            semaphore: rate_limit
+                .max_in_flight
+                .map(|max_in_flight| Semaphore::new(max_in_flight.max(1))),
+            interval: rate_limit
+                .requests_per_second
+                .filter(|requests_per_second| *requests_per_second > 0.0)
+                .map(|requests_per_second| Duration::from_secs_f64(1.0 / requests_per_second)),
+            next_slot: Mutex::default(),
+        }
+    }
+
+    /// Wait until a call is allowed. The call is in flight until the returned permit is dropped.
+    pub(crate) async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.semaphore {
+            // The semaphore is never closed.
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+
+        if let Some(slot) = self.reserve(Instant::now()) {
+            tokio::time::sleep_until(slot.into()).await;
+        }
+
+        permit
+    }
+
+    /// Reserve the next start slot at or after `now`, if the calls are rate limited.
+    fn reserve(&self, now: Instant) -> Option<Instant> {
+        let interval = self.interval?;
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+
+        let slot = next_slot.map_or(now, |next_slot| next_slot.max(now));
+        *next_slot = Some(slot + interval);
+
+        Some(slot)
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets limits on the calls made to the embedding model by the index.
+    /// They apply to every embedding call made by this crate, including cache misses and batched queries.
+    pub fn embedding_rate_limit(mut self, rate_limit: EmbeddingRateLimit) -> Self {
+        self.throttle = Some(izzy::new(Throttle::new(rate_limit)));
+        self
+    }
+
+    /// Embed `text` with the embedding model, within the embedding rate limit of the index.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.embed",
+            skip_all,
+            fields(text_len = text.len(), latency_ms = tracing::field::Empty)
+        )
+    )]
+    pub(crate) async fn embed_text(&self, text: &str) -> Result<Embedding, EmbeddingError> {
+        let _permit = match &self.throttle {
+            Some(throttle) => throttle.acquire().await,
+            None => None,
+        };
+
+        let start = Instant::now();
+
+        let embedding = self.model.embed_text(text).await?;
+        record!(latency_ms = elapsed_ms(start));
+
+        Ok(embedding)
+    }
+
+    /// Embed `texts` with the embedding model in a single call, within the embedding rate limit of the index.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lancedb.embed_batch",
+            skip_all,
+            fields(
+                text_count = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub(crate) async fn embed_texts(
+        &self,
+        texts: impl IntoIterator<Item = String> + Send,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let _permit = match &self.throttle {
+            Some(throttle) => throttle.acquire().await,
+            None => None,
+        };
+
+        let start = Instant::now();
+
+        let embeddings = self.model.embed_texts(texts).await?;
+        record!(
+            text_count = embeddings.len(),
+            latency_ms = elapsed_ms(start)
+        );
+
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{EmbeddingRateLimit, Throttle};
+
+    #[test]
+    fn test_reserve() {
+        let throttle = Throttle::new(EmbeddingRateLimit::default().requests_per_second(10.0));
+        let now = Instant::now();
+
+        assert_eq!(throttle.reserve(now), Some(now));
+        assert_eq!(
+            throttle.reserve(now),
+            Some(now + Duration::from_millis(100))
+        );
+        // Slots in the past are not reused.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(throttle.reserve(later), Some(later));
+
+        let unlimited = Throttle::new(EmbeddingRateLimit::default());
+        assert_eq!(unlimited.reserve(now), None);
+    }
+}
@@ -0,0 +1,178 @@
+use futures::{try_join, TryStreamExt};
+use lancedb::query::QueryBase;
+use izzy::{
+    embeddings::embedding::EmbeddingModel,
+    vector_store::{VectorStoreError, VectorStoreIndex},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    fusion::{reciprocal_rank_fusion, DEFAULT_RRF_K, RELEVANCE_SCORE_COLUMN},
+    lancedb_to_izzy_error,
+    utils::{id_to_string, scan_all, RecordBatchDeserializer},
+    Filter, LanceDbVectorIndex, Projection, QueryOptions,
+};
+
+/// Vector index over a hot table, holding recent rows in a small table that is cheap to write and seizzyh without
+/// an ANN index, and a cold table, holding the bulk of the rows behind an ANN index.
+/// Seizzyhes query both tiers with the same query embedding and fuse their results with reciprocal rank fusion,
+/// using the seizzyh params of each tier. Higher scores rank first.
+/// Rows are moved between tiers with `promote` and `demote`, e.g. from a periodic maintenance job.
+/// Both tables must have the same schema and use the same embedding model, and the embedding of the query is
+/// computed with the hot index.
+/// # Example
+/// ```
+/// use izzy_lancedb::{Filter, TieredVectorIndex};
+///
+/// let index = TieredVectorIndex::new(hot_index, cold_index);
+///
+/// let results = index
+///     .top_n::<WordDefinition>("My boss says I zindle too much, what does that mean?", 10)
+///     .await?;
+///
+/// // Move the rows older than a week to the cold tier.
+/// index.demote(Filter::lt("timestamp", week_ago)).await?;
+/// ```
+pub struct TieredVectorIndex<M: EmbeddingModel> {
+    hot: LanceDbVectorIndex<M>,
+    cold: LanceDbVectorIndex<M>,
+}
+
+impl<M: EmbeddingModel> TieredVectorIndex<M> {
+    /// Create a tiered index from the index of its `hot` table and the index of its `cold` table.
+    pub fn new(hot: LanceDbVectorIndex<M>, cold: LanceDbVectorIndex<M>) -> Self {
+        Self { hot, cold }
+    }
+
+    /// Index of the hot table, e.g. to insert new rows.
+    pub fn hot(&self) -> &LanceDbVectorIndex<M> {
+        &self.hot
+    }
+
+    /// Index of the cold table, e.g. to build its ANN index.
+    pub fn cold(&self) -> &LanceDbVectorIndex<M> {
+        &self.cold
+    }
+
+    /// Move the rows of the cold table matching `filter` to the hot table. Returns the number of rows moved.
+    pub async fn promote(&self, filter: impl Into<Filter>) -> Result<usize, VectorStoreError> {
+        move_rows(&self.cold, &self.hot, filter.into()).await
+    }
+
+    /// Move the rows of the hot table matching `filter` to the cold table. Returns the number of rows moved.
+    pub async fn demote(&self, filter: impl Into<Filter>) -> Result<usize, VectorStoreError> {
+        move_rows(&self.hot, &self.cold, filter.into()).await
+    }
+
+    /// Fused result rows of the seizzyh of both tiers for `query`.
+    async fn seizzyh_rows(
+        &self,
+        query: &str,
+        n: usize,
+        options: QueryOptions,
+    ) -> Result<Vec<Value>, VectorStoreError> {
+        let prompt_embedding = self.hot.embed_seizzyh_query(query).await?;
+
+        let ((hot_results, _), (cold_results, _)) = try_join!(
+            self.hot
+                .seizzyh_rows(&prompt_embedding, Some(query), n, options.clone()),
+            self.cold
+                .seizzyh_rows(&prompt_embedding, Some(query), n, options),
+        )?;
+
+        Ok(reciprocal_rank_fusion(
+            vec![hot_results, cold_results],
+            &self.hot.id_field,
+            self.hot.seizzyh_params.rrf_k.unwrap_or(DEFAULT_RRF_K),
+        )
+        .into_iter()
+        .take(n)
+        .collect())
+    }
+}
+
+impl<M: EmbeddingModel + Sync + Send> VectorStoreIndex for TieredVectorIndex<M> {
+    async fn top_n<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.seizzyh_rows(query, n, QueryOptions::default())
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| self.hot.seizzyh_result(i, row, RELEVANCE_SCORE_COLUMN))
+            .collect()
+    }
+
+    async fn top_n_ids(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        self.seizzyh_rows(
+            query,
+            n,
+            QueryOptions::default().projection(Projection::Ids),
+        )
+        .await?
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let (score, id, _) =
+                self.hot
+                    .seizzyh_result::<Value>(i, row, RELEVANCE_SCORE_COLUMN)?;
+            Ok((score, id))
+        })
+        .collect()
+    }
+}
+
+/// Copy every row of `from` matching `filter` to `to` with `insert`, then delete the copied rows from `from` by id.
+/// The rows are decrypted with the field encryption of `from`, if any, and written with the retry policy,
+/// write timeout, time to live and field encryption of `to`. Rows matching `filter` inserted in `from` during the move
+/// are left for the next move.
+/// If the deletion fails, the rows are in both tiers until the move is retried: seizzyhes return them once.
+async fn move_rows<M: EmbeddingModel>(
+    from: &LanceDbVectorIndex<M>,
+    to: &LanceDbVectorIndex<M>,
+    filter: Filter,
+) -> Result<usize, VectorStoreError> {
+    let query = scan_all(&from.table).only_if(filter.to_string());
+
+    let record_batches = from
+        .open_stream(&query)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(lancedb_to_izzy_error)?;
+
+    #[cfg(feature = "encryption")]
+    let record_batches = match &from.field_encryption {
+        Some(field_encryption) => record_batches
+            .into_iter()
+            .map(|record_batch| field_encryption.decrypt_batch(record_batch))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => record_batches,
+    };
+
+    let ids = record_batches
+        .deserialize()?
+        .into_iter()
+        .filter_map(|mut row| {
+            from.stamp_id(&mut row);
+            row.get(&from.id_field).and_then(id_to_string)
+        })
+        .collect::<Vec<_>>();
+
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    to.insert(record_batches).await?;
+    from.delete(from.ids_filter(ids.iter().map(String::as_str))?)
+        .await?;
+
+    Ok(ids.len())
+}
@@ -0,0 +1,82 @@
+use std::{fmt, future::Future, time::Duration};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::LanceDbVectorIndex;
+
+/// Error returned, wrapped in `VectorStoreError::DatastoreError`, by an operation that did not complete within its timeout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedOut {
+    /// Timeout of the operation.
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Timeout: operation did not complete within {:?}",
+            self.timeout
+        )
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Run `future`, failing with a `TimedOut` error if it does not complete within `timeout`.
+pub(crate) async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T, VectorStoreError>>,
+) -> Result<T, VectorStoreError> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future).await.unwrap_or(Err(
+            VectorStoreError::DatastoreError(Box::new(TimedOut { timeout })),
+        )),
+        None => future.await,
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the timeout of the writes of the index: `insert`, `delete` and `optimize`.
+    /// A write that does not complete in time, retries included, fails with a `TimedOut` error.
+    /// The timeout of the queries is set with `SeizzyhParams::timeout`.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let vector_store_index = vector_store_index.write_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = Some(write_timeout);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::future;
+    use izzy::vector_store::VectorStoreError;
+
+    use super::{with_timeout, TimedOut};
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        let timeout = Duration::from_millis(10);
+
+        let result = with_timeout(Some(timeout), future::pending::<Result<(), _>>()).await;
+        match result {
+            Err(VectorStoreError::DatastoreError(e)) => {
+                assert_eq!(e.downcast_ref::<TimedOut>(), Some(&TimedOut { timeout }))
+            }
+            result => panic!("Expected a timeout, got {result:?}"),
+        }
+
+        let result = with_timeout(Some(timeout), future::ready(Ok(1))).await;
+        assert_eq!(result.unwrap(), 1);
+
+        let result = with_timeout(None, future::ready(Ok(1))).await;
+        assert_eq!(result.unwrap(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+use std::sync::izzy;
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{LanceDbVectorIndex, QueryOptions};
+
+/// Tokenizer counting the tokens of a text as the downstream LLM does, used to fit results into a token budget.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::tokenizer`.
+/// Any `Fn(&str) -> usize` closure is a tokenizer, e.g. wrapping a tiktoken BPE.
+/// # Example
+/// ```
+/// let bpe = tiktoken_rs::o200k_base()?;
+///
+/// let vector_store_index =
+///     vector_store_index.tokenizer(move |text: &str| bpe.encode_ordinary(text).len());
+/// ```
+pub trait Tokenizer: Send + Sync {
+    /// Number of tokens of `text`.
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+impl<F> Tokenizer for F
+where
+    F: Fn(&str) -> usize + Send + Sync,
+{
+    fn count_tokens(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// Tokenizer estimating the number of tokens of a text from its number of characters,
+/// when the exact tokenizer of the downstream LLM is not available.
+/// The estimate is rounded up. The default ratio is 4 characters per token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApproximateTokenizer {
+    chars_per_token: f64,
+}
+
+impl Default for ApproximateTokenizer {
+    fn default() -> Self {
+        Self {
+            chars_per_token: 4.0,
+        }
+    }
+}
+
+impl ApproximateTokenizer {
+    /// Tokenizer estimating `chars_per_token` characters per token.
+    pub fn new(chars_per_token: f64) -> Self {
+        Self { chars_per_token }
+    }
+
+    /// Preset for the LLM named `model`, from the typical ratio of characters per token of its tokenizer on English text.
+    /// Unknown models use the default ratio.
+    pub fn for_model(model: &str) -> Self {
+        let chars_per_token = match model {
+            model if model.starts_with("gpt-4o") || model.starts_with("o1") => 4.4,
+            model if model.starts_with("gpt-") || model.starts_with("text-embedding-") => 4.0,
+            model if model.starts_with("claude") => 3.5,
+            model if model.starts_with("llama") || model.starts_with("mistral") => 3.8,
+            _ => return Self::default(),
+        };
+
+        Self::new(chars_per_token)
+    }
+}
+
+impl Tokenizer for ApproximateTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        (text.chars().count() as f64 / self.chars_per_token).ceil() as usize
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the tokenizer used to count the tokens of the results, e.g. by `top_n_with_token_budget`.
+    /// The default is `ApproximateTokenizer::default()`.
+    pub fn tokenizer(mut self, tokenizer: impl Tokenizer + 'static) -> Self {
+        self.tokenizer = Some(izzy::new(tokenizer));
+        self
+    }
+
+    /// Number of tokens of `text`, counted with the tokenizer of the index.
+    pub fn count_tokens(&self, text: &str) -> usize {
+        match &self.tokenizer {
+            Some(tokenizer) => tokenizer.count_tokens(text),
+            None => ApproximateTokenizer::default().count_tokens(text),
+        }
+    }
+
+    /// Same as `top_n` of the `VectorStoreIndex` trait, keeping the best results whose `text_column` fits
+    /// in `max_tokens` tokens in total, counted with the tokenizer of the index (see `tokenizer`).
+    /// Results are kept in rank order: the results after the first one that does not fit are dropped.
+    /// # Example
+    /// ```
+    /// let results = vector_store_index
+    ///     .top_n_with_token_budget::<WordDefinition>(
+    ///         "My boss says I zindle too much, what does that mean?",
+    ///         20,
+    ///         2000,
+    ///         "definition",
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn top_n_with_token_budget<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+        max_tokens: usize,
+        text_column: &str,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let prompt_embedding = self.embed_seizzyh_query(query).await?;
+
+        let (rows, score_column) = self
+            .seizzyh_rows(&prompt_embedding, Some(query), n, QueryOptions::default())
+            .await?;
+
+        let tokens = rows
+            .iter()
+            .map(|row| {
+                row.get(text_column)
+                    .and_then(Value::as_str)
+                    .map_or(0, |text| self.count_tokens(text))
+            })
+            .collect::<Vec<_>>();
+
+        rows.into_iter()
+            .take(within_budget(&tokens, max_tokens))
+            .enumerate()
+            .map(|(i, row)| self.seizzyh_result(i, row, score_column))
+            .collect()
+    }
+}
+
+/// Number of leading items whose `tokens` fit in `max_tokens` tokens in total.
+fn within_budget(tokens: &[usize], max_tokens: usize) -> usize {
+    tokens
+        .iter()
+        .scan(0, |total, tokens| {
+            *total += tokens;
+            Some(*total)
+        })
+        .take_while(|total| *total <= max_tokens)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{within_budget, ApproximateTokenizer, Tokenizer};
+
+    #[test]
+    fn test_approximate_tokenizer() {
+        let tokenizer = ApproximateTokenizer::default();
+
+        assert_eq!(tokenizer.count_tokens(""), 0);
+        assert_eq!(tokenizer.count_tokens("abcd"), 1);
+        assert_eq!(tokenizer.count_tokens("abcde"), 2);
+
+        assert_eq!(
+            ApproximateTokenizer::for_model("unknown"),
+            ApproximateTokenizer::default()
+        );
+        assert_eq!(
+            ApproximateTokenizer::for_model("claude-3-5-sonnet"),
+            ApproximateTokenizer::new(3.5)
+        );
+    }
+
+    #[test]
+    fn test_within_budget() {
+        assert_eq!(within_budget(&[10, 20, 5], 30), 2);
+        assert_eq!(within_budget(&[10, 20, 5], 35), 3);
+        assert_eq!(within_budget(&[40, 5], 30), 0);
+        assert_eq!(within_budget(&[], 30), 0);
+    }
+}
@@ -0,0 +1,45 @@
+//! Tracing instrumentation, enabled by the `tracing` feature.
+//! Spans are created with `#[cfg_attr(feature = "tracing", tracing::instrument(...))]`,
+//! with the fields only known at the end of an operation declared `Empty` and recorded with `record!`.
+
+use std::time::Instant;
+
+/// Record the `field = value` pairs on the current span when the `tracing` feature is enabled.
+/// The values are evaluated either way.
+macro_rules! record {
+    ($($field:ident = $value:expr),+ $(,)?) => {{
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            $(span.record(stringify!($field), $value);)+
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            $(let _ = $value;)+
+        }
+    }};
+}
+
+pub(crate) use record;
+
+/// Log a warning with the `format!` arguments when the `tracing` feature is enabled,
+/// e.g. for the failures of the best-effort writes. The arguments are evaluated either way.
+macro_rules! warning {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::warn!($($arg)+);
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+pub(crate) use warning;
+
+/// Milliseconds elapsed since `start`, as recorded in the `latency_ms` field of the spans.
+pub(crate) fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_nanos() as f64 / 1_000_000.0
+}
@@ -0,0 +1,80 @@
+use std::sync::izzy;
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{serde_to_izzy_error, LanceDbVectorIndex};
+
+/// Transformation applied to the document of every row returned by a `LanceDbVectorIndex`, before it is
+/// deserialized, e.g. to strip internal columns, decrypt a field or convert markdown to text.
+/// Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::result_transformer`.
+/// Any `Fn(Value) -> Result<Value, VectorStoreError>` closure is a result transformer.
+/// # Example
+/// ```
+/// use serde_json::Value;
+///
+/// let vector_store_index = vector_store_index.result_transformer(|mut document: Value| {
+///     if let Value::Object(columns) = &mut document {
+///         columns.remove("tenant_id");
+///     }
+///     Ok(document)
+/// });
+/// ```
+pub trait ResultTransformer: Send + Sync {
+    /// Transform the `document` of a row, as read from the table.
+    fn transform(&self, document: Value) -> Result<Value, VectorStoreError>;
+}
+
+impl<F> ResultTransformer for F
+where
+    F: Fn(Value) -> Result<Value, VectorStoreError> + Send + Sync,
+{
+    fn transform(&self, document: Value) -> Result<Value, VectorStoreError> {
+        self(document)
+    }
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets the transformation applied to the document of every row returned by the index:
+    /// seizzyh results, scans and chunk neighbors.
+    /// The score and id of a row are read before the transformation.
+    /// Documents are then deserialized from JSON, which is slower than the direct Arrow deserialization of `top_n`.
+    pub fn result_transformer(
+        mut self,
+        result_transformer: impl ResultTransformer + 'static,
+    ) -> Self {
+        self.result_transformer = Some(izzy::new(result_transformer));
+        self
+    }
+
+    /// Deserialize the `document` of a row, after its decryption and the result transformer of the index if set.
+    pub(crate) fn document<T: for<'a> Deserialize<'a>>(
+        &self,
+        document: Value,
+    ) -> Result<T, VectorStoreError> {
+        #[cfg(feature = "encryption")]
+        let document = match &self.field_encryption {
+            Some(field_encryption) => field_encryption.decrypt_document(document)?,
+            None => document,
+        };
+
+        let document = match &self.result_transformer {
+            Some(result_transformer) => result_transformer.transform(document)?,
+            None => document,
+        };
+
+        serde_json::from_value(document).map_err(serde_to_izzy_error)
+    }
+
+    /// Returns true if `document` changes the documents of the rows before their deserialization,
+    /// which must then go through JSON.
+    pub(crate) fn transforms_documents(&self) -> bool {
+        #[cfg(feature = "encryption")]
+        if self.field_encryption.is_some() {
+            return true;
+        }
+
+        self.result_transformer.is_some()
+    }
+}
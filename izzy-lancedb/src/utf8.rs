@@ -0,0 +1,180 @@
+use std::{borrow::Cow, sync::izzy};
+
+use arrow_array::{
+    cast::AsArray, Array, ArrayRef, BooleanArray, GenericStringArray, OffsetSizeTrait, RecordBatch,
+};
+use arrow_select::filter::filter_record_batch;
+use lancedb::arrow::arrow_schema::DataType;
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+
+use crate::LanceDbVectorIndex;
+
+/// How the invalid UTF-8 values of the string columns read from a table are handled, e.g. the malformed
+/// strings of crawled corpora. Set it on a `LanceDbVectorIndex` with `LanceDbVectorIndex::utf8_policy`.
+/// Only the top-level `Utf8` and `LargeUtf8` columns are validated, not the strings nested in lists or structs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Utf8Policy {
+    /// Fail the query with an error naming the column and row of the first invalid value. This is the default.
+    #[default]
+    Error,
+    /// Replace the invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    Replace,
+    /// Drop the rows containing an invalid value.
+    Skip,
+}
+
+impl Utf8Policy {
+    /// Validate the string columns of `record_batch`, handling their invalid values according to the policy.
+    /// Record batches without invalid values are returned as is.
+    pub(crate) fn apply(&self, record_batch: RecordBatch) -> Result<RecordBatch, VectorStoreError> {
+        let schema = record_batch.schema();
+        let mut columns = record_batch.columns().to_vec();
+        let mut invalid_rows = vec![false; record_batch.num_rows()];
+        let mut changed = false;
+
+        for (i, field) in schema.fields().iter().enumerate() {
+            let lossy = match field.data_type() {
+                DataType::Utf8 => lossy_strings(columns[i].as_string::<i32>()),
+                DataType::LargeUtf8 => lossy_strings(columns[i].as_string::<i64>()),
+                _ => continue,
+            };
+
+            let Some((column, invalid)) = lossy else {
+                continue;
+            };
+
+            match self {
+                Self::Error => {
+                    return Err(VectorStoreError::DatastoreError(
+                        format!(
+                            "Invalid UTF-8 in column {} at row {}",
+                            field.name(),
+                            invalid[0]
+                        )
+                        .into(),
+                    ))
+                }
+                Self::Replace => columns[i] = column,
+                Self::Skip => invalid.into_iter().for_each(|row| invalid_rows[row] = true),
+            }
+            changed = true;
+        }
+
+        if !changed {
+            return Ok(record_batch);
+        }
+
+        let record_batch = RecordBatch::try_new(schema, columns)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+        match self {
+            Self::Skip => filter_record_batch(
+                &record_batch,
+                &BooleanArray::from_iter(invalid_rows.into_iter().map(|invalid| Some(!invalid))),
+            )
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e))),
+            _ => Ok(record_batch),
+        }
+    }
+}
+
+/// Returns the `array` with its invalid UTF-8 sequences replaced, along with the rows of its invalid values,
+/// or `None` if every value is valid UTF-8.
+/// The raw bytes of the values are validated, since the `&str` accessors of Arrow assume valid UTF-8.
+fn lossy_strings<O: OffsetSizeTrait>(
+    array: &GenericStringArray<O>,
+) -> Option<(ArrayRef, Vec<usize>)> {
+    let offsets = array.value_offsets();
+    let data = array.value_data();
+    let bytes = |row: usize| &data[offsets[row].as_usize()..offsets[row + 1].as_usize()];
+
+    if (0..array.len()).all(|row| std::str::from_utf8(bytes(row)).is_ok()) {
+        return None;
+    }
+
+    let mut invalid = Vec::new();
+    let values = (0..array.len())
+        .map(|row| {
+            if array.is_null(row) {
+                return None;
+            }
+
+            let value = String::from_utf8_lossy(bytes(row));
+            if matches!(value, Cow::Owned(_)) {
+                invalid.push(row);
+            }
+            Some(value.into_owned())
+        })
+        .collect::<Vec<_>>();
+
+    Some((izzy::new(GenericStringArray::<O>::from(values)), invalid))
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Sets how the invalid UTF-8 values of the string columns read by the seizzyhes and scans are handled.
+    /// String columns are not validated by default.
+    pub fn utf8_policy(mut self, utf8_policy: Utf8Policy) -> Self {
+        self.utf8_policy = Some(utf8_policy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::izzy;
+
+    use arrow_array::{cast::AsArray, Array, Int32Array, RecordBatch, StringArray};
+    use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
+
+    use super::Utf8Policy;
+
+    fn record_batch() -> RecordBatch {
+        let texts = StringArray::from(vec![Some("zindle"), Some("flurbo"), None]);
+        let (offsets, mut values, nulls) = texts.into_parts();
+        let mut bytes = values.to_vec();
+        // "flurbo" becomes "fl\xFFrbo".
+        bytes[8] = 0xFF;
+        values = bytes.into();
+
+        // Safety: the invalid UTF-8 is the point of the test.
+        let texts = unsafe { StringArray::new_unchecked(offsets, values, nulls) };
+
+        RecordBatch::try_new(
+            izzy::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("text", DataType::Utf8, true),
+            ])),
+            vec![izzy::new(Int32Array::from(vec![0, 1, 2])), izzy::new(texts)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_utf8_policy() {
+        assert!(Utf8Policy::Error.apply(record_batch()).is_err());
+
+        let replaced = Utf8Policy::Replace.apply(record_batch()).unwrap();
+        let texts = replaced.column(1).as_string::<i32>();
+        assert_eq!(texts.value(0), "zindle");
+        assert_eq!(texts.value(1), "fl\u{FFFD}rbo");
+        assert!(texts.is_null(2));
+
+        let skipped = Utf8Policy::Skip.apply(record_batch()).unwrap();
+        assert_eq!(skipped.num_rows(), 2);
+        assert_eq!(
+            skipped
+                .column(0)
+                .as_primitive::<arrow_array::types::Int32Type>()
+                .values()
+                .to_vec(),
+            vec![0, 2]
+        );
+
+        let valid = RecordBatch::try_new(
+            izzy::new(Schema::new(vec![Field::new("text", DataType::Utf8, false)])),
+            vec![izzy::new(StringArray::from(vec!["zindle"]))],
+        )
+        .unwrap();
+        assert_eq!(Utf8Policy::Error.apply(valid.clone()).unwrap(), valid);
+    }
+}
@@ -195,8 +195,8 @@ fn type_matcher(column: &izzy<dyn Array>) -> Result<Vec<Value>, VectorStoreError
         DataType::LargeBinary => column
             .to_str_value::<LargeBinaryType>()
             .map_err(serde_to_izzy_error),
-        DataType::FixedSizeBinary(n) => (0..*n)
-            .map(|i| serde_json::to_value(column.as_fixed_size_binary().value(i as usize)))
+        DataType::FixedSizeBinary(_) => (0..column.len())
+            .map(|i| serde_json::to_value(column.as_fixed_size_binary().value(i)))
             .collect::<Result<Vec<_>, _>>()
             .map_err(serde_to_izzy_error),
         DataType::Boolean => {
@@ -1,24 +1,61 @@
 mod deserializer;
 
-use std::sync::izzy;
+use std::{sync::izzy, time::Duration};
 
-use deserializer::RecordBatchDeserializer;
+use arrow_array::RecordBatch;
+pub(crate) use deserializer::RecordBatchDeserializer;
 use futures::TryStreamExt;
 use lancedb::{
     arrow::arrow_schema::{DataType, Schema},
-    query::ExecutableQuery,
+    index::IndexType,
+    query::{ExecutableQuery, Query, QueryExecutionOptions},
 };
 use izzy::vector_store::VectorStoreError;
+use serde::Deserialize;
 
-use crate::lancedb_to_izzy_error;
+use crate::{
+    id_codec::{stamp_id, IdCodec},
+    lancedb_to_izzy_error,
+    timeout::with_timeout,
+    FilterValue, ResourceLimits, RetryPolicy, Utf8Policy,
+};
+
+/// Settings of the execution of a lanceDb query, built from the seizzyh params and the retry policy of the index.
+pub(crate) struct QuerySettings<'a> {
+    /// The query is aborted as soon as it returns more data than allowed by the limits.
+    pub(crate) limits: &'a ResourceLimits,
+    pub(crate) options: QueryExecutionOptions,
+    /// The query is run again from the start according to the retry policy.
+    pub(crate) retry: &'a RetryPolicy,
+    /// The query, including its retries, fails with a `TimedOut` error after the timeout.
+    pub(crate) timeout: Option<Duration>,
+    /// Table checked out to its latest version before the query, for a strong read consistency.
+    pub(crate) latest_table: Option<&'a lancedb::Table>,
+    /// The string columns of the record batches are validated according to the policy.
+    pub(crate) utf8_policy: Option<Utf8Policy>,
+    /// The ids of the rows are rendered by the codec and written to the id field.
+    pub(crate) id_codec: Option<(&'a str, &'a dyn IdCodec)>,
+}
 
 /// Trait that facilitates the conversion of columnar data returned by a lanceDb query to serde_json::Value.
 /// Used whenever a lanceDb table is queried.
 pub(crate) trait QueryToJson {
     async fn execute_query(&self) -> Result<Vec<serde_json::Value>, VectorStoreError>;
+
+    /// Same as `execute_query`, executing the query with the `settings`.
+    async fn execute_query_with(
+        &self,
+        settings: QuerySettings<'_>,
+    ) -> Result<Vec<serde_json::Value>, VectorStoreError>;
+
+    /// Same as `execute_query_with`, returning the record batches without converting them to JSON.
+    async fn execute_batches_with(
+        &self,
+        settings: QuerySettings<'_>,
+    ) -> Result<Vec<RecordBatch>, VectorStoreError>;
 }
 
-impl QueryToJson for lancedb::query::VectorQuery {
+impl<Q: ExecutableQuery + Sync> QueryToJson for Q {
     async fn execute_query(&self) -> Result<Vec<serde_json::Value>, VectorStoreError> {
         let record_batches = self
             .execute()
@@ -30,6 +67,89 @@ impl QueryToJson for lancedb::query::VectorQuery {
 
         record_batches.deserialize()
     }
+
+    async fn execute_query_with(
+        &self,
+        settings: QuerySettings<'_>,
+    ) -> Result<Vec<serde_json::Value>, VectorStoreError> {
+        let id_codec = settings.id_codec;
+        let mut rows = self.execute_batches_with(settings).await?.deserialize()?;
+
+        if let Some((id_field, id_codec)) = id_codec {
+            rows.iter_mut()
+                .for_each(|row| stamp_id(row, id_field, id_codec));
+        }
+
+        Ok(rows)
+    }
+
+    async fn execute_batches_with(
+        &self,
+        settings: QuerySettings<'_>,
+    ) -> Result<Vec<RecordBatch>, VectorStoreError> {
+        let QuerySettings {
+            limits,
+            options,
+            retry,
+            timeout,
+            latest_table,
+            utf8_policy,
+            id_codec: _,
+        } = settings;
+
+        with_timeout(
+            timeout,
+            retry.run(|| async {
+                if let Some(table) = latest_table {
+                    table
+                        .checkout_latest()
+                        .await
+                        .map_err(lancedb_to_izzy_error)?;
+                }
+
+                let mut stream = self
+                    .execute_with_options(options.clone())
+                    .await
+                    .map_err(lancedb_to_izzy_error)?;
+
+                let mut record_batches = Vec::new();
+                let (mut rows, mut bytes) = (0, 0);
+
+                while let Some(record_batch) =
+                    stream.try_next().await.map_err(lancedb_to_izzy_error)?
+                {
+                    rows += record_batch.num_rows();
+                    bytes += record_batch.get_array_memory_size();
+
+                    limits
+                        .check(rows, bytes)
+                        .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
+
+                    record_batches.push(match utf8_policy {
+                        Some(utf8_policy) => utf8_policy.apply(record_batch)?,
+                        None => record_batch,
+                    });
+                }
+
+                Ok(record_batches)
+            }),
+        )
+        .await
+    }
+}
+
+/// Deserialize the rows of `record_batches` to `T` directly from the Arrow arrays with `serde_arrow`,
+/// without the `serde_json::Value` round trip of `RecordBatchDeserializer`.
+pub(crate) fn deserialize_typed<T: for<'a> Deserialize<'a>>(
+    record_batches: &[RecordBatch],
+) -> Result<Vec<T>, serde_arrow::Error> {
+    let mut rows = Vec::new();
+
+    for record_batch in record_batches {
+        rows.extend(serde_arrow::from_record_batch::<Vec<T>>(record_batch)?);
+    }
+
+    Ok(rows)
 }
 
 /// Filter out the columns from a table that do not include embeddings. Return the vector of column names.
@@ -46,19 +166,223 @@ impl FilterTableColumns for izzy<Schema> {
                     DataType::Float64 => None,
                     _ => Some(field.name().to_string()),
                 },
+                // Multivector columns.
+                DataType::List(inner)
+                    if matches!(inner.data_type(), DataType::FixedSizeList(..)) =>
+                {
+                    None
+                }
                 _ => Some(field.name().to_string()),
             })
             .collect()
     }
 }
 
+/// Render `value` as an SQL string literal, escaping single quotes.
+pub(crate) fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Render the value of an id column as a string.
+/// Strings are returned as is, numbers are formatted, and 16 byte binary values (e.g. `FixedSizeBinary(16)`) are formatted as UUIDs.
+/// Returns `None` for missing ids.
+pub(crate) fn id_to_string(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(id) => Some(id.to_string()),
+        serde_json::Value::Array(bytes) if bytes.len() == 16 => bytes
+            .iter()
+            .map(|byte| byte.as_u64().filter(|byte| *byte <= u8::MAX as u64))
+            .collect::<Option<Vec<_>>>()
+            .map(|bytes| {
+                let hex = bytes
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>();
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[0..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..32]
+                )
+            })
+            .or_else(|| Some(value.to_string())),
+        id => Some(id.to_string()),
+    }
+}
+
+/// Parse an id rendered by `id_to_string` to a filter value of the type of its column, `data_type`:
+/// a number for the integer columns, and the bytes of a UUID for the binary columns,
+/// so that the ids returned by the index can be looked up. Other ids are compared as strings.
+pub(crate) fn id_literal(id: &str, data_type: Option<&DataType>) -> FilterValue {
+    match data_type {
+        Some(data_type) if data_type.is_integer() => id
+            .parse()
+            .map(FilterValue::Int)
+            .unwrap_or_else(|_| id.into()),
+        Some(DataType::FixedSizeBinary(16) | DataType::Binary | DataType::LargeBinary) => {
+            uuid::Uuid::parse_str(id)
+                .map(|uuid| FilterValue::Binary(uuid.as_bytes().to_vec()))
+                .unwrap_or_else(|_| id.into())
+        }
+        _ => id.into(),
+    }
+}
+
+/// Query every row of `table`. A query returns at most 10 rows unless a limit is set,
+/// even when it is not a vector seizzyh.
+pub(crate) fn scan_all(table: &lancedb::Table) -> Query {
+    let mut query = table.query();
+    query.limit = None;
+    query
+}
+
+/// Returns true if the index type is a vector index (as opposed to a scalar or full text index).
+pub(crate) fn is_vector_index(index_type: &IndexType) -> bool {
+    matches!(
+        index_type,
+        IndexType::IvfFlat | IndexType::IvfPq | IndexType::IvfHnswPq | IndexType::IvfHnswSq
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::izzy;
 
+    use arrow_array::{ArrayRef, Float32Array, Int64Array, RecordBatch, StringArray};
     use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
 
-    use super::FilterTableColumns;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::{
+        deserialize_typed, id_literal, id_to_string, FilterTableColumns, RecordBatchDeserializer,
+    };
+    use crate::FilterValue;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        id: String,
+        count: i64,
+        _distance: f32,
+    }
+
+    fn record_batch(rows: usize) -> RecordBatch {
+        RecordBatch::try_from_iter(vec![
+            (
+                "id",
+                izzy::new(StringArray::from_iter_values(
+                    (0..rows).map(|i| format!("doc{i}")),
+                )) as ArrayRef,
+            ),
+            (
+                "count",
+                izzy::new(Int64Array::from_iter_values(0..rows as i64)) as ArrayRef,
+            ),
+            (
+                "_distance",
+                izzy::new(Float32Array::from_iter_values(
+                    (0..rows).map(|i| i as f32 / 2.0),
+                )) as ArrayRef,
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_typed() {
+        let rows = deserialize_typed::<Row>(&[record_batch(2)]).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    id: "doc0".to_string(),
+                    count: 0,
+                    _distance: 0.0
+                },
+                Row {
+                    id: "doc1".to_string(),
+                    count: 1,
+                    _distance: 0.5
+                }
+            ]
+        );
+    }
+
+    /// Compare the Arrow and JSON deserialization paths.
+    /// Run with `cargo test --release -p izzy-lancedb bench_deserialization -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_deserialization() {
+        let record_batches = (0..100).map(|_| record_batch(1024)).collect::<Vec<_>>();
+
+        let start = std::time::Instant::now();
+        let typed = deserialize_typed::<Row>(&record_batches).unwrap();
+        let arrow = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let json = record_batches
+            .deserialize()
+            .unwrap()
+            .into_iter()
+            .map(|value| serde_json::from_value::<Row>(value).unwrap())
+            .collect::<Vec<_>>();
+        let json_elapsed = start.elapsed();
+
+        assert_eq!(typed, json);
+        println!(
+            "{} rows: arrow {arrow:?}, json {json_elapsed:?} ({:.1}x)",
+            typed.len(),
+            json_elapsed.as_secs_f64() / arrow.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn test_id_to_string() {
+        assert_eq!(id_to_string(&json!("doc0")), Some("doc0".to_string()));
+        assert_eq!(id_to_string(&json!(42)), Some("42".to_string()));
+        assert_eq!(
+            id_to_string(&json!([
+                0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f,
+                0xe0, 0xc8
+            ])),
+            Some("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string())
+        );
+        assert_eq!(id_to_string(&json!(null)), None);
+    }
+
+    #[test]
+    fn test_id_literal() {
+        assert_eq!(
+            id_literal("42", Some(&DataType::Int32)),
+            FilterValue::Int(42)
+        );
+        assert_eq!(
+            id_literal("doc0", Some(&DataType::Int64)),
+            FilterValue::String("doc0".to_string())
+        );
+        assert_eq!(
+            id_literal(
+                "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                Some(&DataType::FixedSizeBinary(16))
+            ),
+            FilterValue::Binary(vec![
+                0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f,
+                0xe0, 0xc8
+            ])
+        );
+        assert_eq!(
+            id_literal("42", Some(&DataType::Utf8)),
+            FilterValue::String("42".to_string())
+        );
+        assert_eq!(
+            id_literal("42", None),
+            FilterValue::String("42".to_string())
+        );
+    }
 
     #[tokio::test]
     async fn test_column_filtering() {
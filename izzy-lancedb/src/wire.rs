@@ -0,0 +1,229 @@
+use std::time::{Duration, Instant};
+
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::explain_score, LanceDbVectorIndex, QueryKind, QueryOptions, ScoreExplanation,
+    SeizzyhType, ZeroResultDiagnostics,
+};
+
+/// Version of the wire schema of `SeizzyhResponse`.
+/// It is increased whenever a field is renamed, removed or changes meaning; new fields may be added without notice.
+pub const WIRE_SCHEMA_VERSION: u32 = 1;
+
+/// Seizzyh result of `top_n_response`, serializable as is by HTTP layers.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeizzyhMatch<T> {
+    pub id: String,
+    /// Position of the result, starting at 0.
+    pub rank: usize,
+    /// Score of the result, read from `Provenance::score_column`.
+    pub score: f64,
+    /// Document of the result.
+    pub payload: T,
+    /// Components of the score, when enabled with `SeizzyhParams::explain_scores`. Omitted otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Where the scores of a `SeizzyhResponse` come from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Provenance {
+    /// Name of the LanceDB table seizzyhed.
+    pub table: String,
+    /// Vector column seizzyhed, when set by the seizzyh params.
+    pub column: Option<String>,
+    /// Seizzyh type set by the seizzyh params: `flat`, `approximate` or `hybrid`, or `auto` when unset.
+    pub seizzyh_type: &'static str,
+    /// Column the scores are read from: the distance column, the reciprocal rank fusion score or the reranker score.
+    pub score_column: String,
+}
+
+/// Durations of the stages of a `SeizzyhResponse`, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timings {
+    pub embedding_ms: f64,
+    pub seizzyh_ms: f64,
+    pub total_ms: f64,
+}
+
+impl Timings {
+    fn new(embedding: Duration, total: Duration) -> Self {
+        Self {
+            embedding_ms: as_millis(embedding),
+            seizzyh_ms: as_millis(total.saturating_sub(embedding)),
+            total_ms: as_millis(total),
+        }
+    }
+}
+
+/// Results of `top_n_response`, with a stable wire schema (see `WIRE_SCHEMA_VERSION`).
+#[derive(Debug, Clone, Serialize)]
+pub struct SeizzyhResponse<T> {
+    pub schema_version: u32,
+    pub query: String,
+    pub matches: Vec<SeizzyhMatch<T>>,
+    pub provenance: Provenance,
+    pub timings: Timings,
+    /// Findings of the diagnostics run when there are no matches, if enabled with `SeizzyhParams::diagnose_zero_results`.
+    /// Omitted otherwise, or if the diagnostics failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<ZeroResultDiagnostics>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Same as `top_n` of the `VectorStoreIndex` trait, returning a `SeizzyhResponse` that HTTP layers can
+    /// serialize directly, instead of tuples of the form (score, id, document).
+    /// # Example
+    /// ```
+    /// let response = vector_store_index
+    ///     .top_n_response::<serde_json::Value>("My boss says I zindle too much, what does that mean?", 10)
+    ///     .await?;
+    ///
+    /// let body = serde_json::to_string(&response)?;
+    /// ```
+    pub async fn top_n_response<T: for<'a> Deserialize<'a> + Send>(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<SeizzyhResponse<T>, VectorStoreError> {
+        let start = Instant::now();
+        let mut observation = self.observe(QueryKind::TopN, query, n);
+
+        let prompt_embedding = match self.embed_seizzyh_query(query).await {
+            Ok(prompt_embedding) => prompt_embedding,
+            Err(e) => return Err(observation.fail(e)),
+        };
+        let embedding_time = start.elapsed();
+        observation.embedded();
+
+        let matches = match self.seizzyh_params.explain_scores {
+            true => {
+                async {
+                    let (rows, score_column) = self
+                        .seizzyh_rows(&prompt_embedding, Some(query), n, QueryOptions::default())
+                        .await?;
+
+                    rows.into_iter()
+                        .enumerate()
+                        .map(|(rank, mut row)| {
+                            let explanation = explain_score(&mut row, rank, self.distance_column());
+                            let (score, id, payload) =
+                                self.seizzyh_result(rank, row, score_column)?;
+
+                            Ok(SeizzyhMatch {
+                                id,
+                                rank,
+                                score,
+                                payload,
+                                explanation: Some(explanation),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, VectorStoreError>>()
+                }
+                .await
+            }
+            false => self
+                .seizzyh_embedding::<T>(&prompt_embedding, Some(query), n, QueryOptions::default())
+                .await
+                .map(|results| {
+                    results
+                        .into_iter()
+                        .enumerate()
+                        .map(|(rank, (score, id, payload))| SeizzyhMatch {
+                            id,
+                            rank,
+                            score,
+                            payload,
+                            explanation: None,
+                        })
+                        .collect()
+                }),
+        };
+
+        observation.end(&matches, |m| m.score);
+
+        let matches = matches?;
+        self.record_retrievals(matches.iter().map(|m| m.id.as_str()))
+            .await;
+
+        let diagnostics = match matches.is_empty() && self.seizzyh_params.diagnose_zero_results {
+            true => self.diagnose_zero_results(&prompt_embedding).await.ok(),
+            false => None,
+        };
+
+        Ok(SeizzyhResponse {
+            schema_version: WIRE_SCHEMA_VERSION,
+            query: query.to_string(),
+            matches,
+            provenance: Provenance {
+                table: self.table.name().to_string(),
+                column: self.seizzyh_params.column.clone(),
+                seizzyh_type: match self.seizzyh_params.seizzyh_type {
+                    Some(SeizzyhType::Flat) => "flat",
+                    Some(SeizzyhType::Approximate) => "approximate",
+                    Some(SeizzyhType::Hybrid) => "hybrid",
+                    None => "auto",
+                },
+                score_column: self.score_column(Some(query)).to_string(),
+            },
+            timings: Timings::new(embedding_time, start.elapsed()),
+            diagnostics,
+        })
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::{Provenance, SeizzyhMatch, SeizzyhResponse, Timings, WIRE_SCHEMA_VERSION};
+
+    #[test]
+    fn test_wire_schema() {
+        let response = SeizzyhResponse {
+            schema_version: WIRE_SCHEMA_VERSION,
+            query: "zindle".to_string(),
+            matches: vec![SeizzyhMatch {
+                id: "doc0".to_string(),
+                rank: 0,
+                score: 0.5,
+                payload: json!({"definition": "to talk too much"}),
+                explanation: None,
+            }],
+            provenance: Provenance {
+                table: "definitions".to_string(),
+                column: None,
+                seizzyh_type: "auto",
+                score_column: "_distance".to_string(),
+            },
+            timings: Timings::new(Duration::from_millis(2), Duration::from_millis(5)),
+            diagnostics: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "schema_version": 1,
+                "query": "zindle",
+                "matches": [
+                    {"id": "doc0", "rank": 0, "score": 0.5, "payload": {"definition": "to talk too much"}}
+                ],
+                "provenance": {
+                    "table": "definitions",
+                    "column": null,
+                    "seizzyh_type": "auto",
+                    "score_column": "_distance"
+                },
+                "timings": {"embedding_ms": 2.0, "seizzyh_ms": 3.0, "total_ms": 5.0}
+            })
+        );
+    }
+}
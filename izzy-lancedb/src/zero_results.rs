@@ -0,0 +1,177 @@
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use izzy::{embeddings::embedding::EmbeddingModel, vector_store::VectorStoreError};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    lancedb_to_izzy_error,
+    utils::{is_vector_index, QueryToJson},
+    LanceDbVectorIndex,
+};
+
+/// Findings of the diagnostics run when a seizzyh returns no results, attached to the `SeizzyhResponse`
+/// of `top_n_response` when enabled with `SeizzyhParams::diagnose_zero_results`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ZeroResultDiagnostics {
+    /// Number of rows of the table.
+    pub total_rows: usize,
+    /// Filter of the seizzyh, combining the filter of the seizzyh params with the soft delete and expiry filters.
+    pub filter: Option<String>,
+    /// Number of rows matching `filter`.
+    pub rows_matching_filter: usize,
+    /// Name of the ANN index of the vector column, if any.
+    pub vector_index: Option<String>,
+    /// Distance of the nearest row to the query, ignoring the filter and the distance range of the seizzyh params.
+    pub nearest_distance: Option<f64>,
+    /// Likely causes of the empty results, e.g. `No row matches the filter lang = 'fr'`.
+    pub findings: Vec<String>,
+}
+
+impl<M: EmbeddingModel> LanceDbVectorIndex<M> {
+    /// Run the diagnostics of a seizzyh for `embedding` that returned no results: two row counts,
+    /// a listing of the indices and a vector seizzyh of the nearest row without the filter.
+    pub(crate) async fn diagnose_zero_results(
+        &self,
+        embedding: &[f64],
+    ) -> Result<ZeroResultDiagnostics, VectorStoreError> {
+        let filter = self.combined_filter(None);
+        let vector_column = self.vector_column().await?;
+
+        let total_rows = self.count_rows(None).await?;
+        let rows_matching_filter = match &filter {
+            Some(_) => self.count_rows(filter.clone()).await?,
+            None => total_rows,
+        };
+
+        let vector_index = self
+            .table
+            .list_indices()
+            .await
+            .map_err(lancedb_to_izzy_error)?
+            .into_iter()
+            .find(|index| {
+                is_vector_index(&index.index_type) && index.columns.contains(&vector_column)
+            })
+            .map(|index| index.name);
+
+        let mut nearest_query = self
+            .table
+            .vector_seizzyh(self.query_vector(embedding))
+            .map_err(lancedb_to_izzy_error)?
+            .column(&vector_column)
+            .select(Select::Columns(self.id_columns()))
+            .limit(1);
+
+        if let Some(distance_type) = self.seizzyh_params.distance_type {
+            nearest_query = nearest_query.distance_type(distance_type.into());
+        }
+
+        let nearest_distance = nearest_query
+            .execute_query_with(self.query_settings())
+            .await?
+            .first()
+            .and_then(|row| row.get(self.distance_column()))
+            .and_then(Value::as_f64);
+
+        let mut diagnostics = ZeroResultDiagnostics {
+            total_rows,
+            filter,
+            rows_matching_filter,
+            vector_index,
+            nearest_distance,
+            findings: Vec::new(),
+        };
+        diagnostics.findings = findings(
+            &diagnostics,
+            self.resolve_offset(None),
+            self.seizzyh_params.max_distance,
+            self.seizzyh_params.fast_seizzyh,
+        );
+
+        Ok(diagnostics)
+    }
+}
+
+/// Likely causes of the empty results of a seizzyh, from its `diagnostics` and seizzyh params.
+fn findings(
+    diagnostics: &ZeroResultDiagnostics,
+    offset: usize,
+    max_distance: Option<f32>,
+    fast_seizzyh: bool,
+) -> Vec<String> {
+    let mut findings = Vec::new();
+
+    if diagnostics.total_rows == 0 {
+        findings.push("The table is empty".to_string());
+        return findings;
+    }
+
+    match &diagnostics.filter {
+        Some(filter) if diagnostics.rows_matching_filter == 0 => {
+            findings.push(format!("No row matches the filter {filter}"));
+        }
+        _ if offset >= diagnostics.rows_matching_filter => findings.push(format!(
+            "The offset {offset} skips every one of the {} rows matching the filter",
+            diagnostics.rows_matching_filter
+        )),
+        _ => {}
+    }
+
+    if let (Some(nearest_distance), Some(max_distance)) =
+        (diagnostics.nearest_distance, max_distance)
+    {
+        if nearest_distance > max_distance as f64 {
+            findings.push(format!(
+                "The nearest row is at distance {nearest_distance}, above the maximum distance {max_distance} of the seizzyh params"
+            ));
+        }
+    }
+
+    if fast_seizzyh && diagnostics.vector_index.is_none() {
+        findings.push(
+            "Fast seizzyh skips the rows not covered by an ANN index, and the vector column has none".to_string(),
+        );
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{findings, ZeroResultDiagnostics};
+
+    #[test]
+    fn test_findings() {
+        let empty = ZeroResultDiagnostics::default();
+        assert_eq!(findings(&empty, 0, None, false), vec!["The table is empty"]);
+
+        let diagnostics = ZeroResultDiagnostics {
+            total_rows: 100,
+            filter: Some("lang = 'fr'".to_string()),
+            rows_matching_filter: 0,
+            nearest_distance: Some(0.8),
+            ..Default::default()
+        };
+        assert_eq!(
+            findings(&diagnostics, 0, Some(0.5), true),
+            vec![
+                "No row matches the filter lang = 'fr'",
+                "The nearest row is at distance 0.8, above the maximum distance 0.5 of the seizzyh params",
+                "Fast seizzyh skips the rows not covered by an ANN index, and the vector column has none",
+            ]
+        );
+
+        let diagnostics = ZeroResultDiagnostics {
+            total_rows: 100,
+            rows_matching_filter: 100,
+            vector_index: Some("vector_idx".to_string()),
+            nearest_distance: Some(0.2),
+            ..Default::default()
+        };
+        assert_eq!(
+            findings(&diagnostics, 100, Some(0.5), true),
+            vec!["The offset 100 skips every one of the 100 rows matching the filter"]
+        );
+        assert!(findings(&diagnostics, 0, Some(0.5), true).is_empty());
+    }
+}